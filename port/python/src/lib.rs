@@ -0,0 +1,87 @@
+// Python bindings for the Rust port, built into a wheel with maturin.
+
+use khmer_segmenter::kdict::KDict;
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+use khmer_segmenter::normalization::khmer_normalize;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+/// KhmerSegmenter(path=None, *, data=None)
+///
+/// Loads a KDIC dictionary from `path` (memory-mapped) or from the `data`
+/// bytes. With neither, every input comes back as a single token. The
+/// object is immutable and safe to share between threads.
+#[pyclass(name = "KhmerSegmenter", module = "khmer_segmenter", frozen)]
+struct PySegmenter {
+    inner: KhmerSegmenter,
+}
+
+impl PySegmenter {
+    // The tokens of `segment`, as owned strings.
+    fn tokens(&self, text: &str) -> Vec<String> {
+        let text = khmer_normalize(text);
+        self.inner.segment_ranges(&text).into_iter().map(|r| text[r].to_string()).collect()
+    }
+
+    fn segment_one(&self, text: &str, sep: Option<&str>) -> Segmented {
+        match sep {
+            Some(sep) => Segmented::Joined(self.inner.segment(text, Some(sep))),
+            None => Segmented::Tokens(self.tokens(text)),
+        }
+    }
+}
+
+enum Segmented {
+    Tokens(Vec<String>),
+    Joined(String),
+}
+
+impl IntoPy<PyObject> for Segmented {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            Segmented::Tokens(tokens) => tokens.into_py(py),
+            Segmented::Joined(joined) => joined.into_py(py),
+        }
+    }
+}
+
+#[pymethods]
+impl PySegmenter {
+    #[new]
+    #[pyo3(signature = (path=None, *, data=None))]
+    fn new(path: Option<&str>, data: Option<&[u8]>) -> PyResult<Self> {
+        let kdict = match (path, data) {
+            (Some(_), Some(_)) => return Err(PyValueError::new_err("pass either a path or data, not both")),
+            (Some(path), None) => Some(KDict::load(path)?),
+            (None, Some(data)) => Some(KDict::from_bytes(data.to_vec())?),
+            (None, None) => None,
+        };
+        Ok(Self { inner: KhmerSegmenter::new_with_dict(kdict, SegmenterConfig::default()) })
+    }
+
+    /// segment(text, sep=None)
+    ///
+    /// The tokens of `text` as a list of str, or, when `sep` is given, one
+    /// str with `sep` between the tokens. The GIL is released meanwhile.
+    #[pyo3(signature = (text, sep=None))]
+    fn segment(&self, py: Python<'_>, text: &str, sep: Option<&str>) -> Segmented {
+        py.allow_threads(|| self.segment_one(text, sep))
+    }
+
+    /// segment_batch(texts, sep=None)
+    ///
+    /// `segment` applied to every item of `texts`, spread over the rayon
+    /// thread pool with the GIL released. Results keep the input order.
+    #[pyo3(signature = (texts, sep=None))]
+    fn segment_batch(&self, py: Python<'_>, texts: Vec<String>, sep: Option<&str>) -> Vec<Segmented> {
+        py.allow_threads(|| texts.par_iter().map(|text| self.segment_one(text, sep)).collect())
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "khmer_segmenter")]
+fn module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySegmenter>()?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+# Run after `maturin develop`: pytest tests
+from pathlib import Path
+
+import pytest
+
+from khmer_segmenter import KhmerSegmenter
+
+# The regression dictionary checked in with the Rust port
+TINY_KDICT = Path(__file__).resolve().parents[2] / "rust" / "tests" / "data" / "tiny.kdict"
+
+TEXT = "ខ្ញុំស្រឡាញ់ប្រទេសកម្ពុជា"
+TOKENS = ["ខ្ញុំ", "ស្រឡាញ់", "ប្រទេស", "កម្ពុជា"]
+
+
+@pytest.fixture(scope="module")
+def seg():
+    return KhmerSegmenter(str(TINY_KDICT))
+
+
+def test_segment_returns_tokens(seg):
+    assert seg.segment(TEXT) == TOKENS
+
+
+def test_segment_joins_with_sep(seg):
+    assert seg.segment(TEXT, sep=" | ") == " | ".join(TOKENS)
+
+
+def test_data_matches_path(seg):
+    from_bytes = KhmerSegmenter(data=TINY_KDICT.read_bytes())
+    assert from_bytes.segment(TEXT) == seg.segment(TEXT)
+
+
+def test_segment_batch_keeps_order(seg):
+    texts = [TEXT, "", "ខ្ញុំ"]
+    assert seg.segment_batch(texts) == [TOKENS, [], ["ខ្ញុំ"]]
+
+
+def test_path_and_data_together_is_an_error():
+    with pytest.raises(ValueError):
+        KhmerSegmenter(str(TINY_KDICT), data=b"")
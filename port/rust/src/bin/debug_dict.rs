@@ -1,7 +1,27 @@
+use khmer_segmenter::kdict::build::build_kdict;
 use khmer_segmenter::kdict::KDict;
+use std::io::Write;
 
+const DEFAULT_PATH: &str = "port/common/khmer_dictionary.kdict";
+
+// Usage: debug_dict [path] [--dump]
+//        debug_dict --build <words.tsv> <out.kdict> <default_cost> <unknown_cost>
+// Prints the header; `--dump` adds every stored word and its cost, one
+// `word<TAB>cost` line each, in table order. `--build` compiles a
+// `word<TAB>cost` list (the `--dump` format) into a dictionary instead.
 fn main() {
-    let kdict = KDict::load("port/common/khmer_dictionary.kdict").unwrap();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|a| a == "--build") {
+        build(&args[1..]);
+        return;
+    }
+    let dump = args.iter().any(|a| a == "--dump");
+    let path = args.iter().find(|a| !a.starts_with("--")).map_or(DEFAULT_PATH, String::as_str);
+
+    let kdict = KDict::load(path).unwrap_or_else(|e| {
+        eprintln!("Error: cannot load {}: {}", path, e);
+        std::process::exit(1);
+    });
     let header = unsafe { &*kdict.header };
     let magic = std::str::from_utf8(&header.magic).unwrap_or("INVALID");
     let num_entries = header.num_entries;
@@ -16,4 +36,47 @@ fn main() {
     println!("Default Cost: {}", default_cost);
     println!("Unknown Cost: {}", unknown_cost);
     println!("Max Word Length: {}", max_word_length);
+
+    if dump {
+        // Buffered, and quiet when the reader goes away (`| head`)
+        let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+        for (word, cost) in kdict.words() {
+            if writeln!(out, "{}\t{}", String::from_utf8_lossy(word), cost).is_err() { return; }
+        }
+        let _ = out.flush();
+    }
+}
+
+fn build(args: &[String]) {
+    let [words_path, out_path, default_cost, unknown_cost] = args else {
+        eprintln!("Usage: debug_dict --build <words.tsv> <out.kdict> <default_cost> <unknown_cost>");
+        std::process::exit(2);
+    };
+    let parse_cost = |name: &str, value: &str| -> f32 {
+        value.parse().unwrap_or_else(|_| {
+            eprintln!("Error: {} must be a number, got {:?}", name, value);
+            std::process::exit(2);
+        })
+    };
+    let (default_cost, unknown_cost) = (parse_cost("default_cost", default_cost), parse_cost("unknown_cost", unknown_cost));
+
+    let text = std::fs::read_to_string(words_path).unwrap_or_else(|e| {
+        eprintln!("Error: cannot read {}: {}", words_path, e);
+        std::process::exit(1);
+    });
+    let mut words: Vec<(String, f32)> = Vec::new();
+    for (n, line) in text.lines().enumerate().filter(|(_, l)| !l.is_empty()) {
+        let entry = line.split_once('\t').and_then(|(word, cost)| Some((word.to_string(), cost.parse().ok()?)));
+        let Some(entry) = entry else {
+            eprintln!("Error: {}:{}: expected word<TAB>cost, got {:?}", words_path, n + 1, line);
+            std::process::exit(1);
+        };
+        words.push(entry);
+    }
+    let bytes = build_kdict(&words, default_cost, unknown_cost);
+    if let Err(e) = std::fs::write(out_path, bytes) {
+        eprintln!("Error: cannot write {}: {}", out_path, e);
+        std::process::exit(1);
+    }
+    println!("Wrote {} words to {}", words.len(), out_path);
 }
@@ -0,0 +1,94 @@
+// C ABI over `KhmerSegmenter`, so C code written against `port/c` can link
+// this port instead. Build the shared library with
+// `cargo rustc --release --lib --features ffi --crate-type cdylib`.
+//
+// No panic crosses the boundary: bad pointers, invalid UTF-8 and panics all
+// come back as NULL.
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use crate::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+
+// `s` as UTF-8; `None` when it is NULL or not valid UTF-8.
+unsafe fn str_arg<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() { return None; }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+/// Loads the KDIC dictionary at `path` with the default config. Returns
+/// NULL if `path` is NULL or not UTF-8, or the dictionary fails to load.
+/// Release the segmenter with `khmer_segmenter_free`.
+///
+/// # Safety
+/// `path` must be NULL or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn khmer_segmenter_new(path: *const c_char) -> *mut KhmerSegmenter {
+    let seg = catch_unwind(|| {
+        let path = str_arg(path)?;
+        KhmerSegmenter::new(Some(path), SegmenterConfig::default()).ok()
+    });
+    match seg {
+        Ok(Some(seg)) => Box::into_raw(Box::new(seg)),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// `KhmerSegmenter::segment` as a newly allocated NUL-terminated string,
+/// to be released with `khmer_segmenter_free_string` (not `free`). A NULL
+/// `separator` means U+200B ZERO WIDTH SPACE, as in the C port. Returns
+/// NULL if `seg` or `text` is NULL, either string is not UTF-8, or
+/// segmentation panics.
+///
+/// # Safety
+/// `seg` must be NULL or a live pointer from `khmer_segmenter_new`; `text`
+/// and `separator` must be NULL or point to NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn khmer_segmenter_segment(
+    seg: *const KhmerSegmenter,
+    text: *const c_char,
+    separator: *const c_char,
+) -> *mut c_char {
+    let Some(seg) = seg.as_ref() else { return ptr::null_mut() };
+    let Some(text) = str_arg(text) else { return ptr::null_mut() };
+    let separator = if separator.is_null() {
+        None
+    } else {
+        match str_arg(separator) {
+            Some(sep) => Some(sep),
+            None => return ptr::null_mut(),
+        }
+    };
+    let out = catch_unwind(AssertUnwindSafe(|| seg.segment(text, separator)));
+    // The input had no interior NUL and segmentation adds none
+    match out.map(CString::new) {
+        Ok(Ok(s)) => s.into_raw(),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Releases a string returned by `khmer_segmenter_segment`. NULL is
+/// ignored.
+///
+/// # Safety
+/// `s` must be NULL or a string from `khmer_segmenter_segment` that has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn khmer_segmenter_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Releases a segmenter from `khmer_segmenter_new`. NULL is ignored.
+///
+/// # Safety
+/// `seg` must be NULL or a pointer from `khmer_segmenter_new` that has not
+/// been freed yet, and no other thread may be using it.
+#[no_mangle]
+pub unsafe extern "C" fn khmer_segmenter_free(seg: *mut KhmerSegmenter) {
+    if !seg.is_null() {
+        drop(Box::from_raw(seg));
+    }
+}
@@ -0,0 +1,43 @@
+// Token interning for `KhmerSegmenter::segment_interned`.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Maps token text to a shared `Arc<str>`. Implement this to plug in an
+/// existing string cache; `HashInterner` is the default.
+pub trait Interner {
+    fn intern(&mut self, token: &str) -> Arc<str>;
+}
+
+/// Keeps one `Arc<str>` per distinct token seen, so repeated tokens across
+/// calls share a single allocation.
+#[derive(Debug, Default)]
+pub struct HashInterner {
+    tokens: HashSet<Arc<str>>,
+}
+
+impl HashInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct tokens stored.
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+impl Interner for HashInterner {
+    fn intern(&mut self, token: &str) -> Arc<str> {
+        if let Some(shared) = self.tokens.get(token) {
+            return shared.clone();
+        }
+        let shared: Arc<str> = Arc::from(token);
+        self.tokens.insert(shared.clone());
+        shared
+    }
+}
@@ -1,5 +1,10 @@
+#[cfg(not(target_arch = "wasm32"))]
 use memmap2::Mmap;
+#[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
+use std::sync::Arc;
+
+pub mod build;
 
 #[repr(C, packed)]
 #[derive(Debug, Copy, Clone)]
@@ -27,6 +32,8 @@ pub enum DataSource {
     #[cfg(not(target_arch = "wasm32"))]
     Mmap(Mmap),
     Owned(Vec<u8>),
+    /// An image shared with other `KDict`s (`KDict::from_arc`).
+    Shared(Arc<[u8]>),
 }
 
 impl DataSource {
@@ -35,6 +42,7 @@ impl DataSource {
             #[cfg(not(target_arch = "wasm32"))]
             DataSource::Mmap(m) => m.as_ptr(),
             DataSource::Owned(v) => v.as_ptr(),
+            DataSource::Shared(a) => a.as_ptr(),
         }
     }
 
@@ -43,10 +51,73 @@ impl DataSource {
             #[cfg(not(target_arch = "wasm32"))]
             DataSource::Mmap(m) => m.len(),
             DataSource::Owned(v) => v.len(),
+            DataSource::Shared(a) => a.len(),
+        }
+    }
+
+    // Bytes reserved for the image: the mapping length, the `Vec`'s
+    // capacity (which can exceed its length) or the shared slice's length.
+    fn footprint(&self) -> usize {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            DataSource::Mmap(m) => m.len(),
+            DataSource::Owned(v) => v.capacity(),
+            DataSource::Shared(a) => a.len(),
         }
     }
 }
 
+/// Where a dictionary's bytes go, from `KDict::memory_breakdown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryBreakdown {
+    pub header: usize,
+    /// The hash table of `KDictEntry`s.
+    pub table: usize,
+    /// The category, flag and frequency arrays (versions 2 to 4), 0 for
+    /// version 1.
+    pub slot_arrays: usize,
+    /// The string pool, including any trailing bytes of the image.
+    pub pool: usize,
+}
+
+/// Morphological role bits of a dictionary entry (version 3 flags array).
+/// The entry attaches to the token after it.
+pub const FLAG_PREFIX: u8 = 1 << 0;
+/// The entry attaches to the token before it.
+pub const FLAG_SUFFIX: u8 = 1 << 1;
+/// The entry is also a word on its own: as an affix it only attaches to a
+/// dictionary word, never to an unknown fragment.
+pub const FLAG_STANDALONE: u8 = 1 << 2;
+
+/// A loaded `.kdict` image, used in place without parsing.
+///
+/// # Pointer invariants
+///
+/// The raw pointers all point into `source`, which the `KDict` owns, so they
+/// stay valid for as long as the `KDict` lives; moving the `KDict` does not
+/// move the mapped or heap-allocated bytes. After a successful load:
+/// - `header` points at the 32-byte header at offset 0 (packed, align 1);
+/// - `table` points at `table_size` packed `KDictEntry`s, all inside `source`;
+/// - `categories` is null (version 1) or points at `table_size` bytes inside
+///   `source`;
+/// - `flags` is null (versions 1 and 2) or points at the `table_size` bytes
+///   after the categories;
+/// - `frequencies` is null (versions 1 to 3) or points at the
+///   `table_size * 4` bytes after the flags;
+/// - `string_pool` points at the first byte after the table (and categories,
+///   flags and frequencies),
+///   possibly one past the end of `source` when the pool is empty;
+/// - `table_mask` is `table_size - 1`.
+///
+/// Not checked by the loader, which does not walk the table: that each
+/// entry's `name_offset` lies inside the pool and is followed by a NUL, and
+/// that the table has an empty slot to end a failed probe. Lookups stay
+/// inside the pool regardless (a bad entry just never matches), but a full
+/// table makes them loop; call `validate` on dictionaries not produced by
+/// `prepare_data.py` or `KDictBuilder`.
+///
+/// The pointers are never written through, which is what makes the `Send`
+/// and `Sync` impls below sound.
 pub struct KDict {
     // Keep source alive. Pointers below point into this source.
     #[allow(dead_code)]
@@ -54,10 +125,29 @@ pub struct KDict {
     pub header: *const KDictHeader,
     pub table: *const KDictEntry,
     pub string_pool: *const u8,
+    /// Per-slot category bytes (version 2), null for version 1 files.
+    pub categories: *const u8,
+    /// Per-slot `FLAG_*` bits (version 3), null for older files.
+    pub flags: *const u8,
+    /// Per-slot corpus frequencies (version 4), little-endian `u32`s, null
+    /// for older files.
+    pub frequencies: *const u8,
     pub table_mask: u32,
+    // Bytes from `string_pool` to the end of `source`
+    pool_len: usize,
 }
 
 impl KDict {
+    /// Memory-maps the dictionary at `path`.
+    ///
+    /// # Errors
+    /// - Any error from opening or mapping the file (`NotFound`,
+    ///   `PermissionDenied`, ...), passed through unchanged.
+    /// - `InvalidData` when the contents fail the checks of `from_bytes`.
+    ///
+    /// The file must not be modified or truncated while the `KDict` is alive;
+    /// the mapping is read directly and changes under it are undefined
+    /// behavior.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn load(path: &str) -> std::io::Result<Self> {
         let file = File::open(path)?;
@@ -65,10 +155,43 @@ impl KDict {
         Self::from_source(DataSource::Mmap(mmap))
     }
 
+    /// There is no filesystem on wasm32: always fails with `Unsupported`.
+    /// Fetch the image yourself and use `from_bytes`.
+    #[cfg(target_arch = "wasm32")]
+    pub fn load(path: &str) -> std::io::Result<Self> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!("cannot load {}: no file access on wasm32, use KDict::from_bytes", path),
+        ))
+    }
+
+    /// Takes ownership of an in-memory dictionary image.
+    ///
+    /// # Errors
+    /// `InvalidData`, with one of these messages:
+    /// - `File too small`: shorter than the 32-byte header;
+    /// - `Invalid magic`: does not start with `KDIC`;
+    /// - `Unsupported version N`: version other than 1 to 4;
+    /// - `Empty table`: `table_size` is 0, which leaves nothing to probe;
+    /// - `Table size N is not a power of two`: lookups mask hashes with
+    ///   `table_size - 1`;
+    /// - `File truncated`: the table (and category, flag and frequency
+    ///   arrays) run past the end.
     pub fn from_bytes(bytes: Vec<u8>) -> std::io::Result<Self> {
         Self::from_source(DataSource::Owned(bytes))
     }
 
+    /// Uses an image shared through an `Arc`, without copying it: every
+    /// `KDict` made from clones of `data` reads the same bytes, and the
+    /// last one dropped (with any other holders) frees them. Checked exactly
+    /// like `from_bytes`.
+    ///
+    /// # Errors
+    /// As `from_bytes`.
+    pub fn from_arc(data: Arc<[u8]>) -> std::io::Result<Self> {
+        Self::from_source(DataSource::Shared(data))
+    }
+
     fn from_source(source: DataSource) -> std::io::Result<Self> {
         if source.len() < std::mem::size_of::<KDictHeader>() {
              return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "File too small"));
@@ -83,35 +206,68 @@ impl KDict {
         }
 
         let table_offset = std::mem::size_of::<KDictHeader>();
-        // Check bounds would be good here
-        let table_ptr = unsafe { base_ptr.add(table_offset) } as *const KDictEntry;
         
-        let table_bytes = header.table_size as usize * std::mem::size_of::<KDictEntry>();
-        let pool_offset = table_offset + table_bytes;
-        
-        if pool_offset > source.len() {
-             return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "File truncated"));
+        let version = header.version;
+        if !(1..=4).contains(&version) {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Unsupported version {}", version)));
         }
-        
+
+        if header.table_size == 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Empty table"));
+        }
+        if !header.table_size.is_power_of_two() {
+            let table_size = header.table_size;
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Table size {} is not a power of two", table_size)));
+        }
+
+        // Version 2 stores one category byte per slot between table and pool,
+        // version 3 one flags byte per slot after that and version 4 a u32
+        // frequency per slot after those. Checked arithmetic so a huge
+        // `table_size` cannot wrap around on 32-bit targets.
+        let slots = header.table_size as usize;
+        let arrays = match version {
+            1 => 0,
+            2 => slots,
+            3 => 2 * slots,
+            _ => 6 * slots,
+        };
+        let pool_offset = slots
+            .checked_mul(std::mem::size_of::<KDictEntry>())
+            .and_then(|table_bytes| (table_offset + arrays).checked_add(table_bytes))
+            .filter(|&end| end <= source.len())
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "File truncated"))?;
+        let categories_offset = pool_offset - arrays;
+        let flags_offset = categories_offset + if version >= 2 { slots } else { 0 };
+
+        let table_ptr = unsafe { base_ptr.add(table_offset) } as *const KDictEntry;
         let pool_ptr = unsafe { base_ptr.add(pool_offset) };
+        let categories_ptr = if version >= 2 { unsafe { base_ptr.add(categories_offset) } } else { std::ptr::null() };
+        let flags_ptr = if version >= 3 { unsafe { base_ptr.add(flags_offset) } } else { std::ptr::null() };
+        let frequencies_ptr = if version >= 4 { unsafe { base_ptr.add(flags_offset + slots) } } else { std::ptr::null() };
 
+        let pool_len = source.len() - pool_offset;
         Ok(KDict {
             source,
             header: header_ptr,
             table: table_ptr,
             string_pool: pool_ptr,
+            categories: categories_ptr,
+            flags: flags_ptr,
+            frequencies: frequencies_ptr,
             table_mask: header.table_size - 1,
+            pool_len,
         })
     }
 
+    /// The NUL-terminated word at `offset` in the pool, without the NUL.
+    /// Empty if `offset` is past the pool or no NUL follows it before the
+    /// pool ends.
     pub fn get_pool_bytes(&self, offset: u32) -> &[u8] {
-        unsafe {
-            let ptr = self.string_pool.add(offset as usize);
-            let mut len = 0;
-            while *ptr.add(len) != 0 {
-                len += 1;
-            }
-            std::slice::from_raw_parts(ptr, len)
+        let pool = unsafe { std::slice::from_raw_parts(self.string_pool, self.pool_len) };
+        let word = pool.get(offset as usize..).unwrap_or_default();
+        match word.iter().position(|&b| b == 0) {
+            Some(len) => &word[..len],
+            None => &[],
         }
     }
 
@@ -120,6 +276,307 @@ impl KDict {
             self.string_pool.add(offset as usize)
         }
     }
+
+    /// Bytes held for the dictionary image: the file size for a memory-mapped
+    /// dictionary (how much of it is resident depends on which pages were
+    /// touched), the buffer's capacity for an owned one, the slice length
+    /// for a shared one (counted in full by every `KDict` sharing it). The
+    /// `KDict` struct itself adds a few pointers on top.
+    pub fn memory_footprint(&self) -> usize {
+        self.source.footprint()
+    }
+
+    /// The image's size split by section; the parts add up to its length.
+    pub fn memory_breakdown(&self) -> MemoryBreakdown {
+        let base = self.source.as_ptr() as usize;
+        let header = std::mem::size_of::<KDictHeader>();
+        let table_end = self.table as usize - base + (self.table_mask as usize + 1) * std::mem::size_of::<KDictEntry>();
+        let pool_start = self.string_pool as usize - base;
+        MemoryBreakdown {
+            header,
+            table: table_end - header,
+            slot_arrays: pool_start - table_end,
+            pool: self.source.len() - pool_start,
+        }
+    }
+
+    /// Every stored word with its cost, in table order (not sorted).
+    pub fn words(&self) -> impl Iterator<Item = (&[u8], f32)> + '_ {
+        self.slots().map(move |slot| {
+            let entry = self.entry(slot);
+            (self.get_pool_bytes(entry.name_offset), entry.cost)
+        })
+    }
+
+    /// Stored words that `normalize` would change, sorted. Runtime lookups
+    /// use normalized text, so these can never match; a non-empty result
+    /// means the dictionary should be rebuilt. Words that are not valid
+    /// UTF-8 are always reported (lossily decoded).
+    pub fn check_normalized(&self, normalize: impl Fn(&str) -> String) -> Vec<String> {
+        let mut bad: Vec<String> = self.words()
+            .filter_map(|(bytes, _)| match std::str::from_utf8(bytes) {
+                Ok(word) => (normalize(word) != word).then(|| word.to_string()),
+                Err(_) => Some(String::from_utf8_lossy(bytes).into_owned()),
+            })
+            .collect();
+        bad.sort();
+        bad
+    }
+
+    /// Walks the whole table and checks what loading does not: every
+    /// entry's word lies inside the pool, is NUL-terminated there and is
+    /// non-empty; the occupied slots match `num_entries`; no word is longer
+    /// than `max_word_length` (the DP would never reach it); and at least
+    /// one slot is empty, so a failed lookup terminates. O(table + pool).
+    ///
+    /// # Errors
+    /// A description of the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        let header = unsafe { *self.header };
+        let table_size = header.table_size as usize;
+        let max_word_length = header.max_word_length as usize;
+        let mut occupied = 0;
+        for slot in 0..table_size {
+            let offset = self.entry(slot).name_offset as usize;
+            if offset == 0 { continue; }
+            occupied += 1;
+            if offset >= self.pool_len {
+                return Err(format!("slot {}: name offset {} is past the pool ({} bytes)", slot, offset, self.pool_len));
+            }
+            let word = self.get_pool_bytes(offset as u32);
+            if word.is_empty() {
+                let what = if unsafe { *self.string_pool.add(offset) } == 0 { "an empty word" } else { "a word with no NUL before the pool ends" };
+                return Err(format!("slot {}: name offset {} points at {}", slot, offset, what));
+            }
+            if word.len() > max_word_length {
+                return Err(format!("slot {}: word of {} bytes exceeds max_word_length {}", slot, word.len(), max_word_length));
+            }
+        }
+        if occupied != header.num_entries as usize {
+            return Err(format!("{} occupied slots but num_entries is {}", occupied, { header.num_entries }));
+        }
+        if occupied == table_size {
+            return Err("table has no empty slot, so failed lookups never end".to_string());
+        }
+        Ok(())
+    }
+
+    /// Indices of the occupied table slots.
+    pub fn slots(&self) -> impl Iterator<Item = usize> + '_ {
+        let table_size = unsafe { (*self.header).table_size } as usize;
+        (0..table_size).filter(move |&slot| self.entry(slot).name_offset != 0)
+    }
+
+    /// Table slot holding `word`, if it is in the dictionary.
+    #[must_use]
+    pub fn lookup(&self, word: &[u8]) -> Option<usize> {
+        let mut idx = crate::utils::djb2_hash(word) & self.table_mask;
+        loop {
+            let entry = self.entry(idx as usize);
+            if entry.name_offset == 0 { return None; }
+            if self.get_pool_bytes(entry.name_offset) == word { return Some(idx as usize); }
+            idx = (idx + 1) & self.table_mask;
+        }
+    }
+
+    #[must_use]
+    pub fn contains(&self, word: &str) -> bool {
+        self.lookup(word.as_bytes()).is_some()
+    }
+
+    /// The longest dictionary word starting at byte `pos` of `text`, as its
+    /// byte length and cost. `None` if no word starts there, or if `pos` is
+    /// not a char boundary of `text`.
+    #[must_use]
+    pub fn longest_match_at(&self, text: &str, pos: usize) -> Option<(usize, f32)> {
+        if !text.is_char_boundary(pos) { return None; }
+        let mut longest = None;
+        self.for_each_match_at(text, pos, 1, |end, slot| longest = Some((end - pos, self.entry(slot).cost)), |_| {});
+        longest
+    }
+
+    /// Calls `on_match(end, slot)` for every dictionary word spanning
+    /// `text[pos..end]`, shortest first, considering only words of at least
+    /// `min_chars` chars and at most `max_word_length` bytes. `on_probe`
+    /// gets each table lookup's probe count. This is the DP's lookup: one
+    /// incremental hash over the growing prefix, one probe sequence per
+    /// char. `pos` must be a char boundary.
+    #[inline]
+    pub fn for_each_match_at(
+        &self,
+        text: &str,
+        pos: usize,
+        min_chars: usize,
+        mut on_match: impl FnMut(usize, usize),
+        mut on_probe: impl FnMut(u64),
+    ) {
+        debug_assert!(text.is_char_boundary(pos));
+        let max_wl = unsafe { (*self.header).max_word_length } as usize;
+        let bytes = text.as_bytes();
+        let mut khash: u32 = 5381;
+        let mut end = pos;
+
+        for (n_chars, c) in text[pos..].chars().enumerate() {
+            let c_len = c.len_utf8();
+            if end + c_len - pos > max_wl { break; }
+
+            // Incremental Hash
+            for b in &bytes[end..end + c_len] {
+                khash = (khash << 5).wrapping_add(khash).wrapping_add(*b as u32);
+            }
+            end += c_len;
+            if n_chars + 1 < min_chars { continue; }
+
+            // Lookup
+            let len = end - pos;
+            let mut idx = khash & self.table_mask;
+            let mut probes = 0;
+            loop {
+                probes += 1;
+                let entry = unsafe { &*self.table.add(idx as usize) };
+                if entry.name_offset == 0 { break; }
+                // The word and its NUL must lie inside the pool
+                if entry.name_offset as usize + len >= self.pool_len {
+                    idx = (idx + 1) & self.table_mask;
+                    continue;
+                }
+
+                // Optimized: Pointer-based comparison
+                let stored_ptr = self.get_pool_ptr(entry.name_offset);
+                // bytes is a slice, as_ptr is safe.
+                let word_ptr = unsafe { bytes.as_ptr().add(pos) };
+
+                unsafe {
+                    // Check first byte, then SIMD body, then sentinel
+                    if *stored_ptr == *word_ptr &&
+                       crate::utils::fast_str_eq(stored_ptr, word_ptr, len) &&
+                       *stored_ptr.add(len) == 0
+                    {
+                        on_match(end, idx as usize);
+                        break;
+                    }
+                }
+
+                idx = (idx + 1) & self.table_mask;
+            }
+            on_probe(probes);
+        }
+    }
+
+    #[must_use]
+    pub fn entry(&self, slot: usize) -> KDictEntry {
+        debug_assert!(slot <= self.table_mask as usize);
+        unsafe { *self.table.add(slot) }
+    }
+
+    /// Category byte stored for `slot`; 0 (none) for version 1 files.
+    pub fn category(&self, slot: usize) -> u8 {
+        if self.categories.is_null() { return 0; }
+        debug_assert!(slot <= self.table_mask as usize);
+        unsafe { *self.categories.add(slot) }
+    }
+
+    /// `FLAG_*` bits stored for `slot`; 0 for files before version 3.
+    pub fn flags(&self, slot: usize) -> u8 {
+        if self.flags.is_null() { return 0; }
+        debug_assert!(slot <= self.table_mask as usize);
+        unsafe { *self.flags.add(slot) }
+    }
+
+    /// Whether this is a version 3 file, i.e. entries can carry flags.
+    pub fn has_flags(&self) -> bool {
+        !self.flags.is_null()
+    }
+
+    /// Corpus frequency stored for `slot`; 0 (unknown) for files before
+    /// version 4.
+    pub fn frequency(&self, slot: usize) -> u32 {
+        if self.frequencies.is_null() { return 0; }
+        debug_assert!(slot <= self.table_mask as usize);
+        let bytes = unsafe { std::slice::from_raw_parts(self.frequencies.add(slot * 4), 4) };
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    /// Whether this is a version 4 file, i.e. entries can carry frequencies.
+    pub fn has_frequencies(&self) -> bool {
+        !self.frequencies.is_null()
+    }
+}
+
+/// Hash-table probe totals over a run of lookups, from `ProbeCounter`.
+#[cfg(feature = "probe-stats")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProbeStats {
+    /// Lookups performed (one per candidate substring).
+    pub lookups: u64,
+    /// Slots examined, including the final empty or matching slot.
+    pub probes: u64,
+    /// Lookups that examined more than one slot.
+    pub collisions: u64,
+}
+
+#[cfg(feature = "probe-stats")]
+impl ProbeStats {
+    /// Average slots examined per lookup; 1.0 means no probing at all.
+    pub fn mean_probes(&self) -> f64 {
+        if self.lookups == 0 { return 0.0; }
+        self.probes as f64 / self.lookups as f64
+    }
+
+    /// Fraction of lookups that collided.
+    pub fn collision_rate(&self) -> f64 {
+        if self.lookups == 0 { return 0.0; }
+        self.collisions as f64 / self.lookups as f64
+    }
+}
+
+#[cfg(feature = "probe-stats")]
+impl std::ops::AddAssign for ProbeStats {
+    fn add_assign(&mut self, other: Self) {
+        self.lookups += other.lookups;
+        self.probes += other.probes;
+        self.collisions += other.collisions;
+    }
+}
+
+/// Thread-safe accumulator behind `ProbeStats`, shared by every thread
+/// segmenting with the same `KhmerSegmenter`.
+#[cfg(feature = "probe-stats")]
+#[derive(Debug, Default)]
+pub struct ProbeCounter {
+    lookups: std::sync::atomic::AtomicU64,
+    probes: std::sync::atomic::AtomicU64,
+    collisions: std::sync::atomic::AtomicU64,
+}
+
+#[cfg(feature = "probe-stats")]
+impl ProbeCounter {
+    /// Records one lookup that examined `probes` slots.
+    pub fn record(&self, probes: u64) {
+        use std::sync::atomic::Ordering::Relaxed;
+        self.lookups.fetch_add(1, Relaxed);
+        self.probes.fetch_add(probes, Relaxed);
+        if probes > 1 { self.collisions.fetch_add(1, Relaxed); }
+    }
+
+    pub fn snapshot(&self) -> ProbeStats {
+        use std::sync::atomic::Ordering::Relaxed;
+        ProbeStats {
+            lookups: self.lookups.load(Relaxed),
+            probes: self.probes.load(Relaxed),
+            collisions: self.collisions.load(Relaxed),
+        }
+    }
+
+    /// Returns the totals so far and resets them to zero.
+    pub fn take(&self) -> ProbeStats {
+        use std::sync::atomic::Ordering::Relaxed;
+        ProbeStats {
+            lookups: self.lookups.swap(0, Relaxed),
+            probes: self.probes.swap(0, Relaxed),
+            collisions: self.collisions.swap(0, Relaxed),
+        }
+    }
 }
 
 unsafe impl Send for KDict {}
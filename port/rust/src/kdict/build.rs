@@ -0,0 +1,273 @@
+// Writer for the KDIC format read by `KDict`, mirroring the layout produced by
+// `scripts/prepare_data.py`: 32-byte header, open-addressing table probed with
+// djb2 + linear probing, then a pool of NUL-terminated words.
+
+use std::collections::HashMap;
+
+use super::{KDict, KDictEntry, KDictHeader};
+use crate::utils::djb2_hash;
+
+/// Default fraction of table slots filled, the same as `prepare_data.py`.
+/// Linear probing stays short up to about this point.
+pub const DEFAULT_LOAD_FACTOR: f64 = 0.7;
+
+/// Collects words and costs and lays them out as a `.kdict` image.
+///
+/// Pruning options are applied at `build` time, so `num_entries`,
+/// `table_size` and `max_word_length` are always computed from the words that
+/// survive, never from the original list.
+pub struct KDictBuilder {
+    words: HashMap<String, Entry>,
+    default_cost: f32,
+    unknown_cost: f32,
+    max_cost: Option<f32>,
+    keep_cheapest: Option<usize>,
+    load_factor: f64,
+    reserve: usize,
+}
+
+// What the builder keeps per word.
+#[derive(Clone, Copy)]
+struct Entry {
+    cost: f32,
+    category: u8,
+    flags: u8,
+    frequency: u32,
+}
+
+impl KDictBuilder {
+    pub fn new(default_cost: f32, unknown_cost: f32) -> Self {
+        Self {
+            words: HashMap::new(),
+            default_cost,
+            unknown_cost,
+            max_cost: None,
+            keep_cheapest: None,
+            load_factor: DEFAULT_LOAD_FACTOR,
+            reserve: 0,
+        }
+    }
+
+    /// Starts from an existing dictionary's words, categories, flags and
+    /// header costs.
+    pub fn from_dict(dict: &KDict) -> Self {
+        let header = unsafe { &*dict.header };
+        let mut builder = Self::new(header.default_cost, header.unknown_cost);
+        builder.extend_from_dict(dict);
+        builder
+    }
+
+    /// Adds every word of `dict` with its category, flags and frequency, as
+    /// `insert` would: a word already present keeps the lower cost. Header
+    /// costs are left alone.
+    pub fn extend_from_dict(&mut self, dict: &KDict) {
+        for slot in dict.slots() {
+            let entry = dict.entry(slot);
+            let word = String::from_utf8_lossy(dict.get_pool_bytes(entry.name_offset));
+            self.insert_entry(&word, Entry {
+                cost: entry.cost,
+                category: dict.category(slot),
+                flags: dict.flags(slot),
+                frequency: dict.frequency(slot),
+            });
+        }
+    }
+
+    /// Adds a word. Re-inserting a word keeps the lower of the two costs.
+    /// Empty words are ignored. Multiword entries with internal spaces or
+    /// punctuation (`ភ្នំ ពេញ`) are fine: the segmenter matches them across
+    /// the separator and picks them whenever they beat the split path.
+    pub fn insert(&mut self, word: &str, cost: f32) {
+        self.insert_with_category(word, cost, 0);
+    }
+
+    /// Adds a word tagged with a category byte (e.g. a coarse part-of-speech
+    /// code; 0 means none). Any non-zero category makes `build` write a
+    /// version 2 file. On duplicates the cheaper entry's category wins.
+    pub fn insert_with_category(&mut self, word: &str, cost: f32, category: u8) {
+        self.insert_with_flags(word, cost, category, 0);
+    }
+
+    /// Adds a word with a category byte and `FLAG_*` role bits (prefix,
+    /// suffix, standalone). Any non-zero flags make `build` write a version 3
+    /// file. On duplicates the cheaper entry's category and flags win.
+    pub fn insert_with_flags(&mut self, word: &str, cost: f32, category: u8, flags: u8) {
+        self.insert_entry(word, Entry { cost, category, flags, frequency: 0 });
+    }
+
+    /// Adds a word with its corpus frequency (0 means unknown), which the
+    /// segmenter turns into a cost bonus when `enable_frequency_costs` is on.
+    /// Any non-zero frequency makes `build` write a version 4 file. On
+    /// duplicates the cheaper entry's frequency wins.
+    pub fn insert_with_frequency(&mut self, word: &str, cost: f32, frequency: u32) {
+        self.insert_entry(word, Entry { cost, category: 0, flags: 0, frequency });
+    }
+
+    fn insert_entry(&mut self, word: &str, entry: Entry) {
+        if word.is_empty() { return; }
+        self.words
+            .entry(word.to_string())
+            .and_modify(|e| if entry.cost < e.cost { *e = entry })
+            .or_insert(entry);
+    }
+
+    /// Drop words whose cost exceeds `max_cost` (i.e. the rarest ones).
+    pub fn prune_above(&mut self, max_cost: f32) {
+        self.max_cost = Some(max_cost);
+    }
+
+    /// Keep only the `n` cheapest (most frequent) words.
+    pub fn keep_cheapest(&mut self, n: usize) {
+        self.keep_cheapest = Some(n);
+    }
+
+    /// Target fraction of filled slots, in `(0, 1)`. `table_size` becomes the
+    /// next power of two above `num_entries / load_factor`; lower values trade
+    /// memory for shorter probes.
+    ///
+    /// # Panics
+    /// If `load_factor` is not strictly between 0 and 1.
+    pub fn load_factor(&mut self, load_factor: f64) {
+        assert!(load_factor > 0.0 && load_factor < 1.0, "load factor must be in (0, 1), got {}", load_factor);
+        self.load_factor = load_factor;
+    }
+
+    /// Size the table for at least `expected_words` entries, even if fewer end
+    /// up being inserted.
+    pub fn reserve(&mut self, expected_words: usize) {
+        self.reserve = expected_words;
+    }
+
+    /// Returns the dictionary image; nothing is written anywhere else.
+    #[must_use]
+    pub fn build(&self) -> Vec<u8> {
+        let mut words: Vec<(&str, Entry)> = self.words.iter()
+            .filter(|(_, e)| self.max_cost.is_none_or(|max| e.cost <= max))
+            .map(|(w, &e)| (w.as_str(), e))
+            .collect();
+        if let Some(n) = self.keep_cheapest {
+            words.sort_by(|a, b| a.1.cost.total_cmp(&b.1.cost).then(a.0.cmp(b.0)));
+            words.truncate(n);
+        }
+        // Fixed order keeps the output byte-for-byte reproducible
+        words.sort_by(|a, b| a.0.cmp(b.0));
+
+        let num_entries = words.len();
+        let table_size = table_size_for(num_entries.max(self.reserve), self.load_factor);
+        // `KDict` probes with `hash & (table_size - 1)`
+        assert!(table_size.is_power_of_two() && table_size > num_entries);
+        let mask = table_size - 1;
+
+        let mut pool = vec![0u8]; // offset 0 marks an empty slot
+        let mut table = vec![KDictEntry { name_offset: 0, cost: 0.0 }; table_size];
+        let mut categories = vec![0u8; table_size];
+        let mut flags = vec![0u8; table_size];
+        let mut frequencies = vec![0u32; table_size];
+        let mut max_word_length = 0;
+        for (word, entry) in &words {
+            let name_offset = pool.len() as u32;
+            pool.extend_from_slice(word.as_bytes());
+            pool.push(0);
+            max_word_length = max_word_length.max(word.len());
+
+            let mut idx = djb2_hash(word.as_bytes()) as usize & mask;
+            while table[idx].name_offset != 0 {
+                idx = (idx + 1) & mask;
+            }
+            table[idx] = KDictEntry { name_offset, cost: entry.cost };
+            categories[idx] = entry.category;
+            flags[idx] = entry.flags;
+            frequencies[idx] = entry.frequency;
+        }
+        // Stay on version 1 (readable by the C port) unless categories,
+        // flags or frequencies are used; each array needs the ones before it
+        let with_frequencies = frequencies.iter().any(|&f| f != 0);
+        let with_flags = with_frequencies || flags.iter().any(|&f| f != 0);
+        let with_categories = with_flags || categories.iter().any(|&c| c != 0);
+
+        let header = KDictHeader {
+            magic: *b"KDIC",
+            version: if with_frequencies { 4 } else if with_flags { 3 } else if with_categories { 2 } else { 1 },
+            num_entries: num_entries as u32,
+            table_size: table_size as u32,
+            default_cost: self.default_cost,
+            unknown_cost: self.unknown_cost,
+            max_word_length: max_word_length as u32,
+            padding: 0,
+        };
+
+        let mut out = Vec::with_capacity(
+            std::mem::size_of::<KDictHeader>() + table_size * (std::mem::size_of::<KDictEntry>() + 6) + pool.len(),
+        );
+        write_header(&mut out, &header);
+        for entry in &table {
+            let (name_offset, cost) = (entry.name_offset, entry.cost);
+            out.extend_from_slice(&name_offset.to_le_bytes());
+            out.extend_from_slice(&cost.to_le_bytes());
+        }
+        if with_categories {
+            out.extend_from_slice(&categories);
+        }
+        if with_flags {
+            out.extend_from_slice(&flags);
+        }
+        if with_frequencies {
+            out.extend(frequencies.iter().flat_map(|f| f.to_le_bytes()));
+        }
+        out.extend_from_slice(&pool);
+        out
+    }
+}
+
+/// Builds a `.kdict` image from a word/cost list.
+#[must_use]
+pub fn build_kdict(words: &[(String, f32)], default_cost: f32, unknown_cost: f32) -> Vec<u8> {
+    let mut builder = KDictBuilder::new(default_cost, unknown_cost);
+    for (word, cost) in words {
+        builder.insert(word, *cost);
+    }
+    builder.build()
+}
+
+/// Rebuilds `dict` without the words costlier than `max_cost`. Header costs
+/// carry over; `num_entries`, `table_size` and `max_word_length` are
+/// recomputed for the smaller word set, so the result probes faster too.
+#[must_use]
+pub fn prune(dict: &KDict, max_cost: f32) -> Vec<u8> {
+    let mut builder = KDictBuilder::from_dict(dict);
+    builder.prune_above(max_cost);
+    builder.build()
+}
+
+/// Merges `dicts` into one image. A word in several keeps its lowest cost
+/// (with that entry's category and flags); header costs come from the first
+/// dictionary, and `max_word_length` covers every word. `None` when `dicts`
+/// is empty.
+#[must_use]
+pub fn merge(dicts: &[&KDict]) -> Option<Vec<u8>> {
+    let (first, rest) = dicts.split_first()?;
+    let mut builder = KDictBuilder::from_dict(first);
+    for dict in rest {
+        builder.extend_from_dict(dict);
+    }
+    Some(builder.build())
+}
+
+// Smallest power of two above `entries / load_factor`. Always leaves at least
+// one empty slot so a failed probe terminates.
+fn table_size_for(entries: usize, load_factor: f64) -> usize {
+    let wanted = (entries as f64 / load_factor).ceil() as usize;
+    wanted.max(entries + 1).next_power_of_two()
+}
+
+fn write_header(out: &mut Vec<u8>, header: &KDictHeader) {
+    let KDictHeader { magic, version, num_entries, table_size, default_cost, unknown_cost, max_word_length, padding } = *header;
+    out.extend_from_slice(&magic);
+    out.extend_from_slice(&version.to_le_bytes());
+    out.extend_from_slice(&num_entries.to_le_bytes());
+    out.extend_from_slice(&table_size.to_le_bytes());
+    out.extend_from_slice(&default_cost.to_le_bytes());
+    out.extend_from_slice(&unknown_cost.to_le_bytes());
+    out.extend_from_slice(&max_word_length.to_le_bytes());
+    out.extend_from_slice(&padding.to_le_bytes());
+}
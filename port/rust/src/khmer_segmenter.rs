@@ -1,368 +1,2024 @@
-use crate::kdict::KDict;
-use crate::normalization::khmer_normalize;
-use crate::rule_engine::RuleEngine;
-use crate::utils;
-// For handling null-terminated strings in KDict (Removed CStr)
-
-#[derive(Clone)]
-pub struct SegmenterConfig {
-    pub enable_normalization: bool,
-    pub enable_repair_mode: bool,
-    pub enable_acronym_detection: bool,
-    pub enable_unknown_merging: bool,
-    pub enable_frequency_costs: bool,
-}
-
-impl Default for SegmenterConfig {
-    fn default() -> Self {
-        Self {
-            enable_normalization: true,
-            enable_repair_mode: true,
-            enable_acronym_detection: true,
-            enable_unknown_merging: true,
-            enable_frequency_costs: true,
-        }
-    }
-}
-
-pub struct KhmerSegmenter {
-    kdict: Option<KDict>,
-    rule_engine: RuleEngine,
-    config: SegmenterConfig,
-}
-
-#[derive(Clone, Copy)]
-struct State {
-    cost: f32,
-    prev_idx: isize,
-}
-
-impl KhmerSegmenter {
-    pub fn new(kdict_path: Option<&str>, config: SegmenterConfig) -> std::io::Result<Self> {
-        let kdict = if let Some(path) = kdict_path {
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                Some(KDict::load(path)?)
-            }
-            #[cfg(target_arch = "wasm32")]
-            {
-                 // On WASM, new() with path is invalid if we don't have fs access.
-                 // We could panic or return error. 
-                 return Err(std::io::Error::new(std::io::ErrorKind::Other, "File loading not supported on WASM"));
-            }
-        } else {
-            None
-        };
-
-        Ok(Self {
-            kdict,
-            rule_engine: RuleEngine::new(),
-            config,
-        })
-    }
-
-    pub fn new_with_dict(kdict: Option<KDict>, config: SegmenterConfig) -> Self {
-        Self {
-            kdict,
-            rule_engine: RuleEngine::new(),
-            config,
-        }
-    }
-    
-    // Helper to access string pool (Unsafe) - Removed in favor of direct byte access
-
-    pub fn segment(&self, raw_text: &str, separator: Option<&str>) -> String {
-        let sep = separator.unwrap_or("\u{200B}");
-        
-        let text_owned = if self.config.enable_normalization {
-            khmer_normalize(raw_text)
-        } else {
-            raw_text.to_string()
-        };
-        let text = &text_owned;
-        let n = text.len();
-
-        if n == 0 { return String::new(); }
-
-        // DP Table
-        let mut dp = vec![State { cost: 1e9, prev_idx: -1 }; n + 1];
-        dp[0].cost = 0.0;
-        
-        // Dictionary Accessors
-        let (header, table, mask) = if let Some(ref kd) = self.kdict {
-             unsafe { (&*kd.header, kd.table, kd.table_mask) }
-        } else {
-             // Fallback if no dict (shouldn't happen in normal usage but safe)
-             return text.clone();
-        };
-
-        let mut i = 0;
-
-
-        while i < n {
-            // Skip unreachable
-            if dp[i].cost >= 1e9 {
-                if let Some(c) = text[i..].chars().next() {
-                    let clen = c.len_utf8();
-                    i += clen;
-                } else {
-                    i += 1;
-                }
-                continue;
-            }
-
-            let c = text[i..].chars().next().unwrap();
-            let char_len = c.len_utf8();
-            
-            // Repair Mode
-            if self.config.enable_repair_mode {
-                let mut force_repair = false;
-                if c >= '\u{17B6}' && c <= '\u{17C5}' { force_repair = true; } 
-
-                if force_repair {
-                    let next_idx = i + char_len;
-                    let repair_cost = header.unknown_cost + 50.0;
-                    if next_idx <= n && dp[i].cost + repair_cost < dp[next_idx].cost {
-                        dp[next_idx].cost = dp[i].cost + repair_cost;
-                        dp[next_idx].prev_idx = i as isize;
-                    }
-                    i += char_len;
-                    continue; 
-                }
-            }
-
-            // Numbers
-            let is_dig = utils::is_digit_cp(c);
-            
-            if is_dig {
-                let num_len = utils::get_number_length(&text[i..]);
-                let next_idx = i + num_len;
-                let step_cost = 1.0;
-                if next_idx <= n && dp[i].cost + step_cost < dp[next_idx].cost {
-                    dp[next_idx].cost = dp[i].cost + step_cost;
-                    dp[next_idx].prev_idx = i as isize;
-                }
-            } else if utils::is_separator_cp(c) {
-                let next_idx = i + char_len;
-                let step_cost = 0.1;
-                if next_idx <= n && dp[i].cost + step_cost < dp[next_idx].cost {
-                    dp[next_idx].cost = dp[i].cost + step_cost;
-                    dp[next_idx].prev_idx = i as isize;
-                }
-            }
-
-            // Acronyms
-            if self.config.enable_acronym_detection && utils::is_acronym_start(&text[i..]) {
-                let acr_len = utils::get_acronym_length(&text[i..]);
-                let next_idx = i + acr_len;
-                let step_cost = header.default_cost;
-                if next_idx <= n && dp[i].cost + step_cost < dp[next_idx].cost {
-                    dp[next_idx].cost = dp[i].cost + step_cost;
-                    dp[next_idx].prev_idx = i as isize;
-                }
-            }
-
-            // Dictionary Lookup
-            if let Some(ref kd) = self.kdict {
-                let max_wl = header.max_word_length as usize;
-                let mut khash: u32 = 5381;
-                let mut current_offset = i;
-                let bytes = text.as_bytes();
-                
-                for sub_c in text[i..].chars() {
-                    let sc_len = sub_c.len_utf8();
-                    if current_offset + sc_len - i > max_wl { break; }
-
-                    // Incremental Hash
-                    for b in &bytes[current_offset..current_offset + sc_len] {
-                        khash = (khash << 5).wrapping_add(khash).wrapping_add(*b as u32);
-                    }
-                    
-                    current_offset += sc_len;
-                    
-                    // Lookup
-                    let mut idx = khash & mask;
-                    loop {
-                        let entry = unsafe { &*table.add(idx as usize) };
-                        if entry.name_offset == 0 { break; }
-                        
-                        // Optimized: Pointer-based comparison
-                        let len = current_offset - i;
-                        let stored_ptr = kd.get_pool_ptr(entry.name_offset);
-                        // bytes is a slice, as_ptr is safe.
-                        let word_ptr = unsafe { bytes.as_ptr().add(i) };
-                        
-                        unsafe {
-                            // Check first byte, then SIMD body, then sentinel
-                            if *stored_ptr == *word_ptr && 
-                               utils::fast_str_eq(stored_ptr, word_ptr, len) && 
-                               *stored_ptr.add(len) == 0 
-                            {
-                                let new_cost = dp[i].cost + entry.cost;
-                                if new_cost < dp[current_offset].cost {
-                                    dp[current_offset].cost = new_cost;
-                                    dp[current_offset].prev_idx = i as isize;
-                                }
-                                break;
-                            }
-                        }
-                        
-                        idx = (idx + 1) & mask;
-                    }
-                }
-            }
-            
-
-            
-            // Handle Unknown Clusters
-            let cluster_bytes = if utils::is_khmer_char(c) {
-                utils::get_khmer_cluster_length(&text[i..])
-            } else {
-                char_len
-            };
-            
-            let next_idx = i + cluster_bytes;
-            let mut unk_cost = header.unknown_cost;
-            if cluster_bytes == char_len && utils::is_khmer_char(c) {
-                 if !utils::is_valid_single_base_char(c) {
-                     unk_cost += 10.0;
-                 }
-            }
-            
-            if next_idx <= n {
-                let new_cost = dp[i].cost + unk_cost;
-                if new_cost < dp[next_idx].cost {
-                    dp[next_idx].cost = new_cost;
-                    dp[next_idx].prev_idx = i as isize;
-                }
-            }
-            
-            i += char_len;
-        }
-
-        // Backtrack
-        if dp[n].prev_idx == -1 {
-            return text.to_string(); // Fail
-        }
-        
-        let mut segments: Vec<(usize, usize)> = Vec::with_capacity(n / 2); // Pre-allocate estimate
-        let mut curr = n;
-        while curr > 0 {
-            let prev = dp[curr].prev_idx as usize;
-            segments.push((prev, curr));
-            curr = prev;
-        }
-        segments.reverse();
-        
-        // Rule Engine
-        self.rule_engine.apply(text, &mut segments);
-        
-        if self.config.enable_unknown_merging {
-             let mut new_segments = Vec::with_capacity(segments.len());
-             
-             // Track consecutive unknowns as a single range
-             let mut unknown_start: Option<usize> = None;
-             let mut unknown_end: usize = 0;
-
-             for (start, end) in segments {
-                 let seg = &text[start..end];
-                 let mut is_known = false;
-                 
-                 // Re-validation logic to determine if segment is "Known"
-                 let char_count = seg.chars().count();
-                 let first_char = seg.chars().next().unwrap(); // segments are never empty
-
-                 // 1. Check Separators (Single char)
-                 if char_count == 1 {
-                     if utils::is_separator_cp(first_char) { is_known = true; }
-                     else if utils::is_digit_cp(first_char) { is_known = true; } // Single digit
-                     else if utils::is_valid_single_base_char(first_char) { is_known = true; }
-                 }
-
-                 // 2. Check Numbers
-                 if !is_known {
-                     let num_len = utils::get_number_length(seg);
-                     if num_len == seg.len() {
-                         is_known = true;
-                     }
-                 }
-
-
-
-                 // 4. Check Acronyms
-                 if !is_known && self.config.enable_acronym_detection {
-                     if utils::is_acronym_start(seg) {
-                         let acr_len = utils::get_acronym_length(seg);
-                         if acr_len == seg.len() {
-                             is_known = true;
-                         }
-                     }
-                 }
-
-                 // 5. Dictionary Check
-                 if !is_known {
-                      let hash = utils::djb2_hash(seg.as_bytes());
-                      let mut idx = hash & mask;
-                      loop {
-                          let entry = unsafe { &*table.add(idx as usize) };
-                          if entry.name_offset == 0 { break; } // Not found
-                          let stored_bytes = self.kdict.as_ref().unwrap().get_pool_bytes(entry.name_offset);
-                          if stored_bytes == seg.as_bytes() {
-                              is_known = true;
-                              break;
-                          }
-                          idx = (idx + 1) & mask;
-                      }
-                 }
-                 
-                 if is_known {
-                     // Flush unknown buffer if exists
-                     if let Some(u_start) = unknown_start {
-                         new_segments.push((u_start, unknown_end));
-                         unknown_start = None;
-                     }
-                     new_segments.push((start, end));
-                 } else {
-                     // Extend unknown buffer
-                     if let Some(u_start) = unknown_start {
-                         // Check script continuity (Fix for mixing Khmer + Latin unknowns)
-                         let buffer_text = &text[u_start..unknown_end];
-                         if let Some(last_char) = buffer_text.chars().last() {
-                              if let Some(curr_char) = seg.chars().next() {
-                                  if utils::is_khmer_char(last_char) != utils::is_khmer_char(curr_char) {
-                                      // Flush previous buffer
-                                      new_segments.push((u_start, unknown_end));
-                                      unknown_start = None;
-                                  }
-                              }
-                         }
-                     }
-
-                     if unknown_start.is_none() {
-                         unknown_start = Some(start);
-                     }
-                     unknown_end = end;
-                 }
-             }
-             
-             // Flush remaining unknown buffer
-             if let Some(u_start) = unknown_start {
-                 new_segments.push((u_start, unknown_end));
-             }
-             
-             segments = new_segments;
-        }
-        
-        // Final String Construction
-        // Estimate size includes separators
-        let total_len = segments.iter().map(|(s,e)| e - s).sum::<usize>() + segments.len() * sep.len();
-        let mut result = String::with_capacity(total_len);
-        
-        for (i, (start, end)) in segments.iter().enumerate() {
-            if i > 0 { result.push_str(sep); }
-            result.push_str(&text[*start..*end]);
-        }
-        
-        result
-    }
-}
+use crate::interner::Interner;
+use crate::kdict::KDict;
+#[cfg(feature = "probe-stats")]
+use crate::kdict::{ProbeCounter, ProbeStats};
+use crate::normalization::{khmer_normalize_checked_with, khmer_normalize_mapped_with, khmer_normalize_with, NormalizationConfig, OffsetMap};
+use crate::rule_engine::{QaPrefixMerge, RuleEngine};
+use crate::stream::{process_lines, ReaderOptions, ReaderSummary, DEFAULT_WINDOW};
+use crate::utils::{self, DecimalStyle, Script};
+use crate::viterbi::{viterbi_into, viterbi_nbest, State, Transition, Transitions, UNREACHED};
+use crate::vocab::Vocab;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Where the output separator goes around separator tokens (punctuation and
+/// whitespace) when joining.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PunctuationAttachment {
+    /// `word | ។ | word`
+    #[default]
+    Standalone,
+    /// `word។ | word`: no separator before a punctuation token.
+    AttachLeft,
+    /// `word | ។word`: no separator after a punctuation token.
+    AttachRight,
+}
+
+/// Which separator tokens the unknown-merging pass may join two unknown runs
+/// across. Separators are otherwise always a hard boundary for merging.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SeparatorPolicy {
+    /// `unk | - | unk` stays three tokens.
+    #[default]
+    Never,
+    /// A separator token made only of these characters is absorbed when an
+    /// unknown run continues after it, e.g. `Allow(vec!['-'])` turns
+    /// `unk | - | unk` into one token. A separator next to a known token is
+    /// left alone.
+    Allow(Vec<char>),
+}
+
+impl SeparatorPolicy {
+    fn allows(&self, token: &str) -> bool {
+        match self {
+            SeparatorPolicy::Never => false,
+            SeparatorPolicy::Allow(chars) => token.chars().all(|c| chars.contains(&c)),
+        }
+    }
+}
+
+/// What to do with coengs (U+17D2) left dangling at the very end of the input,
+/// i.e. a cluster cut off before its subscript consonant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrailingCoeng {
+    /// Segment them like any other character. The stray coeng is an unknown
+    /// and usually gets merged into the preceding unknown run.
+    #[default]
+    Keep,
+    /// Remove them from the output, as for truncated text.
+    Drop,
+    /// Emit them as one token of their own, never merged, reported as
+    /// `TokenKind::Incomplete` by `segment_detailed`.
+    Flag,
+}
+
+/// How text without a single Khmer character (Thai, Lao, Arabic, English...)
+/// is segmented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fallback {
+    /// Run the usual DP: every non-Khmer character is its own unknown
+    /// token, rejoined only by unknown-merging when that is enabled.
+    #[default]
+    PerChar,
+    /// Runs of non-whitespace and runs of whitespace alternate as tokens,
+    /// so the tokens still cover the whole text.
+    WhitespaceSplit,
+    /// The whole text is one token.
+    SingleToken,
+}
+
+/// What repair mode does with an orphaned dependent vowel (one the DP
+/// reaches with no base before it, e.g. OCR noise or a line starting `ា`).
+/// A vowel after a separator or non-Khmer text is always orphaned: no
+/// dictionary match spans it, even an entry spelled that way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OrphanedVowels {
+    /// Consume it through a repair transition costing `unknown_cost + 50`;
+    /// the rules then usually fold it into the token before it.
+    #[default]
+    Repair,
+    /// Keep it as a token of its own, `TokenKind::Orphaned` in
+    /// `segment_detailed`, at the plain unknown cost. Neither the rules nor
+    /// unknown merging join it to a neighbour, so the orphans in a text can
+    /// be counted.
+    Flag,
+}
+
+/// Handling of reduplicated words: a word followed by the repetition sign
+/// `ៗ` (U+17D7, leik too), or the same word written twice in a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Reduplication {
+    /// Leave them as the DP found them: `ផ្សេង | ៗ`, `ក្មេង | ក្មេង`.
+    #[default]
+    Split,
+    /// Merge them into one token, reported as `TokenKind::Reduplication` by
+    /// `segment_detailed`. Only directly adjacent tokens merge, so a space,
+    /// khan or other separator in between (or a `ៗ` with no word before it)
+    /// leaves them apart.
+    Merge,
+}
+
+/// Handling of numbers joined by more than one `.`, such as version strings
+/// and IPv4 addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DottedNumbers {
+    /// `192.168.0.1` and `1.2.3` are one number token (an id, so
+    /// `utils::parse_number` gives `None`). A trailing `.` is never included.
+    #[default]
+    Join,
+    /// Each component is its own number with `.` separators between them:
+    /// `192 | . | 168 | . | 0 | . | 1`. Single-dot decimals are unaffected.
+    /// This always splits on `.`, so under `DecimalStyle::Comma` it also
+    /// breaks up grouped numbers like `1.000.000`; keep `Join` there.
+    Split,
+}
+
+/// How `segment_confidence` turns a token's path cost into a score in [0, 1]:
+///
+/// `confidence = (1 - clamp(cost / zero_at, 0, 1)) ^ gamma`
+///
+/// so a free token scores 1 and anything costing `zero_at` or more scores 0.
+/// Numbers and separators are cheap by construction and get fixed scores.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct ConfidenceMapping {
+    /// Cost that maps to confidence 0; `None` uses the effective unknown cost.
+    pub zero_at: Option<f32>,
+    /// Curve exponent. Above 1 pushes mid-cost words down, below 1 lifts them.
+    pub gamma: f32,
+    pub number: f32,
+    pub separator: f32,
+}
+
+impl Default for ConfidenceMapping {
+    fn default() -> Self {
+        Self { zero_at: None, gamma: 1.0, number: 1.0, separator: 1.0 }
+    }
+}
+
+/// Everything that shapes segmentation. With the `serde` feature it
+/// (de)serializes field by field, and a missing field takes its `Default`
+/// value, so `{ "enable_repair_mode": false }` is a complete config. Enum
+/// values are written as their variant names (`"Flag"`).
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct SegmenterConfig {
+    pub enable_normalization: bool,
+    pub enable_repair_mode: bool,
+    /// How repair mode handles orphaned dependent vowels; ignored when
+    /// `enable_repair_mode` is off.
+    pub orphaned_vowels: OrphanedVowels,
+    /// Keep dotted initialisms (`ស.ភ.ភ.ព.`) as one token. The same flag gates
+    /// both the DP transition and the merge-pass classification, so turning it
+    /// off consistently yields a dot-split tokenization: each `.` is a
+    /// separator token and the clusters between dots are scored on their own.
+    pub enable_acronym_detection: bool,
+    /// Fewest dotted clusters an acronym needs. With 1 (the default) a single
+    /// cluster before a sentence-final `.` (`ទៅ.`) can be read as an
+    /// acronym and outbid the dictionary word; 2 requires `ក.ខ.` or longer.
+    pub min_acronym_clusters: usize,
+    /// Most dotted clusters an acronym may have. A longer chain, such as an
+    /// enumerated list `ក.ខ.គ.ឃ.ង.`, is not an acronym at all and is
+    /// segmented normally. `usize::MAX` (the default) sets no cap.
+    pub max_acronym_clusters: usize,
+    /// Characters that may end each cluster of an acronym. Defaults to `.`;
+    /// add U+00B7 MIDDLE DOT for texts written `ក·ខ·`.
+    pub acronym_terminators: Vec<char>,
+    pub enable_unknown_merging: bool,
+    /// Lower each dictionary word's DP cost by `frequency_weight * ln(1 +
+    /// frequency)`, from the frequencies a version 4 dictionary stores, so
+    /// common words win close calls. Without stored frequencies (older
+    /// files, or a 0 entry) costs are used as they are.
+    pub enable_frequency_costs: bool,
+    /// Scale of the `enable_frequency_costs` bonus. Costs are negative log
+    /// probabilities, so keep it small next to their spread.
+    pub frequency_weight: f32,
+    pub normalization: NormalizationConfig,
+    /// Also end sentences on `.`, `!` and `?` in `segment_document` (Khmer
+    /// khan `។` and bariyosan `៕` always end a sentence).
+    pub latin_sentence_terminators: bool,
+    /// Used instead of the dictionary header's `unknown_cost` when set. Also
+    /// shifts the repair-mode cost, which is `unknown_cost + 50`.
+    pub unknown_cost_override: Option<f32>,
+    /// Used instead of the dictionary header's `default_cost` when set.
+    pub default_cost_override: Option<f32>,
+    pub punctuation_attachment: PunctuationAttachment,
+    pub merge_unknowns_across: SeparatorPolicy,
+    pub trailing_coeng: TrailingCoeng,
+    pub confidence: ConfidenceMapping,
+    pub dotted_numbers: DottedNumbers,
+    /// Decimal point convention: which separator joins digits into one number
+    /// token and how `segment_numbers` reads its value.
+    pub decimal_convention: DecimalStyle,
+    /// Keep clock times (`10:30`, `១០:៣០:១៥`) as one token instead of
+    /// splitting on the colon. See `utils::get_time_length`.
+    pub enable_time_detection: bool,
+    /// Segmentation of inputs with no Khmer at all; `PerChar` by default.
+    pub non_khmer_fallback: Fallback,
+    /// When the rule engine merges a standalone QA prefix rightward.
+    pub qa_prefix_merge: QaPrefixMerge,
+    pub reduplication: Reduplication,
+    /// Extra cost per unknown cluster already in a consecutive unknown run:
+    /// the k-th unknown in a row costs `unknown_cost + ramp * (k - 1)`, so
+    /// long noise runs score superlinearly while a lone OOV word is
+    /// unaffected. `None` (the default) keeps every unknown at the flat cost.
+    pub unknown_ramp: Option<f32>,
+    /// Bare-letter tokens (consonants or independent vowels only, no signs)
+    /// with fewer chars than this that are not dictionary words are merged
+    /// into the Khmer word before them, or else the one after them. Tokens
+    /// with a separator, number or edge of text on both sides stay. 0 (the
+    /// default) turns this off; 2 cleans up stray single letters.
+    pub min_merge_char_len: usize,
+    /// Shortest dictionary match, in chars, the DP considers. Shorter entries
+    /// are skipped without a table probe, leaving single letters to the
+    /// unknown-cluster path (which scores valid single bases). 1 (the
+    /// default) matches every entry; 2 ignores single-char entries.
+    pub min_dict_match_chars: usize,
+    /// Pairs of dictionary words merged into one compound token when they
+    /// are adjacent, without adding the compound to the dictionary. Pairs
+    /// chain: with `(a, b)` and `(b, c)`, `a b c` becomes one token. Written
+    /// as normalized text; separators never join. Empty by default.
+    pub compound_pairs: HashSet<(String, String)>,
+    /// Join a Latin word and a number written with a hyphen between them
+    /// (`COVID-១៩`, `iPhone-13-Pro`) into one token. The hyphen may be
+    /// `-`, U+2010, U+2011 or an en dash `–`; anything else, including a
+    /// space on either side of it, keeps the parts apart. Two words
+    /// (`X-ray`) or two numbers (`១-២`) are not joined. Off by default.
+    pub join_hyphenated_mixed: bool,
+    /// Characters that always stand alone as a separator token, whatever
+    /// `utils::is_separator_cp` says of them (a record separator `\u{1E}`
+    /// in a data format, say). No token spans one and no merge joins one to
+    /// a neighbour. Empty by default.
+    pub force_boundary_chars: HashSet<char>,
+    /// Function words to flag as `Token::stop` in `segment_detailed`, e.g.
+    /// for dropping them from a search index. Only annotates; segmentation
+    /// is unchanged. Written as normalized text. Empty by default.
+    pub stop_words: HashSet<String>,
+    /// Separator `segment` and its variants join with when called with
+    /// `None`; U+200B ZERO WIDTH SPACE if this is `None` too (the default).
+    pub default_separator: Option<String>,
+    /// Hard boundaries `segment_presplit` cuts the text at before running
+    /// the DP: space and khan `។` by default.
+    pub presplit_boundaries: Vec<char>,
+}
+
+impl SegmenterConfig {
+    /// The bare DP: no normalization, repair, acronyms, clock times,
+    /// unknown merging, frequency bonus or QA prefix merging. Useful for
+    /// seeing what the dictionary alone decides.
+    pub fn all_disabled() -> Self {
+        Self {
+            enable_normalization: false,
+            enable_repair_mode: false,
+            enable_acronym_detection: false,
+            enable_unknown_merging: false,
+            enable_frequency_costs: false,
+            enable_time_detection: false,
+            qa_prefix_merge: QaPrefixMerge::Never,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for SegmenterConfig {
+    fn default() -> Self {
+        Self {
+            enable_normalization: true,
+            enable_repair_mode: true,
+            orphaned_vowels: OrphanedVowels::Repair,
+            enable_acronym_detection: true,
+            min_acronym_clusters: 1,
+            max_acronym_clusters: usize::MAX,
+            acronym_terminators: utils::DEFAULT_ACRONYM_TERMINATORS.to_vec(),
+            enable_unknown_merging: true,
+            enable_frequency_costs: true,
+            frequency_weight: 0.1,
+            normalization: NormalizationConfig::default(),
+            latin_sentence_terminators: false,
+            unknown_cost_override: None,
+            default_cost_override: None,
+            punctuation_attachment: PunctuationAttachment::Standalone,
+            merge_unknowns_across: SeparatorPolicy::Never,
+            trailing_coeng: TrailingCoeng::Keep,
+            confidence: ConfidenceMapping::default(),
+            dotted_numbers: DottedNumbers::Join,
+            decimal_convention: DecimalStyle::Dot,
+            enable_time_detection: false,
+            non_khmer_fallback: Fallback::PerChar,
+            qa_prefix_merge: QaPrefixMerge::Always,
+            reduplication: Reduplication::Split,
+            unknown_ramp: None,
+            min_merge_char_len: 0,
+            min_dict_match_chars: 1,
+            compound_pairs: HashSet::new(),
+            join_hyphenated_mixed: false,
+            force_boundary_chars: HashSet::new(),
+            stop_words: HashSet::new(),
+            default_separator: None,
+            presplit_boundaries: vec![' ', '\u{17D4}'],
+        }
+    }
+}
+
+/// Where the CLI looks for `khmer_dictionary.kdict`, relative to the working
+/// directory: next to the binary's cwd, then the repo's `port/common`.
+pub const DEFAULT_DICT_PATHS: &[&str] = &[
+    "khmer_dictionary.kdict",
+    "../../port/common/khmer_dictionary.kdict",
+    "../common/khmer_dictionary.kdict",
+];
+
+pub struct KhmerSegmenter {
+    kdict: Option<Arc<KDict>>,
+    user_words: UserWords,
+    rule_engine: RuleEngine,
+    config: SegmenterConfig,
+    #[cfg(feature = "probe-stats")]
+    probes: ProbeCounter,
+}
+
+/// Clones share the dictionary (one mapping for every clone) and copy the
+/// config. With `probe-stats`, each clone starts its own zeroed totals.
+impl Clone for KhmerSegmenter {
+    fn clone(&self) -> Self {
+        self.with_config(self.config.clone())
+    }
+}
+
+/// Words added with `add_word`, looked up next to the dictionary's table.
+#[derive(Clone, Default)]
+struct UserWords {
+    costs: HashMap<String, f32>,
+    // Longest word in bytes; the DP scans this far for user words
+    max_len: usize,
+}
+
+impl UserWords {
+    fn contains(&self, word: &str) -> bool {
+        !self.costs.is_empty() && self.costs.contains_key(word)
+    }
+}
+
+/// Reusable buffers for `segment_spans_into` and `segment_with_scratch`. Keep
+/// one per thread and pass it to every call; after warm-up the DP table and
+/// span buffers stop allocating.
+#[derive(Default)]
+pub struct Scratch {
+    text: String,
+    dp: Vec<State<TokenKind>>,
+    merged: Vec<(usize, usize)>,
+    spans: Vec<(usize, usize)>,
+}
+
+impl Scratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Text the spans from the last `segment_spans_into` call index into
+    /// (the normalized input when normalization is enabled).
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+// DP table entries a thread keeps between calls; one very long line should
+// not pin its table for the life of the thread.
+const MAX_RETAINED_DP: usize = 1 << 16;
+
+thread_local! {
+    static SCRATCH: RefCell<Scratch> = RefCell::new(Scratch::new());
+}
+
+// Runs `f` with this thread's scratch buffers, or fresh ones if they are
+// already in use further up the stack.
+fn with_thread_scratch<R>(f: impl FnOnce(&mut Scratch) -> R) -> R {
+    SCRATCH.with(|cell| match cell.try_borrow_mut() {
+        Ok(mut scratch) => {
+            let out = f(&mut scratch);
+            if scratch.dp.capacity() > MAX_RETAINED_DP {
+                *scratch = Scratch::new();
+            }
+            out
+        }
+        Err(_) => f(&mut Scratch::new()),
+    })
+}
+
+impl KhmerSegmenter {
+    /// Loads the dictionary at `kdict_path` (memory-mapped, see `KDict::load`)
+    /// or runs without one when `None`, in which case every input comes back
+    /// as a single token.
+    ///
+    /// # Errors
+    /// Whatever `KDict::load` reports for the path. On wasm32 any path fails
+    /// with `Unsupported`, as there is no filesystem; use `new_with_dict` there.
+    pub fn new(kdict_path: Option<&str>, config: SegmenterConfig) -> std::io::Result<Self> {
+        let kdict = kdict_path.map(KDict::load).transpose()?;
+        Ok(Self::new_with_dict(kdict, config))
+    }
+
+    /// Tries each path in order and uses the first dictionary that loads.
+    /// When none does, the error lists every path tried and why it failed.
+    pub fn new_searching(paths: &[&str], config: SegmenterConfig) -> std::io::Result<Self> {
+        let mut failures = Vec::with_capacity(paths.len());
+        for path in paths {
+            match KDict::load(path) {
+                Ok(kdict) => return Ok(Self::new_with_dict(Some(kdict), config)),
+                Err(e) => failures.push(format!("{}: {}", path, e)),
+            }
+        }
+        Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("no dictionary could be loaded (tried {})", failures.join("; ")),
+        ))
+    }
+
+    /// Loads every dictionary in `paths` and segments against all of them,
+    /// merged into one table at load time (`kdict::build::merge`): a word in
+    /// several keeps its lowest cost, the header costs are the first
+    /// dictionary's and `max_word_length` is the longest word overall, so the
+    /// DP and unknown-merging see every word. One path is mapped as in
+    /// `new`; several are copied into memory. No paths means no dictionary.
+    ///
+    /// # Errors
+    /// The first `KDict::load` failure, or `InvalidData` if the merged table
+    /// does not load.
+    pub fn new_multi(paths: &[&str], config: SegmenterConfig) -> std::io::Result<Self> {
+        let mut dicts = paths.iter().map(|path| KDict::load(path)).collect::<std::io::Result<Vec<_>>>()?;
+        let kdict = if dicts.len() <= 1 {
+            dicts.pop()
+        } else {
+            let refs: Vec<&KDict> = dicts.iter().collect();
+            crate::kdict::build::merge(&refs).map(KDict::from_bytes).transpose()?
+        };
+        Ok(Self::new_with_dict(kdict, config))
+    }
+
+    /// Takes ownership of an already loaded dictionary.
+    pub fn new_with_dict(kdict: Option<KDict>, config: SegmenterConfig) -> Self {
+        Self::new_with_shared_dict(kdict.map(Arc::new), config)
+    }
+
+    /// Uses a dictionary shared with other segmenters; see also `with_config`.
+    pub fn new_with_shared_dict(kdict: Option<Arc<KDict>>, config: SegmenterConfig) -> Self {
+        Self {
+            kdict,
+            user_words: UserWords::default(),
+            rule_engine: RuleEngine {
+                qa_prefix: config.qa_prefix_merge,
+                keep_orphaned_vowels: config.enable_repair_mode && config.orphaned_vowels == OrphanedVowels::Flag,
+                boundary_chars: config.force_boundary_chars.clone(),
+            },
+            config,
+            #[cfg(feature = "probe-stats")]
+            probes: ProbeCounter::default(),
+        }
+    }
+    
+    /// The dictionary this segmenter scores against, if it has one.
+    pub fn dictionary(&self) -> Option<&KDict> {
+        self.kdict.as_deref()
+    }
+
+    /// A segmenter over the same dictionary with a different config. The
+    /// dictionary is shared, not copied, so this is as cheap as `clone`.
+    #[must_use]
+    pub fn with_config(&self, config: SegmenterConfig) -> Self {
+        let mut seg = Self::new_with_shared_dict(self.kdict.clone(), config);
+        seg.user_words = self.user_words.clone();
+        seg
+    }
+
+    /// Adds `word` at `cost` on top of the dictionary, which stays read-only
+    /// (the mmap is not touched). The DP matches it like a dictionary word,
+    /// however long, and the passes treat it as known; re-adding a word
+    /// replaces its cost. A word also in the dictionary competes at both
+    /// costs, so the cheaper one wins. Ignored without a dictionary.
+    pub fn add_word(&mut self, word: &str, cost: f32) {
+        if word.is_empty() { return; }
+        self.user_words.max_len = self.user_words.max_len.max(word.len());
+        self.user_words.costs.insert(word.to_string(), cost);
+    }
+
+    // In the dictionary or added with `add_word`.
+    fn is_known_word(&self, kd: &KDict, word: &str) -> bool {
+        kd.contains(word) || self.user_words.contains(word)
+    }
+
+    /// Dictionary probe totals of the DP lookups made by every segmentation
+    /// on this segmenter so far. Concurrent calls all add to the same totals.
+    #[cfg(feature = "probe-stats")]
+    pub fn probe_stats(&self) -> ProbeStats {
+        self.probes.snapshot()
+    }
+
+    /// `probe_stats`, then resets the totals; call before and after a batch
+    /// to measure just that batch.
+    #[cfg(feature = "probe-stats")]
+    pub fn take_probe_stats(&self) -> ProbeStats {
+        self.probes.take()
+    }
+
+    /// Segments `raw_text` and returns a new string with `separator`
+    /// (default `config.default_separator`, else U+200B ZERO WIDTH SPACE)
+    /// between tokens. The input is only
+    /// borrowed; the result is always freshly allocated, so dropping it
+    /// discards the work.
+    ///
+    /// The DP table and span buffers come from a per-thread `Scratch`, so
+    /// repeated calls on one thread only allocate the normalized text and
+    /// the result.
+    #[must_use]
+    pub fn segment(&self, raw_text: &str, separator: Option<&str>) -> String {
+        with_thread_scratch(|scratch| self.segment_with_scratch(raw_text, separator, scratch))
+    }
+
+    /// `segment`, but failures that `segment` papers over by returning the
+    /// whole text as one token are reported instead: no dictionary loaded
+    /// (text without Khmer is fine when `non_khmer_fallback` handles it), no
+    /// path through the DP, or a span that does not fall on character
+    /// boundaries.
+    ///
+    /// # Errors
+    /// `SegmentError::NoDictionary`, `Unreachable` or `InvalidBoundary`.
+    pub fn try_segment(&self, raw_text: &str, separator: Option<&str>) -> Result<String, SegmentError> {
+        with_thread_scratch(|scratch| {
+            let text = self.normalize(raw_text);
+            if let Some(err) = self.spans_into(&text, &mut scratch.dp, &mut scratch.merged, &mut scratch.spans) {
+                return Err(err);
+            }
+            let bad = scratch.spans.iter().flat_map(|&(s, e)| [s, e]).find(|&i| !text.is_char_boundary(i));
+            if let Some(at) = bad {
+                return Err(SegmentError::InvalidBoundary(at));
+            }
+            Ok(self.join_spans(&text, &scratch.spans, self.separator_or_default(separator)))
+        })
+    }
+
+    /// `segment` using the caller's buffers instead of the thread's own.
+    #[must_use]
+    pub fn segment_with_scratch(&self, raw_text: &str, separator: Option<&str>, scratch: &mut Scratch) -> String {
+        let text = self.normalize(raw_text);
+        self.spans_into(&text, &mut scratch.dp, &mut scratch.merged, &mut scratch.spans);
+        self.join_spans(&text, &scratch.spans, self.separator_or_default(separator))
+    }
+
+    /// `segment` joined once per separator in `seps`, from a single
+    /// segmentation pass. Separator-token attachment applies to every join.
+    #[must_use]
+    pub fn segment_multi(&self, raw_text: &str, seps: &[&str]) -> Vec<String> {
+        let text = self.normalize(raw_text);
+        let segments = self.compute_spans(&text);
+        seps.iter().map(|sep| self.join_spans(&text, &segments, sep)).collect()
+    }
+
+    /// `segment`, plus the cost of the Viterbi path it was built from: the
+    /// DP optimum at the end of the normalized text (before trailing coengs
+    /// split off by `trailing_coeng`), as summed transition costs and run
+    /// penalties. It is taken before the rule engine, unknown-merging and
+    /// the other passes, which change tokens but never rescore them. The
+    /// cost is 0 when the DP does not run (empty text, no dictionary, a
+    /// non-Khmer `non_khmer_fallback`).
+    #[must_use]
+    pub fn segment_with_cost(&self, raw_text: &str, separator: Option<&str>) -> (String, f32) {
+        let text = self.normalize(raw_text);
+        let mut dp = Vec::new();
+        let mut spans = Vec::new();
+        self.spans_into(&text, &mut dp, &mut Vec::new(), &mut spans);
+        let cost = dp.last().filter(|state| state.cost < UNREACHED).map_or(0.0, |state| state.cost);
+        (self.join_spans(&text, &spans, self.separator_or_default(separator)), cost)
+    }
+
+    /// `segment`, with the normalized text first cut at every character of
+    /// `config.presplit_boundaries` and the DP run on each fragment on its
+    /// own (each boundary character is a token by itself). Shorter DP runs
+    /// are cheaper on long lines, and the fragments are independent.
+    ///
+    /// The result equals `segment` whenever the boundaries are genuine
+    /// separators, except where whole-line segmentation would have looked
+    /// across one:
+    /// - dictionary phrases containing a boundary (`"ថ្ងៃ នេះ"`) no longer match;
+    /// - `SeparatorPolicy::Allow` cannot merge unknowns across a boundary;
+    /// - `TrailingCoeng::Drop`/`Flag` apply at the end of every fragment.
+    #[must_use]
+    pub fn segment_presplit(&self, raw_text: &str, separator: Option<&str>) -> String {
+        let text = self.normalize(raw_text);
+        let boundaries = &self.config.presplit_boundaries;
+
+        let mut segments = Vec::new();
+        let (mut dp, mut merged, mut fragment_spans) = (Vec::new(), Vec::new(), Vec::new());
+        let mut push_fragment = |start: usize, end: usize, segments: &mut Vec<(usize, usize)>| {
+            if start == end { return; }
+            self.spans_into(&text[start..end], &mut dp, &mut merged, &mut fragment_spans);
+            segments.extend(fragment_spans.iter().map(|&(s, e)| (start + s, start + e)));
+        };
+
+        let mut start = 0;
+        for (i, c) in text.char_indices() {
+            if boundaries.contains(&c) {
+                push_fragment(start, i, &mut segments);
+                start = i + c.len_utf8();
+                segments.push((i, start));
+            }
+        }
+        push_fragment(start, text.len(), &mut segments);
+
+        self.join_spans(&text, &segments, self.separator_or_default(separator))
+    }
+
+    // A per-call separator, else the configured default, else ZWSP.
+    fn separator_or_default<'s>(&'s self, separator: Option<&'s str>) -> &'s str {
+        separator.or(self.config.default_separator.as_deref()).unwrap_or("\u{200B}")
+    }
+
+    /// Re-joins text another tool already segmented, without running the DP:
+    /// `text` is split on `existing_sep`, each token is normalized on its own
+    /// (per `config.enable_normalization`) and the tokens are joined with
+    /// `out_sep`. Tokens that are empty, or become empty once normalized
+    /// (a stray ZWSP between two separators), are dropped.
+    #[must_use]
+    pub fn resegment_preserving(&self, text: &str, existing_sep: &str, out_sep: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        for token in text.split(existing_sep) {
+            let token = self.normalize(token);
+            if token.is_empty() { continue; }
+            if !result.is_empty() { result.push_str(out_sep); }
+            result.push_str(&token);
+        }
+        result
+    }
+
+    /// Like `segment`, but skips normalization entirely. The caller guarantees
+    /// `text` already went through `khmer_normalize` (with this segmenter's
+    /// normalization config); debug builds assert that contract.
+    #[must_use]
+    pub fn segment_normalized(&self, text: &str, separator: Option<&str>) -> String {
+        debug_assert!(
+            khmer_normalize_with(text, &self.config.normalization) == text,
+            "segment_normalized called with text that is not normalized"
+        );
+        let segments = self.compute_spans(text);
+        self.join_spans(text, &segments, self.separator_or_default(separator))
+    }
+
+    /// Token byte ranges of `text`, which is segmented exactly as given (no
+    /// normalization), so every range slices it: `&text[range.clone()]`.
+    /// Normalize first with `khmer_normalize` for best results.
+    #[must_use]
+    pub fn segment_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        self.compute_spans(text).into_iter().map(|(s, e)| s..e).collect()
+    }
+
+    /// The token spans `segment` joins, as byte ranges of the normalized
+    /// text, plus whether normalization changed anything. When it did not
+    /// (or normalization is off), the spans index `raw_text` directly;
+    /// otherwise map them back with `segment_with_mapping` or use
+    /// `segment_original_tokens`.
+    #[must_use]
+    pub fn segment_spans(&self, raw_text: &str) -> (Vec<(usize, usize)>, bool) {
+        if !self.config.enable_normalization {
+            return (self.compute_spans(raw_text), false);
+        }
+        let (text, changed) = khmer_normalize_checked_with(raw_text, &self.config.normalization);
+        (self.compute_spans(&text), changed)
+    }
+
+    /// The tokens of `segment`, borrowed from `text` without copying. When
+    /// normalization is enabled and changes the text, the tokens exist only
+    /// in the normalized copy, so this fails with `NormalizationRequired`
+    /// rather than allocating; use `segment_original_tokens` (raw slices) or
+    /// normalize first. The check still normalizes once, as `segment` does.
+    ///
+    /// # Errors
+    /// `SegmentError::NormalizationRequired`, as above.
+    pub fn segment_tokens<'a>(&self, text: &'a str) -> Result<Vec<&'a str>, SegmentError> {
+        let (spans, changed) = self.segment_spans(text);
+        if changed {
+            return Err(SegmentError::NormalizationRequired);
+        }
+        Ok(spans.into_iter().map(|(s, e)| &text[s..e]).collect())
+    }
+
+    /// The `k` cheapest DP paths through the normalized text, cheapest first,
+    /// each with its total DP cost and its spans after the rule engine,
+    /// unknown-merging and the other passes `segment` runs. Spans index the
+    /// normalized text, as in `segment_spans`. Ties are broken as in
+    /// `viterbi_nbest`, so the order is stable; with `k == 1` the spans are
+    /// `segment_spans`'s.
+    ///
+    /// Paths the passes turn into the same tokens are reported once, at the
+    /// cheaper cost, so fewer than `k` may come back. Text the DP does not
+    /// handle (no dictionary, a non-Khmer `non_khmer_fallback`) has the one
+    /// segmentation `segment` gives it, at cost 0.
+    #[must_use]
+    pub fn segment_nbest(&self, raw_text: &str, k: usize) -> Vec<(f32, Vec<(usize, usize)>)> {
+        let text = self.normalize(raw_text);
+        let text = text.as_ref();
+        if text.is_empty() || k == 0 { return Vec::new(); }
+        let body = match self.config.trailing_coeng {
+            TrailingCoeng::Keep => text.len(),
+            TrailingCoeng::Drop | TrailingCoeng::Flag => text.trim_end_matches('\u{17D2}').len(),
+        };
+        let dp_runs = body > 0 && (self.config.non_khmer_fallback == Fallback::PerChar || utils::contains_khmer(&text[..body]));
+        let kd = match self.kdict.as_deref() {
+            Some(kd) if dp_runs => kd,
+            _ => return vec![(0.0, self.compute_spans(text))],
+        };
+        let transitions = KhmerTransitions::new(&self.config, kd, &text[..body]).with_user_words(&self.user_words);
+        let mut scratch = Vec::new();
+        let mut results: Vec<(f32, Vec<(usize, usize)>)> = Vec::with_capacity(k);
+        for (cost, mut spans) in viterbi_nbest(&transitions, body, k) {
+            self.refine_spans(&text[..body], kd, &mut scratch, &mut spans);
+            if body < text.len() && self.config.trailing_coeng == TrailingCoeng::Flag {
+                spans.push((body, text.len()));
+            }
+            if !results.iter().any(|(_, seen)| *seen == spans) {
+                results.push((cost, spans));
+            }
+        }
+        results
+    }
+
+    /// Segments text the caller already normalized (by any means; it is used
+    /// as is) and returns the token spans as byte ranges of the caller's raw
+    /// text, translated through `raw_to_norm`. For editors that keep their
+    /// own normalized view and offset bookkeeping.
+    #[must_use]
+    pub fn segment_with_mapping(&self, normalized: &str, raw_to_norm: &OffsetMap) -> Vec<(usize, usize)> {
+        self.compute_spans(normalized)
+            .into_iter()
+            .map(|(s, e)| (raw_to_norm.to_raw(s), raw_to_norm.to_raw(e)))
+            .collect()
+    }
+
+    /// Round-trip form of `segment`: the normalized text decides the token
+    /// boundaries, but every token is sliced from `raw_text` itself, so with
+    /// an empty separator the output is byte-identical to the input.
+    ///
+    /// Where normalization folded, merged or stripped characters, the token
+    /// covers all the raw characters involved (a folded `ឤ` split as `អ` |
+    /// `ា...` stays whole in the first token); stripped characters go to the
+    /// token before them, or the first token at the start of the text.
+    #[must_use]
+    pub fn segment_original(&self, raw_text: &str, separator: Option<&str>) -> String {
+        let spans = self.original_spans(raw_text);
+        self.join_spans(raw_text, &spans, self.separator_or_default(separator))
+    }
+
+    /// The tokens of `segment_original`, borrowed from `raw_text`. They
+    /// concatenate back to `raw_text` exactly.
+    #[must_use]
+    pub fn segment_original_tokens<'a>(&self, raw_text: &'a str) -> Vec<&'a str> {
+        self.original_spans(raw_text).into_iter().map(|(s, e)| &raw_text[s..e]).collect()
+    }
+
+    /// Token positions in `raw_text` in both bytes and chars (Unicode scalar
+    /// values, as Python's `str` counts them), from the same mapping as
+    /// `segment_original_tokens`: the spans tile `raw_text`. Char offsets are
+    /// accumulated in one pass as the spans are emitted.
+    #[must_use]
+    pub fn segment_char_spans(&self, raw_text: &str) -> Vec<CharSpan> {
+        let mut chars = 0;
+        self.original_spans(raw_text).into_iter().map(|(s, e)| {
+            let char_start = chars;
+            chars += raw_text[s..e].chars().count();
+            CharSpan { byte_start: s, byte_end: e, char_start, char_end: chars }
+        }).collect()
+    }
+
+    // Token spans of the normalized text mapped back onto `raw_text`. Only the
+    // ends are mapped, snapped down to a char boundary, so the spans tile the
+    // raw text; tokens that map to nothing (entirely inside a fold expansion)
+    // are absorbed by their neighbour.
+    fn original_spans(&self, raw_text: &str) -> Vec<(usize, usize)> {
+        let (text, map) = if self.config.enable_normalization {
+            khmer_normalize_mapped_with(raw_text, &self.config.normalization)
+        } else {
+            (raw_text.to_string(), OffsetMap::new())
+        };
+        let spans = self.compute_spans(&text);
+
+        let mut out = Vec::with_capacity(spans.len());
+        let mut start = 0;
+        for (k, &(_, e)) in spans.iter().enumerate() {
+            let mut end = if k + 1 == spans.len() { raw_text.len() } else { map.to_raw(e).min(raw_text.len()) };
+            while !raw_text.is_char_boundary(end) { end -= 1; }
+            if end > start {
+                out.push((start, end));
+                start = end;
+            }
+        }
+        if start < raw_text.len() {
+            // Nothing survived normalization, or the tail was dropped
+            out.push((start, raw_text.len()));
+        }
+        out
+    }
+
+    /// Splits `raw_text` into sentences of word tokens. The word DP runs over
+    /// the whole text first, so dots inside acronyms and decimals never end a
+    /// sentence; only a standalone terminator token does. Terminators stay at
+    /// the end of their sentence, runs like `?!` or `...` stay together,
+    /// whitespace between sentences is dropped, and the abbreviation `។ល។`
+    /// ("etc.") does not end a sentence.
+    #[must_use]
+    pub fn segment_document(&self, raw_text: &str) -> Vec<Vec<String>> {
+        let text = self.normalize(raw_text);
+        let spans = self.compute_spans(&text);
+        let tok = |k: usize| &text[spans[k].0..spans[k].1];
+
+        let mut sentences = Vec::new();
+        let mut current: Vec<String> = Vec::new();
+        let mut closing = false;
+        let mut k = 0;
+        while k < spans.len() {
+            if tok(k) == "\u{17D4}" && k + 2 < spans.len() && tok(k + 1) == "\u{179B}" && tok(k + 2) == "\u{17D4}" {
+                if closing {
+                    sentences.push(std::mem::take(&mut current));
+                    closing = false;
+                }
+                current.extend((k..k + 3).map(|j| tok(j).to_string()));
+                k += 3;
+                continue;
+            }
+
+            let t = tok(k);
+            if self.is_sentence_terminator(t) {
+                current.push(t.to_string());
+                closing = true;
+            } else if t.chars().all(char::is_whitespace) && (closing || current.is_empty()) {
+                // Whitespace between sentences
+            } else {
+                if closing {
+                    sentences.push(std::mem::take(&mut current));
+                    closing = false;
+                }
+                current.push(t.to_string());
+            }
+            k += 1;
+        }
+        if !current.is_empty() {
+            sentences.push(current);
+        }
+        sentences
+    }
+
+    fn is_sentence_terminator(&self, token: &str) -> bool {
+        let mut chars = token.chars();
+        match (chars.next(), chars.next()) {
+            (Some('\u{17D4}' | '\u{17D5}'), None) => true,
+            (Some('.' | '!' | '?'), None) => self.config.latin_sentence_terminators,
+            _ => false,
+        }
+    }
+
+    /// Byte length of each token, in order, as `segment` would emit them.
+    #[must_use]
+    pub fn segment_lengths(&self, raw_text: &str) -> Vec<usize> {
+        let text = self.normalize(raw_text);
+        self.compute_spans(&text).iter().map(|(s, e)| e - s).collect()
+    }
+
+    /// Codepoint count of each token (`chars().count()`), in `segment` order.
+    #[must_use]
+    pub fn segment_char_lengths(&self, raw_text: &str) -> Vec<usize> {
+        let text = self.normalize(raw_text);
+        self.compute_spans(&text).iter().map(|&(s, e)| text[s..e].chars().count()).collect()
+    }
+
+    /// Number of user-visible clusters in each token: one per Khmer cluster
+    /// (base plus its subscripts, vowels and signs) and one per character
+    /// elsewhere (per grapheme cluster with the `grapheme-clusters` feature). Closer to the rendered width than a codepoint count.
+    #[must_use]
+    pub fn segment_cluster_lengths(&self, raw_text: &str) -> Vec<usize> {
+        let text = self.normalize(raw_text);
+        self.compute_spans(&text).iter().map(|&(s, e)| utils::cluster_count(&text[s..e])).collect()
+    }
+
+    /// Tokens as shared strings from `interner`, so identical tokens across
+    /// calls point at the same allocation. Same tokens as `segment`.
+    pub fn segment_interned<I: Interner + ?Sized>(&self, raw_text: &str, interner: &mut I) -> Vec<Arc<str>> {
+        let text = self.normalize(raw_text);
+        self.compute_spans(&text).iter().map(|&(s, e)| interner.intern(&text[s..e])).collect()
+    }
+
+    /// Tokens as `vocab` IDs, without building the token strings. Number and
+    /// time tokens map to `Vocab::NUMBER`, acronyms to `Vocab::ACRONYM`, and
+    /// unknown clusters as well as any token missing from `vocab` to
+    /// `Vocab::UNK`.
+    #[must_use]
+    pub fn segment_ids(&self, raw_text: &str, vocab: &Vocab) -> Vec<u32> {
+        let text = self.normalize(raw_text);
+        let mut dp = Vec::new();
+        let mut spans = Vec::new();
+        self.spans_into(&text, &mut dp, &mut Vec::new(), &mut spans);
+
+        spans.iter().map(|&(s, e)| {
+            // Spans assembled by the rule engine or merging are looked up
+            let kind = dp.get(e).filter(|state| state.prev_idx == s as isize).map(|state| state.kind);
+            match kind {
+                Some(TokenKind::Number | TokenKind::Time) => Vocab::NUMBER,
+                Some(TokenKind::Acronym) => Vocab::ACRONYM,
+                Some(TokenKind::Unknown) => Vocab::UNK,
+                _ => vocab.get(&text[s..e]).unwrap_or(Vocab::UNK),
+            }
+        }).collect()
+    }
+
+    /// Tokens with how they were produced. Tokens that are dictionary words
+    /// carry the matched entry's table slot, cost and category (`None` when
+    /// the dictionary has no category for them); `dictionary().entry(slot)`
+    /// gives the whole `KDictEntry` without hashing the word again.
+    #[must_use]
+    pub fn segment_detailed(&self, raw_text: &str) -> Vec<Token> {
+        let text = self.normalize(raw_text);
+        let mut dp = Vec::new();
+        let mut spans = Vec::new();
+        self.spans_into(&text, &mut dp, &mut Vec::new(), &mut spans);
+
+        spans.iter().map(|&(s, e)| {
+            let slot = self.kdict.as_ref().and_then(|kd| kd.lookup(text[s..e].as_bytes()).map(|slot| (kd, slot)));
+            let user_cost = self.user_words.costs.get(&text[s..e]).copied();
+            let kind = self.span_kind(&text, &dp, (s, e), slot.is_some() || user_cost.is_some());
+            let slot = slot.filter(|_| kind == TokenKind::Word);
+            let user_cost = user_cost.filter(|_| kind == TokenKind::Word);
+            // Every span boundary lies on the best path, whose prefixes are
+            // optimal, so the cost difference is what the path spent here
+            let path_cost = match (dp.get(s), dp.get(e)) {
+                (Some(a), Some(b)) if kind != TokenKind::Incomplete => Some((b.cost - a.cost).max(0.0)),
+                _ => None,
+            };
+            Token {
+                text: text[s..e].to_string(),
+                kind,
+                slot: slot.map(|(_, slot)| slot),
+                cost: slot.map(|(kd, slot)| kd.entry(slot).cost).or(user_cost),
+                category: slot.map(|(kd, slot)| kd.category(slot)).filter(|&c| c != 0),
+                flags: slot.map(|(kd, slot)| kd.flags(slot)).filter(|&f| f != 0),
+                path_cost,
+                stop: !self.config.stop_words.is_empty() && self.config.stop_words.contains(&text[s..e]),
+            }
+        }).collect()
+    }
+
+    // The `TokenKind` `segment_detailed` reports for a span of `text`, given
+    // the DP table and whether the span is a dictionary word. Spans changed
+    // by the rule engine or merging have no single DP edge; they count as
+    // words only if the dictionary has them.
+    fn span_kind(&self, text: &str, dp: &[State<TokenKind>], (s, e): (usize, usize), in_dict: bool) -> TokenKind {
+        match dp.get(e) {
+            _ if e == text.len() && self.config.trailing_coeng == TrailingCoeng::Flag
+                && text[s..e].chars().all(|c| c == '\u{17D2}') => TokenKind::Incomplete,
+            Some(state) if state.prev_idx == s as isize => state.kind,
+            _ if self.config.reduplication == Reduplication::Merge && is_reduplication(&text[s..e]) => TokenKind::Reduplication,
+            _ if in_dict => TokenKind::Word,
+            _ => TokenKind::Unknown,
+        }
+    }
+
+    /// `segment` over every line on the rayon pool, with a `BatchStats`
+    /// summary of the tokens gathered in the same pass (each line's counts
+    /// are merged in as it finishes). Kinds are those `segment_detailed`
+    /// reports. The outputs are in input order.
+    pub fn segment_batch_stats<S: AsRef<str> + Sync>(&self, lines: &[S], separator: Option<&str>) -> (Vec<String>, BatchStats) {
+        use rayon::prelude::*;
+
+        let per_line: Vec<(String, BatchStats)> = lines.par_iter().map(|line| {
+            let text = self.normalize(line.as_ref());
+            let mut dp = Vec::new();
+            let mut spans = Vec::new();
+            self.spans_into(&text, &mut dp, &mut Vec::new(), &mut spans);
+
+            let mut stats = BatchStats { lines: 1, ..Default::default() };
+            for &(s, e) in &spans {
+                let in_dict = self.kdict.as_ref().is_some_and(|kd| self.is_known_word(kd, &text[s..e]));
+                stats.add_token(self.span_kind(&text, &dp, (s, e), in_dict), text[s..e].chars().count());
+            }
+            (self.join_spans(&text, &spans, self.separator_or_default(separator)), stats)
+        }).collect();
+
+        let mut total = BatchStats::default();
+        let outputs = per_line.into_iter().map(|(out, stats)| {
+            total += stats;
+            out
+        }).collect();
+        (outputs, total)
+    }
+
+    /// `segment` over every line of `reader`, writing each result plus a
+    /// newline to `writer` in input order. Lines are read and segmented in
+    /// bounded windows on the rayon pool (see `stream::process_lines`), so
+    /// memory use does not grow with the input.
+    ///
+    /// Stops at the first read or write error; lines before it have been
+    /// written. A line that is not valid UTF-8 is an `InvalidData` error.
+    pub fn segment_reader<R: BufRead, W: Write>(&self, reader: R, writer: W, separator: Option<&str>) -> io::Result<()> {
+        let summary = self.segment_reader_with(reader, writer, separator, ReaderOptions::default(), |_, segmented| segmented + "\n")?;
+        summary.read_error.map_or(Ok(()), Err)
+    }
+
+    /// `segment_reader` with the reading controlled by `options` (line
+    /// limit, invalid UTF-8, BOM) and each output record built by
+    /// `render(original_line, segmented_line)`, newline included.
+    ///
+    /// Only write errors are returned as `Err`. A read error, or a line that
+    /// is not valid UTF-8 unless `options.skip_invalid_utf8` is set, ends the
+    /// input and is reported in the summary instead.
+    pub fn segment_reader_with<R, W, F>(&self, mut reader: R, mut writer: W, separator: Option<&str>, options: ReaderOptions, render: F) -> io::Result<ReaderSummary>
+    where
+        R: BufRead,
+        W: Write,
+        F: Fn(&str, String) -> String + Sync,
+    {
+        let mut read_error = None;
+        let mut at_start = true;
+        let mut buf = Vec::new();
+        let lines = std::iter::from_fn(|| loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => {
+                    read_error = Some(e);
+                    return None;
+                }
+            }
+            if buf.ends_with(b"\n") {
+                buf.pop();
+                if buf.ends_with(b"\r") { buf.pop(); }
+            }
+            if std::mem::take(&mut at_start) && options.strip_bom && buf.starts_with("\u{FEFF}".as_bytes()) {
+                buf.drain(..3);
+            }
+            match String::from_utf8(std::mem::take(&mut buf)) {
+                Ok(line) => return Some(line),
+                Err(_) if options.skip_invalid_utf8 => {}
+                Err(e) => {
+                    read_error = Some(io::Error::new(io::ErrorKind::InvalidData, e));
+                    return None;
+                }
+            }
+        }).fuse().take(options.max_lines);
+
+        let lines = process_lines(lines, &mut writer, DEFAULT_WINDOW, |l| {
+            let segmented = if options.keep_original {
+                self.segment_original(l, separator)
+            } else {
+                self.segment(l, separator)
+            };
+            render(l, segmented)
+        })?;
+        Ok(ReaderSummary { lines, read_error })
+    }
+
+    /// `segment_detailed` rendered as an HTML fragment for visual review:
+    /// each token becomes `<span class="tok kind-word">...</span>` (see
+    /// `TokenKind::name`), while separator tokens are written bare between
+    /// the spans. Token text is escaped (`&`, `<`, `>`).
+    #[must_use]
+    pub fn segment_html(&self, raw_text: &str) -> String {
+        let mut html = String::with_capacity(raw_text.len() * 4);
+        for token in self.segment_detailed(raw_text) {
+            if token.kind == TokenKind::Separator {
+                escape_html_into(&token.text, &mut html);
+                continue;
+            }
+            html.push_str("<span class=\"tok kind-");
+            html.push_str(token.kind.name());
+            html.push_str("\">");
+            escape_html_into(&token.text, &mut html);
+            html.push_str("</span>");
+        }
+        html
+    }
+
+    /// Tokens paired with a confidence in [0, 1] computed from their path cost
+    /// per `config.confidence` (see `ConfidenceMapping`). Dictionary words
+    /// score high, unknown clusters low. Tokens without a path cost (no
+    /// dictionary, flagged trailing coengs) score 0.
+    #[must_use]
+    pub fn segment_confidence(&self, raw_text: &str) -> Vec<(String, f32)> {
+        let mapping = &self.config.confidence;
+        let zero_at = mapping.zero_at.unwrap_or_else(|| match self.kdict {
+            Some(ref kd) => self.config.unknown_cost_override.unwrap_or(unsafe { (*kd.header).unknown_cost }),
+            None => 1.0,
+        });
+
+        self.segment_detailed(raw_text).into_iter().map(|t| {
+            let confidence = match (t.kind, t.path_cost) {
+                (TokenKind::Number | TokenKind::Time, _) => mapping.number,
+                (TokenKind::Separator, _) => mapping.separator,
+                (_, Some(cost)) if zero_at > 0.0 => (1.0 - (cost / zero_at).clamp(0.0, 1.0)).powf(mapping.gamma),
+                _ => 0.0,
+            };
+            (t.text, confidence)
+        }).collect()
+    }
+
+    /// Tokens grouped into runs of the same script, classified by each
+    /// token's first character. Separator tokens (`Script::Common`) stay in
+    /// the run they follow, so `hello world` is one Latin run; separators
+    /// before the first other token join that token's run. Input that is only
+    /// separators gives a single `Common` run.
+    #[must_use]
+    pub fn segment_by_script(&self, raw_text: &str) -> Vec<(Script, Vec<String>)> {
+        let text = self.normalize(raw_text);
+        let mut runs: Vec<(Script, Vec<String>)> = Vec::new();
+        for (s, e) in self.compute_spans(&text) {
+            let token = &text[s..e];
+            let script = Script::of(token.chars().next().unwrap());
+            match runs.last_mut() {
+                Some((run_script, tokens)) if script == Script::Common || *run_script == script => {
+                    tokens.push(token.to_string());
+                }
+                Some((run_script, tokens)) if *run_script == Script::Common => {
+                    *run_script = script;
+                    tokens.push(token.to_string());
+                }
+                _ => runs.push((script, vec![token.to_string()])),
+            }
+        }
+        runs
+    }
+
+    /// Tokens paired with their numeric value: number tokens (Khmer or ASCII
+    /// digits, with `,`/`.` read per `config.decimal_convention`) carry
+    /// `Some(value)`, everything else and numbers that cannot be read
+    /// unambiguously (see `utils::parse_number_with`) carry `None`.
+    #[must_use]
+    pub fn segment_numbers(&self, raw_text: &str) -> Vec<(String, Option<f64>)> {
+        let text = self.normalize(raw_text);
+        self.compute_spans(&text)
+            .iter()
+            .map(|&(s, e)| (text[s..e].to_string(), utils::parse_number_with(&text[s..e], self.config.decimal_convention)))
+            .collect()
+    }
+
+    /// Allocation-free variant of span segmentation: fills `out` with the token
+    /// spans of `raw_text`, reusing the buffers held in `scratch`. The spans
+    /// index into `scratch.text()`. Normalization still allocates when enabled.
+    pub fn segment_spans_into(&self, raw_text: &str, scratch: &mut Scratch, out: &mut Vec<(usize, usize)>) {
+        if self.config.enable_normalization {
+            scratch.text = khmer_normalize_with(raw_text, &self.config.normalization);
+        } else {
+            scratch.text.clear();
+            scratch.text.push_str(raw_text);
+        }
+        self.spans_into(&scratch.text, &mut scratch.dp, &mut scratch.merged, out);
+    }
+
+    fn join_spans(&self, text: &str, segments: &[(usize, usize)], sep: &str) -> String {
+        let attach = self.config.punctuation_attachment;
+        let is_punct = |&(s, _): &(usize, usize)| text[s..].chars().next().is_some_and(utils::is_separator_cp);
+
+        // Estimate size includes separators
+        let total_len = segments.iter().map(|(s,e)| e - s).sum::<usize>() + segments.len() * sep.len();
+        let mut result = String::with_capacity(total_len);
+        
+        for (i, span) in segments.iter().enumerate() {
+            if i > 0 {
+                let glued = match attach {
+                    PunctuationAttachment::Standalone => false,
+                    PunctuationAttachment::AttachLeft => is_punct(span),
+                    PunctuationAttachment::AttachRight => is_punct(&segments[i - 1]),
+                };
+                if !glued { result.push_str(sep); }
+            }
+            result.push_str(&text[span.0..span.1]);
+        }
+        
+        result
+    }
+
+    fn normalize<'a>(&self, raw_text: &'a str) -> Cow<'a, str> {
+        if self.config.enable_normalization {
+            Cow::Owned(khmer_normalize_with(raw_text, &self.config.normalization))
+        } else {
+            Cow::Borrowed(raw_text)
+        }
+    }
+
+    fn compute_spans(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut segments = Vec::new();
+        with_thread_scratch(|scratch| self.spans_into(text, &mut scratch.dp, &mut scratch.merged, &mut segments));
+        segments
+    }
+
+    // Splits off coengs dangling at the end of the text per `trailing_coeng`,
+    // then segments the rest. Returns why the body fell back to a single
+    // span, if it did; `segments` holds the fallback either way.
+    fn spans_into(&self, text: &str, dp: &mut Vec<State<TokenKind>>, new_segments: &mut Vec<(usize, usize)>, segments: &mut Vec<(usize, usize)>) -> Option<SegmentError> {
+        let body = match self.config.trailing_coeng {
+            TrailingCoeng::Keep => text.len(),
+            TrailingCoeng::Drop | TrailingCoeng::Flag => text.trim_end_matches('\u{17D2}').len(),
+        };
+        let failure = self.body_spans_into(&text[..body], dp, new_segments, segments);
+        if body < text.len() && self.config.trailing_coeng == TrailingCoeng::Flag {
+            segments.push((body, text.len()));
+        }
+        failure
+    }
+
+    // Runs the DP, rule engine and unknown-merging over already-normalized text.
+    // Falls back to a single span covering the whole text when no path exists.
+    fn body_spans_into(&self, text: &str, dp: &mut Vec<State<TokenKind>>, new_segments: &mut Vec<(usize, usize)>, segments: &mut Vec<(usize, usize)>) -> Option<SegmentError> {
+        let n = text.len();
+        segments.clear();
+
+        if n == 0 { return None; }
+
+        if self.config.non_khmer_fallback != Fallback::PerChar && !utils::contains_khmer(text) {
+            fallback_spans(text, self.config.non_khmer_fallback, segments);
+            return None;
+        }
+
+        // Dictionary Accessors
+        let kd = match self.kdict.as_deref() {
+            Some(kd) => kd,
+            None => {
+                // Fallback if no dict (shouldn't happen in normal usage but safe)
+                segments.push((0, n));
+                return Some(SegmentError::NoDictionary);
+            }
+        };
+        let transitions = KhmerTransitions::new(&self.config, kd, text).with_user_words(&self.user_words);
+        #[cfg(feature = "probe-stats")]
+        let transitions = transitions.with_probe_counter(&self.probes);
+        #[cfg(feature = "parallel-scan")]
+        let found = if n >= PARALLEL_SCAN_MIN_BYTES {
+            viterbi_into(&PrecomputedTransitions::new(&transitions, text), n, dp, segments)
+        } else {
+            viterbi_into(&transitions, n, dp, segments)
+        };
+        #[cfg(not(feature = "parallel-scan"))]
+        let found = viterbi_into(&transitions, n, dp, segments);
+        if !found {
+            segments.push((0, n)); // Fail
+            return Some(SegmentError::Unreachable);
+        }
+        self.refine_spans(text, kd, new_segments, segments);
+        None
+    }
+
+    // The passes over a DP path: rule engine, unknown-merging and the
+    // optional merges.
+    fn refine_spans(&self, text: &str, kd: &KDict, new_segments: &mut Vec<(usize, usize)>, segments: &mut Vec<(usize, usize)>) {
+        // Rule Engine
+        self.rule_engine.apply_with_dict(text, segments, Some(kd));
+        debug_assert!(
+            segments.iter().all(|&(s, e)| s < e && text.is_char_boundary(s) && text.is_char_boundary(e)),
+            "rule engine produced a span off char boundaries"
+        );
+        
+        if self.config.enable_unknown_merging {
+             new_segments.clear();
+             
+             // Track consecutive unknowns as a single range
+             let mut unknown_start: Option<usize> = None;
+             let mut unknown_end: usize = 0;
+             // Allowed separator after an open unknown run, held until we know
+             // whether the run continues past it
+             let mut pending_sep: Option<(usize, usize)> = None;
+
+             for &(start, end) in segments.iter() {
+                 let seg = &text[start..end];
+                 let mut is_known = false;
+                 
+                 // Re-validation logic to determine if segment is "Known"
+                 let char_count = seg.chars().count();
+                 let first_char = seg.chars().next().unwrap(); // segments are never empty
+
+                 // 0. Separator tokens of any length never join an unknown run
+                 let is_sep = seg.chars().all(|c| utils::is_separator_cp(c) || self.config.force_boundary_chars.contains(&c));
+                 if is_sep {
+                     is_known = true;
+                 }
+                 // Nor do flagged orphaned vowels
+                 if self.rule_engine.keep_orphaned_vowels && char_count == 1 && utils::is_dependent_vowel(first_char) {
+                     is_known = true;
+                 }
+
+                 // 1. Check Separators (Single char)
+                 if char_count == 1
+                     && (utils::is_separator_cp(first_char)
+                         || utils::is_digit_cp(first_char) // Single digit
+                         || utils::is_valid_single_base_char(first_char))
+                 {
+                     is_known = true;
+                 }
+
+                 // 2. Check Numbers
+                 if !is_known {
+                     let num_len = utils::get_number_length_with(seg, self.config.decimal_convention);
+                     if num_len == seg.len() {
+                         is_known = true;
+                     }
+                 }
+
+                 // 3. Check Times
+                 if !is_known && self.config.enable_time_detection && utils::get_time_length(seg) == seg.len() {
+                     is_known = true;
+                 }
+
+                 // 4. Check Acronyms
+                 if !is_known
+                     && self.config.enable_acronym_detection
+                     && utils::is_acronym_start(seg, &self.config.acronym_terminators)
+                     && acronym_length(&self.config, &text[..start], seg) == seg.len()
+                 {
+                     is_known = true;
+                 }
+
+                 // 5. Dictionary Check
+                 if !is_known {
+                     is_known = self.is_known_word(kd, seg);
+                 }
+                 
+                 if is_known {
+                     if is_sep && unknown_start.is_some() && pending_sep.is_none()
+                         && self.config.merge_unknowns_across.allows(seg)
+                         && !seg.chars().any(|c| self.config.force_boundary_chars.contains(&c))
+                     {
+                         pending_sep = Some((start, end));
+                         continue;
+                     }
+                     // Flush unknown buffer if exists
+                     if let Some(u_start) = unknown_start {
+                         new_segments.push((u_start, unknown_end));
+                         unknown_start = None;
+                     }
+                     if let Some(sep) = pending_sep.take() {
+                         new_segments.push(sep);
+                     }
+                     new_segments.push((start, end));
+                 } else {
+                     // Extend unknown buffer
+                     if let Some(u_start) = unknown_start {
+                         // Check script continuity (Fix for mixing Khmer + Latin unknowns)
+                         let buffer_text = &text[u_start..unknown_end];
+                         if let Some(last_char) = buffer_text.chars().last() {
+                              if let Some(curr_char) = seg.chars().next() {
+                                  if utils::is_khmer_char(last_char) != utils::is_khmer_char(curr_char) {
+                                      // Flush previous buffer
+                                      new_segments.push((u_start, unknown_end));
+                                      unknown_start = None;
+                                      if let Some(sep) = pending_sep.take() {
+                                          new_segments.push(sep);
+                                      }
+                                  }
+                              }
+                         }
+                     }
+                     // Otherwise the run continues and absorbs the separator
+                     pending_sep = None;
+
+                     if unknown_start.is_none() {
+                         unknown_start = Some(start);
+                     }
+                     unknown_end = end;
+                 }
+             }
+             
+             // Flush remaining unknown buffer
+             if let Some(u_start) = unknown_start {
+                 new_segments.push((u_start, unknown_end));
+             }
+             if let Some(sep) = pending_sep {
+                 new_segments.push(sep);
+             }
+             
+             std::mem::swap(segments, new_segments);
+        }
+
+        if self.config.join_hyphenated_mixed {
+            merge_hyphenated_mixed(text, segments);
+        }
+        if !self.config.compound_pairs.is_empty() {
+            self.merge_compounds(text, kd, segments);
+        }
+        if self.config.reduplication == Reduplication::Merge {
+            merge_reduplications(text, segments);
+        }
+        if self.config.min_merge_char_len > 1 {
+            self.merge_short_tokens(text, kd, segments);
+        }
+    }
+
+    // Joins adjacent dictionary words listed in `compound_pairs`. Each pair
+    // is checked against the tokens as segmented, so chains extend one word
+    // at a time.
+    fn merge_compounds(&self, text: &str, kd: &KDict, segments: &mut Vec<(usize, usize)>) {
+        let known = |t: &str| !t.starts_with(utils::is_separator_cp) && self.is_known_word(kd, t);
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(segments.len());
+        let mut prev: Option<&str> = None;
+        for &(s, e) in segments.iter() {
+            let cur = &text[s..e];
+            let joins = prev.is_some_and(|p| {
+                known(p) && known(cur) && self.config.compound_pairs.contains(&(p.to_string(), cur.to_string()))
+            });
+            match merged.last_mut() {
+                Some(last) if joins => last.1 = e,
+                _ => merged.push((s, e)),
+            }
+            prev = Some(cur);
+        }
+        *segments = merged;
+    }
+
+    // Folds short bare-letter tokens into a neighbouring Khmer word, per
+    // `min_merge_char_len`. Prefers the left neighbour, like the rule engine.
+    fn merge_short_tokens(&self, text: &str, kd: &KDict, segments: &mut Vec<(usize, usize)>) {
+        let is_word = |&(s, e): &(usize, usize)| {
+            text[s..e].chars().next().is_some_and(|c| utils::is_khmer_char(c) && !utils::is_separator_cp(c) && !utils::is_digit_cp(c))
+        };
+        let mut i = 0;
+        while i < segments.len() {
+            let (s, e) = segments[i];
+            let seg = &text[s..e];
+            let short = seg.chars().count() < self.config.min_merge_char_len
+                && seg.chars().all(utils::is_valid_single_base_char)
+                && !self.is_known_word(kd, seg);
+            if short && i > 0 && is_word(&segments[i - 1]) {
+                segments[i - 1].1 = e;
+                segments.remove(i);
+            } else if short && i + 1 < segments.len() && is_word(&segments[i + 1]) {
+                segments[i + 1].0 = s;
+                segments.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Why a segmentation request could not be served as asked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentError {
+    /// Normalization changed the text, so tokens cannot borrow from the
+    /// input (`segment_tokens`).
+    NormalizationRequired,
+    /// The text needs the DP but no dictionary is loaded.
+    NoDictionary,
+    /// The DP found no path to the end of the text.
+    Unreachable,
+    /// A span boundary fell inside a character, at this byte offset of the
+    /// normalized text.
+    InvalidBoundary(usize),
+}
+
+impl std::fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SegmentError::NormalizationRequired => f.write_str("normalization changed the text; tokens cannot borrow from the input"),
+            SegmentError::NoDictionary => f.write_str("no dictionary loaded"),
+            SegmentError::Unreachable => f.write_str("no segmentation path reaches the end of the text"),
+            SegmentError::InvalidBoundary(at) => write!(f, "span boundary at byte {} is inside a character", at),
+        }
+    }
+}
+
+impl std::error::Error for SegmentError {}
+
+/// One token of `segment_detailed`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub text: String,
+    pub kind: TokenKind,
+    /// Table slot of the matched dictionary entry, for dictionary words (see
+    /// `KDict::entry`).
+    pub slot: Option<usize>,
+    /// Cost of the dictionary entry, for dictionary words.
+    pub cost: Option<f32>,
+    /// Category byte of the dictionary entry, if the dictionary stores one.
+    pub category: Option<u8>,
+    /// `kdict::FLAG_*` bits of the dictionary entry, if it has any.
+    pub flags: Option<u8>,
+    /// What the best path spent on this token, including tokens the rule
+    /// engine or unknown-merging assembled from several DP steps.
+    pub path_cost: Option<f32>,
+    /// The token is in `config.stop_words`.
+    pub stop: bool,
+}
+
+/// Token counts over a batch of lines, from `segment_batch_stats`. Merge
+/// batches (or per-thread partial counts) with `+=`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchStats {
+    pub lines: usize,
+    pub tokens: usize,
+    /// Chars in all tokens, of the normalized text.
+    pub chars: usize,
+    /// Chars in `TokenKind::Unknown` tokens.
+    pub unknown_chars: usize,
+    kinds: [usize; TokenKind::ALL.len()],
+}
+
+impl BatchStats {
+    /// Number of tokens of `kind`.
+    pub fn count(&self, kind: TokenKind) -> usize {
+        self.kinds[kind as usize]
+    }
+
+    /// Share of chars in unknown tokens, from 0 to 1; 0 for no text.
+    pub fn unknown_ratio(&self) -> f64 {
+        if self.chars == 0 { 0.0 } else { self.unknown_chars as f64 / self.chars as f64 }
+    }
+
+    pub fn add_token(&mut self, kind: TokenKind, chars: usize) {
+        self.tokens += 1;
+        self.chars += chars;
+        self.kinds[kind as usize] += 1;
+        if kind == TokenKind::Unknown {
+            self.unknown_chars += chars;
+        }
+    }
+}
+
+impl std::ops::AddAssign for BatchStats {
+    fn add_assign(&mut self, other: Self) {
+        self.lines += other.lines;
+        self.tokens += other.tokens;
+        self.chars += other.chars;
+        self.unknown_chars += other.unknown_chars;
+        for (mine, theirs) in self.kinds.iter_mut().zip(other.kinds) {
+            *mine += theirs;
+        }
+    }
+}
+
+/// One token's position, in bytes and in chars, from `segment_char_spans`.
+/// Ends are exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharSpan {
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+impl CharSpan {
+    pub fn byte_range(&self) -> Range<usize> {
+        self.byte_start..self.byte_end
+    }
+
+    pub fn char_range(&self) -> Range<usize> {
+        self.char_start..self.char_end
+    }
+}
+
+/// Kind of the DP transition that produced a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenKind {
+    #[default]
+    Unknown,
+    Word,
+    Number,
+    Separator,
+    Acronym,
+    /// Clock time (`enable_time_detection`).
+    Time,
+    Repair,
+    /// Dangling coeng at the end of the input (`TrailingCoeng::Flag`).
+    Incomplete,
+    /// Word with `ៗ` or written twice (`Reduplication::Merge`).
+    Reduplication,
+    /// Dependent vowel with no base (`OrphanedVowels::Flag`).
+    Orphaned,
+}
+
+impl TokenKind {
+    /// Every kind, in declaration order.
+    pub const ALL: [TokenKind; 10] = [
+        TokenKind::Unknown,
+        TokenKind::Word,
+        TokenKind::Number,
+        TokenKind::Separator,
+        TokenKind::Acronym,
+        TokenKind::Time,
+        TokenKind::Repair,
+        TokenKind::Incomplete,
+        TokenKind::Reduplication,
+        TokenKind::Orphaned,
+    ];
+
+    /// Lowercase name, as used in `segment_html` class names.
+    pub fn name(self) -> &'static str {
+        match self {
+            TokenKind::Unknown => "unknown",
+            TokenKind::Word => "word",
+            TokenKind::Number => "number",
+            TokenKind::Separator => "separator",
+            TokenKind::Acronym => "acronym",
+            TokenKind::Time => "time",
+            TokenKind::Repair => "repair",
+            TokenKind::Incomplete => "incomplete",
+            TokenKind::Reduplication => "reduplication",
+            TokenKind::Orphaned => "orphaned",
+        }
+    }
+}
+
+fn escape_html_into(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// The crate's Khmer segmentation model as a `Transitions` implementation:
+/// repair, numbers, separators, acronyms, dictionary words and unknown clusters.
+pub struct KhmerTransitions<'a> {
+    config: &'a SegmenterConfig,
+    kdict: &'a KDict,
+    text: &'a str,
+    unknown_cost: f32,
+    default_cost: f32,
+    // Byte positions of `force_boundary_chars` in `text`, ascending
+    forced_boundaries: Vec<usize>,
+    // Byte positions of dependent vowels nothing before them can carry, in
+    // repair mode; ascending
+    orphans: Vec<usize>,
+    user_words: Option<&'a UserWords>,
+    // `enable_frequency_costs`, and the dictionary has frequencies
+    frequency_costs: bool,
+    #[cfg(feature = "probe-stats")]
+    probes: Option<&'a ProbeCounter>,
+}
+
+impl<'a> KhmerTransitions<'a> {
+    pub fn new(config: &'a SegmenterConfig, kdict: &'a KDict, text: &'a str) -> Self {
+        let header = unsafe { &*kdict.header };
+        Self {
+            config,
+            kdict,
+            text,
+            unknown_cost: config.unknown_cost_override.unwrap_or(header.unknown_cost),
+            default_cost: config.default_cost_override.unwrap_or(header.default_cost),
+            forced_boundaries: if config.force_boundary_chars.is_empty() {
+                Vec::new()
+            } else {
+                text.char_indices().filter(|(_, c)| config.force_boundary_chars.contains(c)).map(|(p, _)| p).collect()
+            },
+            orphans: if config.enable_repair_mode { orphan_positions(text) } else { Vec::new() },
+            user_words: None,
+            frequency_costs: config.enable_frequency_costs && kdict.has_frequencies(),
+            #[cfg(feature = "probe-stats")]
+            probes: None,
+        }
+    }
+
+    // DP cost of the dictionary entry in `slot`, with the frequency bonus.
+    fn word_cost(&self, slot: usize) -> f32 {
+        let cost = self.kdict.entry(slot).cost;
+        if !self.frequency_costs { return cost; }
+        match self.kdict.frequency(slot) {
+            0 => cost,
+            freq => cost - self.config.frequency_weight * (freq as f32).ln_1p(),
+        }
+    }
+
+    // Also matches the segmenter's `add_word` words.
+    fn with_user_words(mut self, words: &'a UserWords) -> Self {
+        self.user_words = Some(words).filter(|w| !w.costs.is_empty());
+        self
+    }
+
+    /// Records every dictionary lookup's probe count in `counter`.
+    #[cfg(feature = "probe-stats")]
+    pub fn with_probe_counter(mut self, counter: &'a ProbeCounter) -> Self {
+        self.probes = Some(counter);
+        self
+    }
+}
+
+impl Transitions for KhmerTransitions<'_> {
+    type Kind = TokenKind;
+
+    fn transitions<F: FnMut(Transition<TokenKind>)>(&self, i: usize, mut emit: F) {
+        let text = self.text;
+
+        // Catch offset mistakes where they happen: every position we start
+        // from or emit must sit on a char boundary
+        debug_assert!(text.is_char_boundary(i), "DP position {} is inside a char", i);
+        let c = text[i..].chars().next().unwrap();
+        let char_len = c.len_utf8();
+
+        // Forced boundaries are separator tokens of their own, and nothing
+        // spans one: unknown and separator runs stop short of the next,
+        // anything else reaching past it is dropped
+        if self.config.force_boundary_chars.contains(&c) {
+            emit(Transition { next: i + char_len, cost: 0.1, kind: TokenKind::Separator });
+            return;
+        }
+        let limit = self.forced_boundaries.get(self.forced_boundaries.partition_point(|&p| p <= i)).copied().unwrap_or(usize::MAX);
+        let mut emit = |mut t: Transition<TokenKind>| {
+            if t.next > limit {
+                if !matches!(t.kind, TokenKind::Unknown | TokenKind::Separator) { return; }
+                t.next = limit;
+            }
+            debug_assert!(t.next > i && text.is_char_boundary(t.next), "transition {}..{} ends inside a char", i, t.next);
+            emit(t)
+        };
+        
+        // Repair Mode
+        if self.config.enable_repair_mode {
+            let force_repair = utils::is_dependent_vowel(c);
+
+            if force_repair {
+                let transition = match self.config.orphaned_vowels {
+                    OrphanedVowels::Repair => Transition { next: i + char_len, cost: self.unknown_cost + 50.0, kind: TokenKind::Repair },
+                    OrphanedVowels::Flag => Transition { next: i + char_len, cost: self.unknown_cost, kind: TokenKind::Orphaned },
+                };
+                emit(transition);
+                return;
+            }
+        }
+
+        // Numbers
+        let is_dig = utils::is_digit_cp(c);
+        
+        if is_dig {
+            let num_len = match self.config.dotted_numbers {
+                DottedNumbers::Join => utils::get_number_length_with(&text[i..], self.config.decimal_convention),
+                DottedNumbers::Split => {
+                    let before = &text[..i];
+                    let after_dot = before.ends_with('.')
+                        && before[..before.len() - 1].chars().next_back().is_some_and(utils::is_digit_cp);
+                    utils::get_split_number_length(&text[i..], after_dot, self.config.decimal_convention)
+                }
+            };
+            let step_cost = 1.0;
+            emit(Transition { next: i + num_len, cost: step_cost, kind: TokenKind::Number });
+
+            if self.config.enable_time_detection {
+                let time_len = utils::get_time_length(&text[i..]);
+                if time_len > 0 {
+                    emit(Transition { next: i + time_len, cost: step_cost, kind: TokenKind::Time });
+                }
+            }
+        } else if utils::is_separator_cp(c) {
+            let step_cost = 0.1;
+            emit(Transition { next: i + utils::non_khmer_len(&text[i..], c), cost: step_cost, kind: TokenKind::Separator });
+        }
+
+        // Acronyms
+        if self.config.enable_acronym_detection && utils::is_acronym_start(&text[i..], &self.config.acronym_terminators) {
+            let acr_len = acronym_length(self.config, &text[..i], &text[i..]);
+            if acr_len > 0 {
+                let step_cost = self.default_cost;
+                emit(Transition { next: i + acr_len, cost: step_cost, kind: TokenKind::Acronym });
+            }
+        }
+
+        // Dictionary Lookup (runs even at a separator, and hashes whatever
+        // bytes follow, so entries containing spaces match as one word). A
+        // match may not swallow an orphaned vowel repair mode would isolate.
+        let kd = self.kdict;
+        let orphan = self.orphans.get(self.orphans.partition_point(|&p| p <= i)).copied().unwrap_or(usize::MAX);
+        kd.for_each_match_at(
+            text,
+            i,
+            self.config.min_dict_match_chars,
+            |end, slot| if end <= orphan {
+                emit(Transition { next: end, cost: self.word_cost(slot), kind: TokenKind::Word })
+            },
+            |_probes| {
+                #[cfg(feature = "probe-stats")]
+                if let Some(counter) = self.probes { counter.record(_probes); }
+            },
+        );
+        if let Some(user) = self.user_words {
+            for (chars, (off, ch)) in text[i..].char_indices().enumerate() {
+                let end = i + off + ch.len_utf8();
+                if end - i > user.max_len { break; }
+                if chars + 1 < self.config.min_dict_match_chars || end > orphan { continue; }
+                if let Some(&cost) = user.costs.get(&text[i..end]) {
+                    emit(Transition { next: end, cost, kind: TokenKind::Word });
+                }
+            }
+        }
+        
+        // Handle Unknown Clusters
+        let cluster_bytes = if utils::is_khmer_char(c) {
+            utils::get_khmer_cluster_length(&text[i..])
+        } else {
+            utils::non_khmer_len(&text[i..], c)
+        };
+        
+        let mut unk_cost = self.unknown_cost;
+        if cluster_bytes == char_len && utils::is_khmer_char(c) && !utils::is_valid_single_base_char(c) {
+            unk_cost += 10.0;
+        }
+        
+        emit(Transition { next: i + cluster_bytes, cost: unk_cost, kind: TokenKind::Unknown });
+    }
+
+    fn run_penalty(&self, kind: TokenKind, run: u32) -> Option<f32> {
+        unknown_run_penalty(self.config.unknown_ramp, kind, run)
+    }
+}
+
+// `unknown_ramp` as a `run_penalty`: runs are made of unknown clusters only.
+fn unknown_run_penalty(ramp: Option<f32>, kind: TokenKind, run: u32) -> Option<f32> {
+    match (ramp, kind) {
+        (Some(ramp), TokenKind::Unknown) => Some(ramp * run as f32),
+        _ => None,
+    }
+}
+
+// Dependent vowels following something that cannot carry one (start of
+// text, a separator, non-Khmer text), which repair mode always consumes
+// on their own. Anything after a base, subscript, vowel or sign belongs to
+// that cluster.
+fn orphan_positions(text: &str) -> Vec<usize> {
+    let mut prev = None;
+    let mut orphans = Vec::new();
+    for (p, c) in text.char_indices() {
+        let carried = prev.is_some_and(|b: char| ('\u{1780}'..='\u{17D3}').contains(&b) || b == '\u{17DD}');
+        if utils::is_dependent_vowel(c) && !carried {
+            orphans.push(p);
+        }
+        prev = Some(c);
+    }
+    orphans
+}
+
+// A token that can be reduplicated: Khmer, not a separator or number.
+fn is_khmer_word(token: &str) -> bool {
+    token.chars().next().is_some_and(|c| utils::is_khmer_char(c) && !utils::is_separator_cp(c) && !utils::is_digit_cp(c))
+}
+
+// `word` + `ៗ`, or the same word twice.
+fn is_reduplication(token: &str) -> bool {
+    if let Some(word) = token.strip_suffix('\u{17D7}') {
+        return is_khmer_word(word);
+    }
+    let half = token.len() / 2;
+    token.is_char_boundary(half) && token[..half] == token[half..] && is_khmer_word(&token[..half])
+}
+
+// `Reduplication::Merge`: folds `ៗ` into the word before it, and a word into
+// an identical word before it.
+fn merge_reduplications(text: &str, segments: &mut Vec<(usize, usize)>) {
+    let mut i = 1;
+    while i < segments.len() {
+        let (prev, cur) = (segments[i - 1], segments[i]);
+        let (prev_text, cur_text) = (&text[prev.0..prev.1], &text[cur.0..cur.1]);
+        let merge = is_khmer_word(prev_text) && !prev_text.ends_with('\u{17D7}')
+            && (cur_text == "\u{17D7}" || cur_text == prev_text);
+        if merge {
+            segments[i - 1].1 = cur.1;
+            segments.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+// `join_hyphenated_mixed`: `word - number` or `number - word`, where the
+// word is Latin letters (digits allowed) and the number all digits.
+fn merge_hyphenated_mixed(text: &str, segments: &mut Vec<(usize, usize)>) {
+    let is_hyphen = |t: &str| matches!(t, "-" | "\u{2010}" | "\u{2011}" | "\u{2013}");
+    let is_number = |t: &str| !t.is_empty() && t.chars().all(utils::is_digit_cp);
+    let is_word = |t: &str| {
+        t.chars().any(|c| utils::Script::of(c) == utils::Script::Latin)
+            && t.chars().all(|c| matches!(utils::Script::of(c), utils::Script::Latin | utils::Script::Digits))
+    };
+    let mixed = |a: &str, b: &str| (is_word(a) && is_number(b)) || (is_number(a) && is_word(b));
+
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(segments.len());
+    // The part of the last merged token that the next pair is checked against
+    let mut last_part = None;
+    let mut k = 0;
+    while k < segments.len() {
+        let (s, e) = segments[k];
+        if let (Some((ls, le)), Some(&(ns, ne))) = (last_part, segments.get(k + 1)) {
+            if is_hyphen(&text[s..e]) && mixed(&text[ls..le], &text[ns..ne]) {
+                merged.last_mut().unwrap().1 = ne;
+                last_part = Some((ns, ne));
+                k += 2;
+                continue;
+            }
+        }
+        merged.push((s, e));
+        last_part = Some((s, e));
+        k += 1;
+    }
+    *segments = merged;
+}
+
+// Spans of a text with no Khmer in it under a non-`PerChar` fallback.
+fn fallback_spans(text: &str, fallback: Fallback, segments: &mut Vec<(usize, usize)>) {
+    if fallback == Fallback::SingleToken {
+        segments.push((0, text.len()));
+        return;
+    }
+    let mut start = 0;
+    let mut in_space = None;
+    for (i, c) in text.char_indices() {
+        let space = c.is_whitespace();
+        if in_space.is_some_and(|s| s != space) {
+            segments.push((start, i));
+            start = i;
+        }
+        in_space = Some(space);
+    }
+    segments.push((start, text.len()));
+}
+
+// Acronym length at the start of `text`, or 0 when it has fewer dotted
+// clusters than the config asks for, or more than the cap. With a cap, a
+// chain that continues one before it (`before` ends in `<cluster>.`) is no
+// acronym either, so an over-long chain is not taken piecemeal from its
+// tail. Shared by the DP and the merge pass so both agree on what counts as
+// an acronym.
+fn acronym_length(config: &SegmenterConfig, before: &str, text: &str) -> usize {
+    let terminators = &config.acronym_terminators;
+    let len = utils::get_acronym_length(text, terminators);
+    let clusters = text[..len].matches(|c| terminators.contains(&c)).count();
+    if clusters < config.min_acronym_clusters || clusters > config.max_acronym_clusters { return 0; }
+    if config.max_acronym_clusters != usize::MAX {
+        let mut rev = before.chars().rev();
+        if rev.next().is_some_and(|c| terminators.contains(&c)) && rev.next().is_some_and(utils::is_khmer_char) {
+            return 0;
+        }
+    }
+    len
+}
+
+/// Texts at least this long (in bytes) take the parallel precompute path
+/// when the `parallel-scan` feature is on.
+#[cfg(feature = "parallel-scan")]
+pub const PARALLEL_SCAN_MIN_BYTES: usize = 16 * 1024;
+
+// Every position's candidate transitions, computed up front on the rayon pool
+// and replayed in the same order during the sequential sweep. Positions are
+// independent (candidates only depend on the text), so the DP result is the
+// same as scanning lazily; the sweep itself stays sequential.
+#[cfg(feature = "parallel-scan")]
+struct PrecomputedTransitions {
+    // edges[starts[pos]..starts[pos + 1]] leave `pos`
+    starts: Vec<usize>,
+    edges: Vec<Transition<TokenKind>>,
+    unknown_ramp: Option<f32>,
+}
+
+#[cfg(feature = "parallel-scan")]
+impl PrecomputedTransitions {
+    fn new(transitions: &KhmerTransitions, text: &str) -> Self {
+        use rayon::prelude::*;
+        const CHUNK: usize = 4096;
+
+        let n = text.len();
+        let chunks: Vec<(Vec<usize>, Vec<Transition<TokenKind>>)> = (0..n.div_ceil(CHUNK))
+            .into_par_iter()
+            .map(|c| {
+                let range = c * CHUNK..((c + 1) * CHUNK).min(n);
+                let mut counts = Vec::with_capacity(range.len());
+                let mut edges = Vec::new();
+                for pos in range {
+                    let before = edges.len();
+                    if text.is_char_boundary(pos) {
+                        transitions.transitions(pos, |t| edges.push(t));
+                    }
+                    counts.push(edges.len() - before);
+                }
+                (counts, edges)
+            })
+            .collect();
+
+        let mut starts = Vec::with_capacity(n + 1);
+        let mut edges = Vec::with_capacity(chunks.iter().map(|(_, e)| e.len()).sum());
+        for (counts, chunk_edges) in chunks {
+            let mut next = edges.len();
+            for count in counts {
+                starts.push(next);
+                next += count;
+            }
+            edges.extend(chunk_edges);
+        }
+        starts.push(edges.len());
+        Self { starts, edges, unknown_ramp: transitions.config.unknown_ramp }
+    }
+}
+
+#[cfg(feature = "parallel-scan")]
+impl Transitions for PrecomputedTransitions {
+    type Kind = TokenKind;
+
+    fn transitions<F: FnMut(Transition<TokenKind>)>(&self, pos: usize, mut emit: F) {
+        for &t in &self.edges[self.starts[pos]..self.starts[pos + 1]] {
+            emit(t);
+        }
+    }
+
+    fn run_penalty(&self, kind: TokenKind, run: u32) -> Option<f32> {
+        unknown_run_penalty(self.unknown_ramp, kind, run)
+    }
+}
@@ -1,5 +1,11 @@
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod interner;
 pub mod kdict;
 pub mod khmer_segmenter;
 pub mod normalization;
 pub mod rule_engine;
+pub mod stream;
 pub mod utils;
+pub mod viterbi;
+pub mod vocab;
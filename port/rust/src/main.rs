@@ -5,7 +5,8 @@ use std::path::Path;
 use std::time::Instant;
 use rayon::prelude::*;
 
-use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, PunctuationAttachment, SegmenterConfig, DEFAULT_DICT_PATHS};
+use khmer_segmenter::stream::ReaderOptions;
 
 #[cfg(target_os = "linux")]
 fn get_memory_mb() -> f64 {
@@ -14,14 +15,12 @@ fn get_memory_mb() -> f64 {
     
     if let Ok(file) = File::open("/proc/self/status") {
         let reader = BufReader::new(file);
-        for line in reader.lines() {
-            if let Ok(l) = line {
-                if l.starts_with("VmRSS:") {
-                    let parts: Vec<&str> = l.split_whitespace().collect();
-                    if parts.len() >= 2 {
-                        if let Ok(kb) = parts[1].parse::<f64>() {
-                            return kb / 1024.0;
-                        }
+        for l in reader.lines().map_while(Result::ok) {
+            if l.starts_with("VmRSS:") {
+                let parts: Vec<&str> = l.split_whitespace().collect();
+                if parts.len() >= 2 {
+                    if let Ok(kb) = parts[1].parse::<f64>() {
+                        return kb / 1024.0;
                     }
                 }
             }
@@ -37,6 +36,75 @@ fn get_memory_mb() -> f64 {
 
 
 
+// Every mode writes its records here unless `--output` says otherwise.
+const DEFAULT_OUTPUT: &str = "segmentation_results.txt";
+
+const DEFAULT_TEMPLATE: &str = "Original:  {original}\nSegmented: {segmented}\n----------------------------------------\n";
+
+const SEGMENTED_ONLY_TEMPLATE: &str = "{segmented}\n";
+
+// Expands `{original}` / `{segmented}` in one pass (so placeholder-looking text
+// inside the data is never re-expanded) plus the escapes `\n`, `\t` and `\\`.
+fn render_template(template: &str, original: &str, segmented: &str) -> String {
+    let mut out = String::with_capacity(template.len() + original.len() + segmented.len());
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        if let Some(r) = rest.strip_prefix("{original}") {
+            out.push_str(original);
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("{segmented}") {
+            out.push_str(segmented);
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("\\n") {
+            out.push('\n');
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("\\t") {
+            out.push('\t');
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("\\\\") {
+            out.push('\\');
+            rest = r;
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+// `-` writes to stdout.
+fn open_output(path: &str) -> io::Result<Box<dyn Write>> {
+    if path == "-" {
+        Ok(Box::new(io::stdout()))
+    } else {
+        Ok(Box::new(File::create(path)?))
+    }
+}
+
+fn report_saved(path: &str) {
+    if path != "-" {
+        eprintln!("Results saved to {}", path);
+    }
+}
+
+fn write_record<W: Write + ?Sized>(out: &mut W, template: &str, original: &str, segmented: &str) -> io::Result<()> {
+    out.write_all(render_template(template, original, segmented).as_bytes())
+}
+
+// The one place `--input` files are opened, for the streaming and the
+// benchmark paths alike. Gzip files (a `.gz` name or the magic bytes) are
+// decompressed on the fly when the `gzip` feature is on.
+fn open_input(path: &str) -> io::Result<Box<dyn BufRead>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    if path.ends_with(".gz") || reader.fill_buf()?.starts_with(&[0x1F, 0x8B]) {
+        #[cfg(feature = "gzip")]
+        return Ok(Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(reader))));
+        #[cfg(not(feature = "gzip"))]
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "file is gzip-compressed; rebuild with --features gzip"));
+    }
+    Ok(Box::new(reader))
+}
+
 fn main() -> io::Result<()> {
     // Config defaults
     let mut config = SegmenterConfig::default();
@@ -46,18 +114,33 @@ fn main() -> io::Result<()> {
     let mut mode_benchmark = false;
     let mut threads = 4;
     let mut limit: i32 = -1;
+    let mut template = DEFAULT_TEMPLATE.to_string();
+    let mut keep_original = false;
 
     let args: Vec<String> = env::args().collect();
     let mut i = 1;
+    let mut positional_only = false;
     while i < args.len() {
         let arg = &args[i];
-        if arg == "--benchmark" || arg == "--bench" {
+        if positional_only || !arg.starts_with('-') {
+            if let Some(ref mut text) = input_text {
+                text.push(' ');
+                text.push_str(arg);
+            } else {
+                input_text = Some(arg.clone());
+            }
+        } else if arg == "--" {
+            // Everything after `--` is text to segment, even if it looks like a flag
+            positional_only = true;
+        } else if let Some(path) = arg.strip_prefix("--input=").or_else(|| arg.strip_prefix("--file=")) {
+            // Single path; the `=` form allows names starting with '-'
+            input_files.push(path.to_string());
+        } else if arg == "--benchmark" || arg == "--bench" {
             mode_benchmark = true;
-            eprintln!("DEBUG: Set benchmark match {}", arg);
         } else if arg == "--input" || arg == "--file" {
-            eprintln!("DEBUG: Found input flag at {}", i);
+            // Every argument up to the next flag or `--` is a path, and each
+            // must exist (checked below); put text to segment after `--`
             while i + 1 < args.len() && !args[i+1].starts_with('-') {
-                eprintln!("DEBUG: Pushing input file: {}", args[i+1]);
                 input_files.push(args[i+1].clone());
                 i += 1;
             }
@@ -66,6 +149,11 @@ fn main() -> io::Result<()> {
                 output_file = Some(args[i+1].clone());
                 i += 1;
             }
+        } else if arg == "--template" {
+            if i + 1 < args.len() {
+                template = args[i+1].clone();
+                i += 1;
+            }
         } else if arg == "--threads" {
             if i + 1 < args.len() {
                 threads = args[i+1].parse().unwrap_or(4);
@@ -76,6 +164,33 @@ fn main() -> io::Result<()> {
                 limit = args[i+1].parse().unwrap_or(-1);
                 i += 1;
             }
+        } else if arg == "--unknown-cost" {
+            if i + 1 < args.len() {
+                config.unknown_cost_override = args[i+1].parse().ok();
+                i += 1;
+            }
+        } else if arg == "--default-cost" {
+            if i + 1 < args.len() {
+                config.default_cost_override = args[i+1].parse().ok();
+                i += 1;
+            }
+        } else if arg == "--attach-punct" {
+            let value = args.get(i + 1).map_or("", String::as_str);
+            config.punctuation_attachment = match value {
+                "left" => PunctuationAttachment::AttachLeft,
+                "right" => PunctuationAttachment::AttachRight,
+                "none" => PunctuationAttachment::Standalone,
+                _ => {
+                    eprintln!("Error: --attach-punct expects left, right or none, got {:?}", value);
+                    std::process::exit(2);
+                }
+            };
+            i += 1;
+        } else if arg == "--segmented-only" {
+            // One segmented line per input line, usable as a corpus as is
+            template = SEGMENTED_ONLY_TEMPLATE.to_string();
+        } else if arg == "--keep-original" {
+            keep_original = true;
         } else if arg == "--no-norm" {
             config.enable_normalization = false;
         } else if arg == "--no-repair" {
@@ -85,52 +200,35 @@ fn main() -> io::Result<()> {
         } else if arg == "--no-merging" {
             config.enable_unknown_merging = false;
         } else if arg == "--no-freq" {
-            config.enable_frequency_costs = false; // Not used in binary dict but kept for compat
-        } else if !arg.starts_with('-') {
-            if let Some(ref mut text) = input_text {
-                text.push(' ');
-                text.push_str(arg);
-            } else {
-                input_text = Some(arg.clone());
-            }
+            config.enable_frequency_costs = false; // Ignore version 4 dictionary frequencies
         }
         i += 1;
     }
 
-    
-    eprintln!("DEBUG: Args: {:?}", args);
-    eprintln!("DEBUG: Parsed Input Files: {:?}", input_files);
-    eprintln!("DEBUG: Benchmark Mode: {}", mode_benchmark);
-
-    if !input_files.is_empty() && output_file.is_none() {
-        output_file = Some("segmentation_results.txt".to_string());
-    }
-
-    // Locate Dictionary
-    let dict_paths = [
-        "khmer_dictionary.kdict",
-        "../../port/common/khmer_dictionary.kdict",
-        "../common/khmer_dictionary.kdict", // Just in case
-        "c:/Users/Sovichea/Documents/git/khmer_segmenter/port/common/khmer_dictionary.kdict", // Absolute fallback
-    ];
-    
-    let mut dict_path: Option<&str> = None;
-    for p in &dict_paths {
-        if Path::new(p).exists() {
-            dict_path = Some(p);
-            break;
+    // Fail early and clearly rather than with an opaque IO error mid-run
+    for file in &input_files {
+        if !Path::new(file).is_file() {
+            eprintln!("Error: input file not found: {}", file);
+            eprintln!("(to segment text after --input files, separate it with `--`)");
+            std::process::exit(2);
         }
     }
+    if !input_files.is_empty() && input_text.is_some() {
+        eprintln!("Warning: positional text is ignored when --input is given");
+    }
+
+
+    let output_file = output_file.unwrap_or_else(|| DEFAULT_OUTPUT.to_string());
 
     if mode_benchmark || !input_files.is_empty() {
-        eprintln!("Initializing segmenter (Dict: {:?})...", dict_path);
+        eprintln!("Initializing segmenter (Dict search: {:?})...", DEFAULT_DICT_PATHS);
     }
 
-    let seg = match KhmerSegmenter::new(dict_path, config) {
+    let seg = match KhmerSegmenter::new_searching(DEFAULT_DICT_PATHS, config.clone()) {
         Ok(s) => s,
         Err(e) => {
-            eprintln!("Failed to init segmenter: {}", e);
-            return Ok(());
+            eprintln!("Warning: {}; segmenting without a dictionary", e);
+            KhmerSegmenter::new_with_dict(None, config)
         }
     };
     
@@ -138,6 +236,13 @@ fn main() -> io::Result<()> {
         eprintln!("Initialization complete.");
     }
 
+    // Non-benchmark output; `--keep-original` slices tokens from the input
+    let segment_line = |l: &str| if keep_original {
+        seg.segment_original(l, Some(" | "))
+    } else {
+        seg.segment(l, Some(" | "))
+    };
+
     // Set thread pool? Rayon auto-configures but we can force it if we want strict control.
     rayon::ThreadPoolBuilder::new().num_threads(threads).build_global().unwrap();
 
@@ -146,12 +251,8 @@ fn main() -> io::Result<()> {
             let mut lines = Vec::new();
             let mut current_limit = limit;
             
-            eprintln!("DEBUG: Input files: {:?}", input_files);
-            
             for file in &input_files {
-                 eprintln!("DEBUG: Reading file: {}", file);
-                 let f = File::open(file)?;
-                 let reader = BufReader::new(f);
+                 let reader = open_input(file)?;
                  for line in reader.lines() {
                      if limit != -1 && current_limit <= 0 { break; }
                      if let Ok(l) = line {
@@ -168,7 +269,6 @@ fn main() -> io::Result<()> {
                  }
                  if limit != -1 && current_limit <= 0 { break; }
             }
-                        eprintln!("DEBUG: Read {} lines", lines.len());
              
              // Calculate size
              let total_bytes: usize = lines.iter().map(|l| l.len()).sum();
@@ -190,14 +290,12 @@ fn main() -> io::Result<()> {
              eprintln!(" Done in {:.3}s ({:.2} lines/sec)", duration.as_secs_f64(), lines.len() as f64 / duration.as_secs_f64());
              eprintln!("Mem Delta: {:.2} MB", end_mem - start_mem);
             
-            if let Some(out_path) = &output_file {
-                 let mut f = File::create(out_path)?;
+            {
+                 let mut f = open_output(&output_file)?;
                  for (orig, res) in lines.iter().zip(results_seq.iter()) {
-                     writeln!(f, "Original:  {}", orig)?;
-                     writeln!(f, "Segmented: {}", res)?;
-                     writeln!(f, "----------------------------------------")?;
+                     write_record(&mut f, &template, orig, res)?;
                  }
-                 eprintln!("Results saved to {}", out_path);
+                 report_saved(&output_file);
             }
 
             // 2. Parallel
@@ -229,17 +327,9 @@ fn main() -> io::Result<()> {
              let check = seg.segment(text, Some(" | "));
              println!("\n[Output Check]\n{}\n", check);
              
-             if let Some(out_path) = output_file {
-                 let mut f = File::create(out_path)?;
-                 writeln!(f, "Original:  {}", text)?;
-                 writeln!(f, "Segmented: {}", check)?;
-                 writeln!(f, "----------------------------------------")?;
-             } else {
-                  let mut f = File::create("benchmark_results.txt")?;
-                  writeln!(f, "Original:  {}", text)?;
-                  writeln!(f, "Segmented: {}", check)?;
-                  writeln!(f, "----------------------------------------")?;
-             }
+             let mut f = open_output(&output_file)?;
+             write_record(&mut f, &template, text, &check)?;
+             report_saved(&output_file);
              
              // Sequential
              println!("\n[Sequential] Running {} iterations...", iterations_seq);
@@ -271,73 +361,57 @@ fn main() -> io::Result<()> {
              println!("Mem Delta: {:.2} MB", end_mem - start_mem);
         }
     } else if !input_files.is_empty() {
-        let mut out: Box<dyn Write> = if let Some(path) = output_file {
-            Box::new(File::create(path)?)
-        } else {
-             Box::new(io::stdout())
-        };
-        
-        let mut lines = Vec::new();
-        let mut current_limit = limit;
+        let mut out = open_output(&output_file)?;
+
+        // Streamed in bounded windows so input size is not limited by memory.
+        // Lines that are not valid UTF-8 are skipped, a read error moves on to
+        // the next file and `--limit` counts across all files
+        let mut remaining = if limit == -1 { usize::MAX } else { limit.max(0) as usize };
         for file in &input_files {
-             let f = File::open(file)?;
-             let reader = BufReader::new(f);
-             for line in reader.lines() {
-                 if limit != -1 && current_limit <= 0 { break; }
-                 if let Ok(l) = line {
-                     // Remove BOM
-                        let clean = if l.starts_with("\u{FEFF}") {
-                             l.chars().skip(1).collect()
-                         } else {
-                             l
-                         };
-                     lines.push(clean);
-                     if limit != -1 { current_limit -= 1; }
-                 }
-             }
-             if limit != -1 && current_limit <= 0 { break; }
-        }
-        
-        // Use parallel processing if threads > 1
-        if threads > 1 {
-             let results: Vec<String> = lines.par_iter()
-                .map(|l| seg.segment(l, Some(" | ")))
-                .collect();
-             
-             for (orig, res) in lines.iter().zip(results.iter()) {
-                 writeln!(out, "Original:  {}", orig)?;
-                 writeln!(out, "Segmented: {}", res)?;
-                 writeln!(out, "----------------------------------------")?;
-             }
-        } else {
-             for l in lines {
-                 let res = seg.segment(&l, Some(" | "));
-                 writeln!(out, "Original:  {}", l)?;
-                 writeln!(out, "Segmented: {}", res)?;
-                 writeln!(out, "----------------------------------------")?;
-             }
+            if remaining == 0 { break; }
+            let reader = match open_input(file) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("Warning: cannot read {}: {}", file, e);
+                    continue;
+                }
+            };
+            let options = ReaderOptions { max_lines: remaining, skip_invalid_utf8: true, strip_bom: true, keep_original };
+            let summary = seg.segment_reader_with(reader, &mut out, Some(" | "), options, |original, segmented| {
+                render_template(&template, original, &segmented)
+            })?;
+            remaining -= summary.lines;
+            if let Some(e) = summary.read_error {
+                eprintln!("Warning: stopped reading {}: {}", file, e);
+            }
         }
+        report_saved(&output_file);
         
     } else if let Some(text) = input_text {
-        let res = seg.segment(&text, Some(" | "));
+        let res = segment_line(&text);
         println!("Input: {}", text);
         println!("Output: {}", res);
         
         // Save
-        let out_path = output_file.unwrap_or("segmentation_results.txt".to_string());
-        let mut f = File::create(&out_path)?;
-        writeln!(f, "Original:  {}", text)?;
-        writeln!(f, "Segmented: {}", res)?;
-        writeln!(f, "----------------------------------------")?;
-        eprintln!("Results saved to {}", out_path);
+        let mut f = open_output(&output_file)?;
+        write_record(&mut f, &template, &text, &res)?;
+        report_saved(&output_file);
     } else {
         println!("Usage: khmer_segmenter.exe [flags] [text]");
-        println!("  --input <path...> Multiple input files");
-        println!("  --output <path>   Output file path");
+        println!("  --input <path...> Input files: every argument up to the next flag or `--`");
+        println!("  --input=<path>    Single input file (may start with '-')");
+        println!("  --output <path>   Output file for every mode (default: {}, '-' for stdout)", DEFAULT_OUTPUT);
         println!("  --limit <N>       Limit total lines processed");
+        println!("  --template <T>    Output record format, e.g. '{{original}}\\t{{segmented}}\\n'");
+        println!("  --segmented-only  Write only the segmented line per input (same as --template '{{segmented}}\\n')");
         println!("  --threads <N>     Number of threads (default: 4)");
+        println!("  --attach-punct <left|right|none> Glue punctuation to the neighbouring token");
+        println!("  --unknown-cost <F> Override the dictionary's unknown cost");
+        println!("  --default-cost <F> Override the dictionary's default cost");
+        println!("  --keep-original   Emit tokens exactly as written in the input (not normalized)");
         println!("  --benchmark       Run benchmark (uses --input if provided)");
         println!("  <text>            Process raw text");
+        println!("  -- <text>         Everything after `--` is text, even after --input");
     }
 
     Ok(())
@@ -1,3 +1,8 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::utils;
+
 
 
 #[derive(Eq, PartialEq, Clone, Copy)]
@@ -8,7 +13,14 @@ struct ClsPart {
     index: u8,
 }
 
+#[inline]
 fn get_char_type_norm(c: char) -> i32 {
+    utils::norm_type(c).unwrap_or_else(|| char_type_norm_slow(c))
+}
+
+// Range checks behind `get_char_type_norm`; `utils` tabulates them for the
+// common blocks.
+pub(crate) const fn char_type_norm_slow(c: char) -> i32 {
     if (c >= '\u{1780}' && c <= '\u{17A2}') || (c >= '\u{17A3}' && c <= '\u{17B3}') { return 1; } // BASE
     if c == '\u{17D2}' { return 2; } // COENG
     if c == '\u{17C9}' || c == '\u{17CA}' { return 3; } // REGISTER
@@ -31,16 +43,177 @@ fn get_prio(p: &ClsPart) -> i32 {
     100
 }
 
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct NormalizationConfig {
+    /// Compatibility codepoints folded to their canonical replacement before
+    /// cluster reordering. Empty by default, matching the C and Python
+    /// ports; fill it with the forms a legacy encoding uses, or start from
+    /// `deprecated_fold_table`.
+    pub fold_table: HashMap<char, String>,
+    /// Most parts (base, subscript pairs, vowels, signs) a cluster may collect
+    /// before it is flushed. Real clusters have well under ten; the cap bounds
+    /// the per-cluster sort on malformed stacks, whose excess parts are
+    /// reordered as separate clusters instead.
+    pub max_cluster_parts: usize,
+    /// Invisible format characters removed before anything else. Add
+    /// U+2060 WORD JOINER here to drop it too; it is kept by default since it
+    /// can carry intent (no break allowed).
+    pub strip_chars: Vec<char>,
+}
+
+impl Default for NormalizationConfig {
+    fn default() -> Self {
+        Self { fold_table: HashMap::new(), max_cluster_parts: 16, strip_chars: default_strip_chars() }
+    }
+}
+
+/// Zero-width and format characters that only get in the way of
+/// segmentation: ZWSP, ZWNJ, ZWJ, soft hyphen (common in PDF extraction) and
+/// a stray BOM / ZWNBSP.
+pub fn default_strip_chars() -> Vec<char> {
+    Vec::from(['\u{200B}', '\u{200C}', '\u{200D}', '\u{00AD}', '\u{FEFF}'])
+}
+
+/// Characters the Unicode standard marks as deprecated in the Khmer block,
+/// mapped to their recommended spellings. Not applied unless put in
+/// `NormalizationConfig::fold_table`.
+pub fn deprecated_fold_table() -> HashMap<char, String> {
+    HashMap::from([
+        ('\u{17A3}', "\u{17A2}".to_string()),                  // QAQ -> QA
+        ('\u{17A4}', "\u{17A2}\u{17B6}".to_string()),          // QAA -> QA + AA
+        ('\u{17B4}', String::new()),                           // Inherent AQ (invisible)
+        ('\u{17B5}', String::new()),                           // Inherent AA (invisible)
+        ('\u{17D8}', "\u{17D4}\u{179B}\u{17D4}".to_string()), // Beyyal -> ។ល។
+    ])
+}
+
+/// Byte-offset correspondence between a raw text and its normalized form,
+/// kept as anchor pairs `(raw, normalized)` sorted on both sides.
+///
+/// Between two anchors the texts are assumed to line up byte for byte, so an
+/// anchor is needed wherever they diverge: after every edit (strip, fold,
+/// vowel merge) and around every reordered cluster. Offset 0 is implicitly
+/// anchored to 0.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OffsetMap {
+    anchors: Vec<(usize, usize)>,
+}
+
+impl OffsetMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Panics
+    /// If the pairs are not non-decreasing on both sides.
+    pub fn from_anchors(anchors: Vec<(usize, usize)>) -> Self {
+        assert!(
+            anchors.windows(2).all(|w| w[0].0 <= w[1].0 && w[0].1 <= w[1].1),
+            "offset map anchors must be sorted on both sides"
+        );
+        Self { anchors }
+    }
+
+    /// Records that raw offset `raw` corresponds to normalized offset `norm`.
+    ///
+    /// # Panics
+    /// If either offset is smaller than the previous anchor's.
+    pub fn push(&mut self, raw: usize, norm: usize) {
+        if let Some(&(r, n)) = self.anchors.last() {
+            assert!(raw >= r && norm >= n, "offset map anchors must be pushed in order");
+        }
+        self.anchors.push((raw, norm));
+    }
+
+    /// Raw offset for normalized offset `norm`: the nearest anchor at or
+    /// before it plus the remaining distance, never past the next anchor.
+    /// When several anchors share a normalized offset (raw text that was
+    /// deleted), the last one wins, so removed characters end up in the span
+    /// before them and mapped spans still tile the raw text.
+    pub fn to_raw(&self, norm: usize) -> usize {
+        let idx = self.anchors.partition_point(|&(_, n)| n <= norm);
+        let (r, n) = if idx == 0 { (0, 0) } else { self.anchors[idx - 1] };
+        let raw = r + (norm - n);
+        match self.anchors.get(idx) {
+            Some(&(next_raw, _)) => raw.min(next_raw),
+            None => raw,
+        }
+    }
+}
+
+#[must_use]
 pub fn khmer_normalize(text: &str) -> String {
+    static DEFAULT: OnceLock<NormalizationConfig> = OnceLock::new();
+    khmer_normalize_with(text, DEFAULT.get_or_init(NormalizationConfig::default))
+}
+
+#[must_use]
+pub fn khmer_normalize_with(text: &str, config: &NormalizationConfig) -> String {
+    normalize_impl(text, config, &mut false, None)
+}
+
+/// Normalizes `text` and reports whether anything was edited (zero-width
+/// characters stripped, compatibility forms folded, vowels merged or cluster
+/// parts reordered). The flag is set during the pass, not by comparing strings.
+#[must_use]
+pub fn khmer_normalize_checked(text: &str) -> (String, bool) {
+    static DEFAULT: OnceLock<NormalizationConfig> = OnceLock::new();
+    khmer_normalize_checked_with(text, DEFAULT.get_or_init(NormalizationConfig::default))
+}
+
+#[must_use]
+pub fn khmer_normalize_checked_with(text: &str, config: &NormalizationConfig) -> (String, bool) {
+    let mut changed = false;
+    let out = normalize_impl(text, config, &mut changed, None);
+    (out, changed)
+}
+
+/// Normalizes `text` and returns the offset map from the normalized text back
+/// to `text`. Cluster reordering keeps every cluster at the same byte range,
+/// so anchors are only recorded after strips, folds and vowel merges; offsets
+/// inside a reordered cluster are approximate.
+pub fn khmer_normalize_mapped_with(text: &str, config: &NormalizationConfig) -> (String, OffsetMap) {
+    let mut map = OffsetMap::new();
+    let out = normalize_impl(text, config, &mut false, Some(&mut map));
+    (out, map)
+}
+
+fn normalize_impl(text: &str, config: &NormalizationConfig, changed: &mut bool, mut map: Option<&mut OffsetMap>) -> String {
     let mut temp = String::with_capacity(text.len());
-    let mut chars = text.chars().peekable();
+    let mut chars = text.char_indices().peekable();
+    let mut anchor = |raw: usize, norm: usize| if let Some(m) = map.as_deref_mut() { m.push(raw, norm) };
     
-    while let Some(c) = chars.next() {
-        if c == '\u{200B}' || c == '\u{200C}' || c == '\u{200D}' { continue; }
+    while let Some((i, c)) = chars.next() {
+        let end = i + c.len_utf8();
+        #[cfg(feature = "grapheme-clusters")]
+        if c == '\u{200D}' && joins_grapheme(&temp, chars.peek().map(|&(_, n)| n)) {
+            temp.push(c);
+            continue;
+        }
+        if config.strip_chars.contains(&c) { *changed = true; anchor(end, temp.len()); continue; }
+        if let Some(rep) = config.fold_table.get(&c) {
+            temp.push_str(rep);
+            if rep.chars().ne(std::iter::once(c)) {
+                *changed = true;
+                anchor(end, temp.len());
+            }
+            continue;
+        }
         if c == '\u{17C1}' { // e
-            if let Some(&next) = chars.peek() {
-                if next == '\u{17B8}' { temp.push('\u{17BE}'); chars.next(); continue; } // oe
-                if next == '\u{17B6}' { temp.push('\u{17C4}'); chars.next(); continue; } // au
+            if let Some(&(_, next)) = chars.peek() {
+                let merged = match next {
+                    '\u{17B8}' => Some('\u{17BE}'), // oe
+                    '\u{17B6}' => Some('\u{17C4}'), // au
+                    _ => None,
+                };
+                if let Some(m) = merged {
+                    temp.push(m);
+                    chars.next();
+                    *changed = true;
+                    anchor(end + next.len_utf8(), temp.len());
+                    continue;
+                }
             }
         }
         temp.push(c);
@@ -48,17 +221,21 @@ pub fn khmer_normalize(text: &str) -> String {
     
     let mut final_str = String::with_capacity(temp.len());
     let mut cluster: Vec<ClsPart> = Vec::with_capacity(8);
-    let mut cls_count = 0;
+    let max_parts = config.max_cluster_parts.clamp(1, u8::MAX as usize);
     
     let mut iter = temp.chars().peekable();
     
     while let Some(c) = iter.next() {
         let type_ = get_char_type_norm(c);
+        if type_ >= 2 && cluster.len() >= max_parts {
+            flush_cluster(&mut final_str, &mut cluster, changed);
+        }
+        // Position within the cluster; only used to detect reordering
+        let cls_count = cluster.len() as u8;
         
         if type_ == 1 { // BASE
-            flush_cluster(&mut final_str, &mut cluster);
-            cluster.push(ClsPart { c1: c, c2: None, type_, index: cls_count });
-            cls_count += 1;
+            flush_cluster(&mut final_str, &mut cluster, changed);
+            cluster.push(ClsPart { c1: c, c2: None, type_, index: 0 });
         } else if type_ == 2 { // COENG
              let mut c2 = None;
              if let Some(&next) = iter.peek() {
@@ -68,25 +245,38 @@ pub fn khmer_normalize(text: &str) -> String {
                  }
              }
              cluster.push(ClsPart { c1: c, c2, type_: 2, index: cls_count });
-             cls_count += 1;
         } else if type_ > 2 {
             if !cluster.is_empty() {
                 cluster.push(ClsPart { c1: c, c2: None, type_, index: cls_count });
-                cls_count += 1;
             } else {
                 final_str.push(c);
             }
         } else {
-            flush_cluster(&mut final_str, &mut cluster);
+            flush_cluster(&mut final_str, &mut cluster, changed);
             final_str.push(c);
-            cls_count = 0;
         }
     }
-    flush_cluster(&mut final_str, &mut cluster);
+    flush_cluster(&mut final_str, &mut cluster, changed);
     final_str
 }
 
-fn flush_cluster(final_str: &mut String, cluster: &mut Vec<ClsPart>) {
+// Writes `cluster` in canonical order. Only the parts after the first are
+// sorted: a cluster opens with its base (or, after a cap flush, a coeng pair)
+// and every later part is pushed behind it, so a two-part cluster has a single
+// possible order and is canonical as typed. Swapped marks such as a register
+// shifter typed after its vowel need a base plus two parts and are sorted here.
+// A ZWJ that glues the last grapheme of `before` and `next` into one
+// grapheme (an emoji sequence) is part of the text, not noise.
+#[cfg(feature = "grapheme-clusters")]
+fn joins_grapheme(before: &str, next: Option<char>) -> bool {
+    use unicode_segmentation::UnicodeSegmentation;
+    match (before.graphemes(true).next_back(), next) {
+        (Some(last), Some(n)) => format!("{}\u{200D}{}", last, n).graphemes(true).count() == 1,
+        _ => false,
+    }
+}
+
+fn flush_cluster(final_str: &mut String, cluster: &mut Vec<ClsPart>, changed: &mut bool) {
     if cluster.is_empty() { return; }
     if cluster.len() > 2 {
         let base = cluster.remove(0);
@@ -97,6 +287,7 @@ fn flush_cluster(final_str: &mut String, cluster: &mut Vec<ClsPart>) {
             else { a.index.cmp(&b.index) }
         });
         cluster.insert(0, base);
+        *changed |= cluster.windows(2).any(|w| w[0].index > w[1].index);
     }
     for part in cluster.iter() {
         final_str.push(part.c1);
@@ -1,180 +1,252 @@
-use crate::utils;
-
-pub struct RuleEngine;
-
-impl RuleEngine {
-    pub fn new() -> Self {
-        RuleEngine
-    }
-
-    pub fn apply(&self, text: &str, segments: &mut Vec<(usize, usize)>) {
-        let mut i = 0;
-        while i < segments.len() {
-            // Get current segment string slice
-            let (start, end) = segments[i];
-            let seg = &text[start..end];
-            let chars: Vec<char> = seg.chars().collect();
-            let len = chars.len();
-            let mut rule_applied = false;
-
-            // Rule 0: "Ahsda Exception Keep"
-            // txt[3] == 0xE1 && txt[4] == 0x9F && txt[5] == 0x8F (U+17CF Ahsda)
-            // txt[0,1,2] check
-            if len == 2 { // 2 chars (Rust chars)
-                 if chars[1] == '\u{17CF}' {
-                     if chars[0] == '\u{1780}' || chars[0] == '\u{178A}' { // KA or DA
-                         i += 1;
-                         continue;
-                     }
-                 }
-            }
-
-            // Rule 1: "Prefix OR Merge" (U+17A2)
-            if len == 1 && chars[0] == '\u{17A2}' {
-                if i + 1 < segments.len() {
-                    let (_, next_end) = segments[i+1];
-                    let next_seg = &text[segments[i+1].0..next_end];
-                    if !is_separator(next_seg) {
-                        // Merge: extend current end to next end
-                        segments[i].1 = next_end;
-                        segments.remove(i+1);
-                        rule_applied = true;
-                    }
-                }
-            }
-            
-            if rule_applied { continue; }
-
-            // Rule 2 & 4: Suffix Checks (Signs Merge Left)
-            // C code checked specific bytes for suffix.
-            // if (txt[0]...txt[2] is KA-QA [0x80-0xA2]) check suffix
-            // suffix[0].. is U+17CB, 17CD, 17CE, 17CC
-            if len == 2 {
-                 if chars[0] >= '\u{1780}' && chars[0] <= '\u{17A2}' {
-                     let s = chars[1];
-                     if s == '\u{17CB}' || s == '\u{17CE}' || s == '\u{17CF}' || s == '\u{17CC}' { // 8B, 8E, 8F, 8C
-                         if i > 0 {
-                             // Merge current into previous
-                             let (_, curr_end) = segments[i];
-                             segments[i-1].1 = curr_end;
-                             segments.remove(i);
-                             i -= 1;
-                             rule_applied = true;
-                         }
-                     }
-                 }
-            }
-
-            if rule_applied { continue; }
-
-            // Rule 3: Samyok Sannya (Merge Next)
-            // U+17D0 (90)
-            if len == 2 {
-                if chars[0] >= '\u{1780}' && chars[0] <= '\u{17A2}' {
-                    if chars[1] == '\u{17D0}' {
-                        if i + 1 < segments.len() {
-                            let (_, next_end) = segments[i+1];
-                            segments[i].1 = next_end;
-                            segments.remove(i+1);
-                            rule_applied = true;
-                        }
-                    }
-                }
-            }
-
-             if rule_applied { continue; }
-
-            // Rule 4: Specific Char Merge Previous
-            // ឃ(1783), ជ(1787), ឈ(1788), ឋ(178B), ឌ(178C), ឍ(178D), ណ(178E), ថ(1790), ធ(1792), ន(1793), យ(1799), ហ(17A0)
-            if len == 1 {
-                let c = chars[0];
-                let is_target = match c {
-                    '\u{1783}' | '\u{1787}' | '\u{1788}' | '\u{178B}' | '\u{178C}' | '\u{178D}' | 
-                    '\u{178E}' | '\u{1790}' | '\u{1792}' | '\u{1793}' | '\u{1799}' | '\u{17A0}' => true,
-                    _ => false,
-                };
-
-                if is_target {
-                    let p_sep = if i > 0 { 
-                        let (p_start, p_end) = segments[i-1];
-                        is_separator(&text[p_start..p_end]) 
-                    } else { 
-                        true 
-                    };
-
-                    if !p_sep {
-                        if i > 0 {
-                            let (_, curr_end) = segments[i];
-                            segments[i-1].1 = curr_end;
-                            segments.remove(i);
-                            i -= 1;
-                            rule_applied = true;
-                        }
-                    }
-                }
-            }
-
-            if rule_applied { continue; }
-
-            // Rule 5: Invalid Single Consonant Cleanup
-            if is_invalid_single(seg) {
-                let p_sep = if i > 0 { 
-                    let (p_start, p_end) = segments[i-1];
-                    is_separator(&text[p_start..p_end]) 
-                } else { 
-                    true 
-                };
-                
-                if !p_sep {
-                    if i > 0 {
-                        let (_, curr_end) = segments[i];
-                        segments[i-1].1 = curr_end;
-                        segments.remove(i);
-                        i -= 1;
-                        rule_applied = true;
-                    }
-                }
-            }
-            
-            if !rule_applied {
-                i += 1;
-            }
-        }
-    }
-}
-
-fn is_separator(s: &str) -> bool {
-    // Only check first char? The C code checks cp of string, implies single char check mainly
-    // But returns true if any char is sep?
-    // C: utf8_decode_re(s, &cp); ... 
-    // It checks ONLY the first character.
-    if let Some(c) = s.chars().next() {
-        return utils::is_separator_cp(c);
-    }
-    false
-}
-
-fn is_invalid_single(s: &str) -> bool {
-    let mut chars = s.chars();
-    let first = match chars.next() {
-        Some(c) => c,
-        None => return false,
-    };
-    
-    // Fix: Non-Khmer characters are valid singles (e.g. Latin 'a')
-    if !utils::is_khmer_char(first) { return false; }
-    
-    if chars.next().is_some() { return false; } // More than 1 char -> valid (or handled elsewhere)
-    
-    // logic: 
-    // if ((cp >= 0x1780 && cp <= 0x17A2) || (cp >= 0x17A3 && cp <= 0x17B3)) return 0;
-    // if (isdigit(cp) || (cp >= 0x17E0 && cp <= 0x17E9)) return 0;
-    // if (is_separator(s)) return 0;
-    // return 1;
-    
-    if (first >= '\u{1780}' && first <= '\u{17A2}') || (first >= '\u{17A3}' && first <= '\u{17B3}') { return false; }
-    if utils::is_digit_cp(first) { return false; }
-    if utils::is_separator_cp(first) { return false; }
-    
-    true
-}
+use crate::kdict::{KDict, FLAG_PREFIX, FLAG_STANDALONE, FLAG_SUFFIX};
+use crate::utils;
+use std::collections::HashSet;
+
+/// When rule 1 glues a standalone QA (U+17A2, the "a/ar" prefix) onto the
+/// token after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QaPrefixMerge {
+    /// Never; QA stays a token of its own.
+    Never,
+    /// Before any token that is not a separator.
+    #[default]
+    Always,
+    /// Only before a dictionary word, so QA is not welded onto an unknown
+    /// fragment. Without a dictionary this never merges.
+    BeforeKnownWord,
+}
+
+#[derive(Default)]
+pub struct RuleEngine {
+    pub qa_prefix: QaPrefixMerge,
+    /// Treat lone dependent vowels (orphans left by `OrphanedVowels::Flag`)
+    /// like separators: nothing merges into or out of them.
+    pub keep_orphaned_vowels: bool,
+    /// Extra characters whose tokens are treated like separators
+    /// (`SegmenterConfig::force_boundary_chars`).
+    pub boundary_chars: HashSet<char>,
+}
+
+impl RuleEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_qa_prefix(qa_prefix: QaPrefixMerge) -> Self {
+        Self { qa_prefix, ..Self::default() }
+    }
+
+    /// Runs the built-in merge rules over `segments` left to right. The first
+    /// rule that fires on a token merges it into a neighbour, and the merged
+    /// token is checked against every rule again: a leftward merge makes the
+    /// grown left neighbour the current token, so cascades such as a run of
+    /// stray signs all folding into one word are intended. Each merge removes
+    /// a token, so this takes at most 2n - 1 steps and O(n) time.
+    pub fn apply(&self, text: &str, segments: &mut Vec<(usize, usize)>) {
+        self.apply_with_dict(text, segments, None)
+    }
+
+    /// `apply`, with `kdict` used by rules that check for known words. With
+    /// a version 3 dictionary the entries' affix flags are applied first
+    /// (see `apply_affix_flags`), then the built-in rules.
+    pub fn apply_with_dict(&self, text: &str, segments: &mut Vec<(usize, usize)>, kdict: Option<&KDict>) {
+        if let Some(kd) = kdict.filter(|kd| kd.has_flags()) {
+            affix_flags_with(text, segments, kd, |t| self.is_boundary(t));
+        }
+
+        let len = segments.len();
+        if len == 0 { return; }
+
+        // Compacts in place: segments[..w] are final, `cur` is under
+        // examination and segments[r..] are still unread. Every merge
+        // consumes a token, so there are at most n - 1 merges plus n keeps,
+        // and no step shifts the vector.
+        let (mut w, mut r) = (0, 1);
+        let mut cur = segments[0];
+        loop {
+            let prev = (w > 0).then(|| &text[segments[w - 1].0..segments[w - 1].1]);
+            let next = (r < len).then(|| &text[segments[r].0..segments[r].1]);
+            match self.step(&text[cur.0..cur.1], prev, next, kdict) {
+                // The grown left neighbour is examined again from rule 0
+                Step::MergeLeft => {
+                    w -= 1;
+                    segments[w].1 = cur.1;
+                    cur = segments[w];
+                }
+                // The grown token is examined again from rule 0
+                Step::MergeRight => {
+                    cur.1 = segments[r].1;
+                    r += 1;
+                }
+                Step::Keep => {
+                    segments[w] = cur;
+                    w += 1;
+                    if r == len { break; }
+                    cur = segments[r];
+                    r += 1;
+                }
+            }
+        }
+        segments.truncate(w);
+    }
+
+    // A separator, or a token starting with one of `boundary_chars`.
+    fn is_boundary(&self, seg: &str) -> bool {
+        is_separator(seg) || self.is_boundary_char_token(seg)
+    }
+
+    fn is_boundary_char_token(&self, seg: &str) -> bool {
+        !self.boundary_chars.is_empty() && seg.chars().next().is_some_and(|c| self.boundary_chars.contains(&c))
+    }
+
+    fn is_kept_orphan(&self, seg: &str) -> bool {
+        let mut chars = seg.chars();
+        self.keep_orphaned_vowels
+            && chars.next().is_some_and(utils::is_dependent_vowel)
+            && chars.next().is_none()
+    }
+
+    // The first rule that fires on `seg`, given its neighbours (`None` at
+    // the edges).
+    fn step(&self, seg: &str, prev: Option<&str>, next: Option<&str>, kdict: Option<&KDict>) -> Step {
+        // Rules only ever inspect the first two chars and whether there are
+        // more; avoid collecting, or even walking a long merged token.
+        let mut chars = ['\0'; 2];
+        let mut len = 0;
+        for c in seg.chars().take(3) {
+            if len < 2 { chars[len] = c; }
+            len += 1;
+        }
+        // Like rules 4 and 5, nothing is glued onto a separator: a leading
+        // space would turn into part of a "word".
+        let joinable = |t: &str| !self.is_boundary(t) && !self.is_kept_orphan(t);
+        if self.is_kept_orphan(seg) || self.is_boundary_char_token(seg) { return Step::Keep; }
+        let prev_joinable = prev.is_some_and(joinable);
+        let next_joinable = next.is_some_and(joinable);
+
+        // Rule 0: "Ahsda Exception Keep"
+        // txt[3] == 0xE1 && txt[4] == 0x9F && txt[5] == 0x8F (U+17CF Ahsda)
+        // txt[0,1,2] check
+        if len == 2 && chars[1] == '\u{17CF}' && (chars[0] == '\u{1780}' || chars[0] == '\u{178A}') { // KA or DA
+            return Step::Keep;
+        }
+
+        // Rule 1: "Prefix OR Merge" (U+17A2)
+        if len == 1 && chars[0] == '\u{17A2}' && next_joinable {
+            let allowed = match self.qa_prefix {
+                QaPrefixMerge::Never => false,
+                QaPrefixMerge::BeforeKnownWord => kdict.is_some_and(|kd| next.is_some_and(|n| kd.contains(n))),
+                QaPrefixMerge::Always => true,
+            };
+            if allowed { return Step::MergeRight; }
+        }
+
+        let base_first = len == 2 && chars[0] >= '\u{1780}' && chars[0] <= '\u{17A2}';
+
+        // Rule 2 & 4: Suffix Checks (Signs Merge Left)
+        // C code checked specific bytes for suffix.
+        // if (txt[0]...txt[2] is KA-QA [0x80-0xA2]) check suffix
+        // suffix[0].. is U+17CB, 17CD, 17CE, 17CC
+        if base_first && matches!(chars[1], '\u{17CB}' | '\u{17CE}' | '\u{17CF}' | '\u{17CC}') && prev_joinable { // 8B, 8E, 8F, 8C
+            return Step::MergeLeft;
+        }
+
+        // Rule 3: Samyok Sannya (Merge Next)
+        // U+17D0 (90)
+        if base_first && chars[1] == '\u{17D0}' && next_joinable {
+            return Step::MergeRight;
+        }
+
+        // Rule 4: Specific Char Merge Previous
+        // ឃ(1783), ជ(1787), ឈ(1788), ឋ(178B), ឌ(178C), ឍ(178D), ណ(178E), ថ(1790), ធ(1792), ន(1793), យ(1799), ហ(17A0)
+        if len == 1 && prev_joinable {
+            let is_target = matches!(chars[0],
+                '\u{1783}' | '\u{1787}' | '\u{1788}' | '\u{178B}' | '\u{178C}' | '\u{178D}' |
+                '\u{178E}' | '\u{1790}' | '\u{1792}' | '\u{1793}' | '\u{1799}' | '\u{17A0}'
+            );
+            if is_target { return Step::MergeLeft; }
+        }
+
+        // Rule 5: Invalid Single Consonant Cleanup
+        if prev_joinable && is_invalid_single(seg) {
+            return Step::MergeLeft;
+        }
+
+        Step::Keep
+    }
+}
+
+enum Step {
+    Keep,
+    MergeLeft,
+    MergeRight,
+}
+
+/// Data-driven affix merging: a `FLAG_PREFIX` entry joins the token after it
+/// and a `FLAG_SUFFIX` entry the token before it, never across a separator.
+/// An affix that is also `FLAG_STANDALONE` only joins a dictionary word.
+/// Each token's flags come from the DP's tokens, so a merged token is not
+/// looked up again (a prefix + suffix around one stem still gives one token).
+pub fn apply_affix_flags(text: &str, segments: &mut Vec<(usize, usize)>, kd: &KDict) {
+    affix_flags_with(text, segments, kd, is_separator)
+}
+
+// `apply_affix_flags`, with `is_boundary` deciding what an affix never joins.
+fn affix_flags_with(text: &str, segments: &mut Vec<(usize, usize)>, kd: &KDict, is_boundary: impl Fn(&str) -> bool) {
+    let lookup = |&(s, e): &(usize, usize)| kd.lookup(&text.as_bytes()[s..e]);
+    let flags: Vec<u8> = segments.iter().map(|span| lookup(span).map_or(0, |slot| kd.flags(slot))).collect();
+    let joinable = |k: usize, affix_flags: u8| {
+        let span = &segments[k];
+        !is_boundary(&text[span.0..span.1]) && (affix_flags & FLAG_STANDALONE == 0 || lookup(span).is_some())
+    };
+
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(segments.len());
+    let mut join_next = false;
+    for (k, &span) in segments.iter().enumerate() {
+        let f = flags[k];
+        let attach_left = f & FLAG_SUFFIX != 0 && k > 0 && joinable(k - 1, f);
+        match merged.last_mut() {
+            Some(last) if join_next || attach_left => last.1 = span.1,
+            _ => merged.push(span),
+        }
+        join_next = f & FLAG_PREFIX != 0 && k + 1 < segments.len() && joinable(k + 1, f);
+    }
+    *segments = merged;
+}
+
+fn is_separator(s: &str) -> bool {
+    // Only check first char? The C code checks cp of string, implies single char check mainly
+    // But returns true if any char is sep?
+    // C: utf8_decode_re(s, &cp); ... 
+    // It checks ONLY the first character.
+    if let Some(c) = s.chars().next() {
+        return utils::is_separator_cp(c);
+    }
+    false
+}
+
+fn is_invalid_single(s: &str) -> bool {
+    let mut chars = s.chars();
+    let first = match chars.next() {
+        Some(c) => c,
+        None => return false,
+    };
+    
+    // Fix: Non-Khmer characters are valid singles (e.g. Latin 'a')
+    if !utils::is_khmer_char(first) { return false; }
+    
+    if chars.next().is_some() { return false; } // More than 1 char -> valid (or handled elsewhere)
+    
+    // logic: 
+    // if ((cp >= 0x1780 && cp <= 0x17A2) || (cp >= 0x17A3 && cp <= 0x17B3)) return 0;
+    // if (isdigit(cp) || (cp >= 0x17E0 && cp <= 0x17E9)) return 0;
+    // if (is_separator(s)) return 0;
+    // return 1;
+    
+    if ('\u{1780}'..='\u{17A2}').contains(&first) || ('\u{17A3}'..='\u{17B3}').contains(&first) { return false; }
+    if utils::is_digit_cp(first) { return false; }
+    if utils::is_separator_cp(first) { return false; }
+    
+    true
+}
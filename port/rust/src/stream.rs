@@ -0,0 +1,71 @@
+// Order-preserving parallel line processing with bounded memory.
+
+use std::io::{self, Write};
+
+use rayon::prelude::*;
+
+/// Lines held in memory at once by default: enough to keep every core busy,
+/// small enough that multi-gigabyte inputs never need to fit in RAM.
+pub const DEFAULT_WINDOW: usize = 4096;
+
+/// Pulls up to `window` lines at a time from `lines`, maps them through `f`
+/// on the rayon pool and writes the results to `writer` in input order before
+/// reading more. At most `window` inputs and outputs are alive at any point.
+/// `f` returns exactly what is written for its line (add your own newline).
+///
+/// Returns the number of lines processed, or the first write error.
+pub fn process_lines<I, W, F>(lines: I, writer: &mut W, window: usize, f: F) -> io::Result<usize>
+where
+    I: IntoIterator<Item = String>,
+    W: Write + ?Sized,
+    F: Fn(&str) -> String + Sync,
+{
+    let window = window.max(1);
+    let mut lines = lines.into_iter();
+    let mut batch: Vec<String> = Vec::with_capacity(window);
+    let mut total = 0;
+    loop {
+        batch.clear();
+        batch.extend(lines.by_ref().take(window));
+        if batch.is_empty() { break; }
+
+        let results: Vec<String> = batch.par_iter().map(|l| f(l)).collect();
+        for r in &results {
+            writer.write_all(r.as_bytes())?;
+        }
+        total += batch.len();
+    }
+    writer.flush()?;
+    Ok(total)
+}
+
+/// How `KhmerSegmenter::segment_reader_with` reads its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderOptions {
+    /// Stop after this many lines have been segmented; nothing past them is
+    /// read.
+    pub max_lines: usize,
+    /// Skip lines that are not valid UTF-8 instead of stopping at them.
+    pub skip_invalid_utf8: bool,
+    /// Drop a byte order mark at the start of the input.
+    pub strip_bom: bool,
+    /// Segment with `segment_original` (tokens as written) instead of
+    /// `segment`.
+    pub keep_original: bool,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        Self { max_lines: usize::MAX, skip_invalid_utf8: false, strip_bom: false, keep_original: false }
+    }
+}
+
+/// What `KhmerSegmenter::segment_reader_with` got through.
+#[derive(Debug, Default)]
+pub struct ReaderSummary {
+    /// Lines segmented and written; skipped lines are not counted.
+    pub lines: usize,
+    /// The read error that ended the input early, if any. Lines before it
+    /// have been written.
+    pub read_error: Option<io::Error>,
+}
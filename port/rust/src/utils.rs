@@ -1,256 +1,620 @@
-
-#[cfg(target_arch = "x86_64")]
-use std::arch::x86_64::*;
-
-#[allow(unused_unsafe)]
-#[inline(always)]
-pub unsafe fn fast_str_eq(a: *const u8, b: *const u8, len: usize) -> bool {
-    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
-    {
-        let mut i = 0;
-        while i + 32 <= len {
-            let va = _mm256_loadu_si256(a.add(i) as *const __m256i);
-            let vb = _mm256_loadu_si256(b.add(i) as *const __m256i);
-            let cmp = _mm256_cmpeq_epi8(va, vb);
-            let mask = _mm256_movemask_epi8(cmp);
-            if mask != -1 { return false; }
-            i += 32;
-        }
-        let rem = len - i;
-        if rem == 0 { return true; }
-        let mut j = 0;
-        while j < rem {
-            if *a.add(i + j) != *b.add(i + j) { return false; }
-            j += 1;
-        }
-        return true;
-    }
-
-    #[cfg(all(target_arch = "x86_64", not(target_feature = "avx2")))]
-    {
-        let mut i = 0;
-        while i + 16 <= len {
-             let va = _mm_loadu_si128(a.add(i) as *const __m128i);
-             let vb = _mm_loadu_si128(b.add(i) as *const __m128i);
-             let cmp = _mm_cmpeq_epi8(va, vb);
-             let mask = _mm_movemask_epi8(cmp);
-             if mask != 0xFFFF { return false; }
-             i += 16;
-        }
-        let rem = len - i;
-        if rem == 0 { return true; }
-        let mut j = 0;
-        while j < rem {
-            if *a.add(i + j) != *b.add(i + j) { return false; }
-            j += 1;
-        }
-        return true;
-    }
-
-    #[cfg(not(target_arch = "x86_64"))]
-    {
-        // SWAR Optimization (8 bytes at a time) for WASM/other
-        // WASM supports unaligned loads
-        let mut i = 0;
-        while i + 8 <= len {
-             let va = (a.add(i) as *const u64).read_unaligned();
-             let vb = (b.add(i) as *const u64).read_unaligned();
-             if va != vb { return false; }
-             i += 8;
-        }
-
-        while i < len {
-            if *a.add(i) != *b.add(i) { return false; }
-            i += 1;
-        }
-        return true;
-    }
-}
-
-pub fn is_khmer_char(cp: char) -> bool {
-    (cp >= '\u{1780}' && cp <= '\u{17FF}') || (cp >= '\u{19E0}' && cp <= '\u{19FF}')
-}
-
-pub fn is_digit_cp(cp: char) -> bool {
-    // 0-9
-    if cp >= '0' && cp <= '9' { return true; }
-    // Khmer Digits
-    if cp >= '\u{17E0}' && cp <= '\u{17E9}' { return true; }
-    false
-}
-
-pub fn is_separator_cp(cp: char) -> bool {
-    // Khmer Punctuation
-    if cp >= '\u{17D4}' && cp <= '\u{17DA}' { return true; }
-    // Khmer Currency
-    if cp == '\u{17DB}' { return true; }
-
-    // Basic ASCII Punctuation & Space
-    if (cp as u32) < 0x80 && (cp.is_ascii_punctuation() || cp.is_ascii_whitespace()) { return true; }
-    
-    // Additional separators
-    if cp == '\u{00A0}' { return true; } // Non-breaking space
-    if cp == '\u{02DD}' { return true; } // Double acute accent
-    
-    // Latin-1 Supplement Punctuation
-    if cp == '\u{00AB}' || cp == '\u{00BB}' { return true; }
-    
-    // General Punctuation (0x2000-0x206F)
-    if cp >= '\u{2000}' && cp <= '\u{206F}' { return true; }
-    
-    // Currency Symbols (0x20A0-0x20CF)
-    if cp >= '\u{20A0}' && cp <= '\u{20CF}' { return true; }
-
-    // Latin-1 Currency
-    if cp == '\u{00A3}' || cp == '\u{00A5}' { return true; }
-
-    false
-}
-
-pub fn is_valid_single_base_char(cp: char) -> bool {
-    // Consonants: 0x1780 - 0x17A2
-    if cp >= '\u{1780}' && cp <= '\u{17A2}' { return true; }
-    // Independent Vowels: 0x17A3 - 0x17B3
-    if cp >= '\u{17A3}' && cp <= '\u{17B3}' { return true; }
-    false
-}
-
-pub fn get_khmer_cluster_length(text: &str) -> usize {
-    let mut chars = text.chars();
-    let first = match chars.next() {
-        Some(c) => c,
-        None => return 0,
-    };
-
-    // Must start with Base or Indep Vowel
-    if !((first >= '\u{1780}' && first <= '\u{17B3}')) {
-        // If it's a coeng/vowel at start, invalid but consume 1 char
-        return first.len_utf8();
-    }
-
-    let mut len = first.len_utf8();
-    let mut iter = text[len..].chars();
-    
-    while let Some(next_cp) = iter.next() {
-        // Coeng (0x17D2) handling
-        if next_cp == '\u{17D2}' {
-             // Check next next
-             let next_len = next_cp.len_utf8();
-             let mut lookahead = iter.clone();
-             if let Some(sub_cp) = lookahead.next() {
-                 if sub_cp >= '\u{1780}' && sub_cp <= '\u{17A2}' {
-                     len += next_len + sub_cp.len_utf8();
-                     iter.next(); // consume sub_cp
-                     continue;
-                 }
-             }
-             break; // Trailing coeng or invalid
-        }
-
-        // Vowels/Signs
-        // 0x17B6 - 0x17D1, 0x17D3, 0x17DD
-        if (next_cp >= '\u{17B6}' && next_cp <= '\u{17D1}') || next_cp == '\u{17D3}' || next_cp == '\u{17DD}' {
-            len += next_cp.len_utf8();
-            continue;
-        }
-
-        break;
-    }
-    
-    len
-}
-
-pub fn get_number_length(text: &str) -> usize {
-    let mut chars = text.chars();
-    let first = match chars.next() {
-        Some(c) => c,
-        None => return 0,
-    };
-    
-    if !is_digit_cp(first) { return 0; }
-    
-    let mut len = first.len_utf8();
-    let mut iter = text[len..].chars();
-    
-    while let Some(next_cp) = iter.next() {
-        if is_digit_cp(next_cp) {
-            len += next_cp.len_utf8();
-            continue;
-        }
-        
-        // Separators: , . Space
-        if next_cp == ',' || next_cp == '.' {
-            let next_len = next_cp.len_utf8();
-            let mut lookahead = iter.clone();
-             if let Some(f_cp) = lookahead.next() {
-                 if is_digit_cp(f_cp) {
-                     len += next_len + f_cp.len_utf8();
-                     iter.next(); // consume digit
-                     continue;
-                 }
-             }
-        }
-        break;
-    }
-    len
-}
-
-
-
-pub fn is_acronym_start(text: &str) -> bool {
-    let mut chars = text.chars();
-    let first = match chars.next() {
-        Some(c) => c,
-        None => return false,
-    };
-    
-    // Must start with Khmer Consonant or Independent Vowel
-    if !((first >= '\u{1780}' && first <= '\u{17B3}')) { return false; }
-    
-    let cluster_bytes = get_khmer_cluster_length(text);
-    if cluster_bytes == 0 { return false; }
-    
-    if let Some(c) = text[cluster_bytes..].chars().next() {
-        if c == '.' { return true; }
-    }
-    
-    false
-}
-
-pub fn get_acronym_length(text: &str) -> usize {
-    let mut len = 0;
-    let mut rest = text;
-    
-    loop {
-        let mut chars = rest.chars();
-        let first = match chars.next() {
-            Some(c) => c,
-            None => break,
-        };
-        
-        if !((first >= '\u{1780}' && first <= '\u{17B3}')) { break; }
-        
-        let cluster_bytes = get_khmer_cluster_length(rest);
-        if cluster_bytes == 0 { break; }
-        
-        if let Some(c) = rest[cluster_bytes..].chars().next() {
-            if c == '.' {
-                let dot_len = c.len_utf8();
-                len += cluster_bytes + dot_len;
-                rest = &rest[cluster_bytes+dot_len..];
-                continue;
-            }
-        }
-        break;
-    }
-    
-    len
-}
-
-pub fn djb2_hash(str: &[u8]) -> u32 {
-    let mut hash: u32 = 5381;
-    for &c in str {
-        hash = (hash << 5).wrapping_add(hash).wrapping_add(c as u32);
-    }
-    hash
-}
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+/// Compares `len` bytes at `a` and `b`.
+///
+/// # Safety
+///
+/// Both pointers must be valid for reads of `len` bytes.
+#[allow(unused_unsafe, clippy::needless_return)]
+#[inline(always)]
+pub unsafe fn fast_str_eq(a: *const u8, b: *const u8, len: usize) -> bool {
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    {
+        let mut i = 0;
+        while i + 32 <= len {
+            let va = _mm256_loadu_si256(a.add(i) as *const __m256i);
+            let vb = _mm256_loadu_si256(b.add(i) as *const __m256i);
+            let cmp = _mm256_cmpeq_epi8(va, vb);
+            let mask = _mm256_movemask_epi8(cmp);
+            if mask != -1 { return false; }
+            i += 32;
+        }
+        let rem = len - i;
+        if rem == 0 { return true; }
+        let mut j = 0;
+        while j < rem {
+            if *a.add(i + j) != *b.add(i + j) { return false; }
+            j += 1;
+        }
+        return true;
+    }
+
+    #[cfg(all(target_arch = "x86_64", not(target_feature = "avx2")))]
+    {
+        let mut i = 0;
+        while i + 16 <= len {
+             let va = _mm_loadu_si128(a.add(i) as *const __m128i);
+             let vb = _mm_loadu_si128(b.add(i) as *const __m128i);
+             let cmp = _mm_cmpeq_epi8(va, vb);
+             let mask = _mm_movemask_epi8(cmp);
+             if mask != 0xFFFF { return false; }
+             i += 16;
+        }
+        let rem = len - i;
+        if rem == 0 { return true; }
+        let mut j = 0;
+        while j < rem {
+            if *a.add(i + j) != *b.add(i + j) { return false; }
+            j += 1;
+        }
+        return true;
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        // SWAR Optimization (8 bytes at a time) for WASM/other
+        // WASM supports unaligned loads
+        let mut i = 0;
+        while i + 8 <= len {
+             let va = (a.add(i) as *const u64).read_unaligned();
+             let vb = (b.add(i) as *const u64).read_unaligned();
+             if va != vb { return false; }
+             i += 8;
+        }
+
+        while i < len {
+            if *a.add(i) != *b.add(i) { return false; }
+            i += 1;
+        }
+        return true;
+    }
+}
+
+pub fn is_khmer_char(cp: char) -> bool {
+    ('\u{1780}'..='\u{17FF}').contains(&cp) || ('\u{19E0}'..='\u{19FF}').contains(&cp)
+}
+
+/// Whether any character of `text` is in the Khmer or Khmer Symbols block.
+pub fn contains_khmer(text: &str) -> bool {
+    text.chars().any(is_khmer_char)
+}
+
+// Class bits for every codepoint below `CLASS_TABLE_END`, which covers ASCII,
+// Latin-1, the Khmer blocks, General Punctuation and Currency Symbols. The
+// predicates below read this table and only run their range checks above it.
+const CLASS_TABLE_END: u32 = 0x2100;
+const CLASS_DIGIT: u8 = 1;
+const CLASS_SEPARATOR: u8 = 1 << 1;
+const CLASS_SINGLE_BASE: u8 = 1 << 2;
+// `normalization::char_type_norm_slow` in bits 3..6
+const NORM_TYPE_SHIFT: u8 = 3;
+
+static CHAR_CLASSES: [u8; CLASS_TABLE_END as usize] = build_char_classes();
+
+const fn build_char_classes() -> [u8; CLASS_TABLE_END as usize] {
+    let mut table = [0u8; CLASS_TABLE_END as usize];
+    let mut cp = 0;
+    while cp < CLASS_TABLE_END {
+        if let Some(c) = char::from_u32(cp) {
+            let mut bits = (crate::normalization::char_type_norm_slow(c) as u8) << NORM_TYPE_SHIFT;
+            if is_digit_slow(c) { bits |= CLASS_DIGIT; }
+            if is_separator_slow(c) { bits |= CLASS_SEPARATOR; }
+            if is_single_base_slow(c) { bits |= CLASS_SINGLE_BASE; }
+            table[cp as usize] = bits;
+        }
+        cp += 1;
+    }
+    table
+}
+
+/// Class bits of `cp` from the lookup table, `None` above its range.
+#[inline(always)]
+fn char_classes(cp: char) -> Option<u8> {
+    CHAR_CLASSES.get(cp as usize).copied()
+}
+
+/// `normalization`'s cluster part type of `cp` from the lookup table, `None`
+/// above its range.
+#[inline(always)]
+pub(crate) fn norm_type(cp: char) -> Option<i32> {
+    char_classes(cp).map(|bits| (bits >> NORM_TYPE_SHIFT) as i32)
+}
+
+#[inline]
+pub fn is_digit_cp(cp: char) -> bool {
+    match char_classes(cp) {
+        Some(bits) => bits & CLASS_DIGIT != 0,
+        None => is_digit_slow(cp),
+    }
+}
+
+const fn is_digit_slow(cp: char) -> bool {
+    // 0-9
+    if cp >= '0' && cp <= '9' { return true; }
+    // Khmer Digits
+    if cp >= '\u{17E0}' && cp <= '\u{17E9}' { return true; }
+    false
+}
+
+#[inline]
+pub fn is_separator_cp(cp: char) -> bool {
+    match char_classes(cp) {
+        Some(bits) => bits & CLASS_SEPARATOR != 0,
+        None => is_separator_slow(cp),
+    }
+}
+
+const fn is_separator_slow(cp: char) -> bool {
+    // Khmer Punctuation
+    if cp >= '\u{17D4}' && cp <= '\u{17DA}' { return true; }
+    // Khmer Currency
+    if cp == '\u{17DB}' { return true; }
+
+    // Basic ASCII Punctuation & Space
+    if (cp as u32) < 0x80 && (cp.is_ascii_punctuation() || cp.is_ascii_whitespace()) { return true; }
+    
+    // Additional separators
+    if cp == '\u{00A0}' { return true; } // Non-breaking space
+    if cp == '\u{02DD}' { return true; } // Double acute accent
+    
+    // Latin-1 Supplement Punctuation
+    if cp == '\u{00AB}' || cp == '\u{00BB}' { return true; }
+    
+    // General Punctuation (0x2000-0x206F)
+    if cp >= '\u{2000}' && cp <= '\u{206F}' { return true; }
+    
+    // Currency Symbols (0x20A0-0x20CF)
+    if cp >= '\u{20A0}' && cp <= '\u{20CF}' { return true; }
+
+    // Latin-1 Currency
+    if cp == '\u{00A3}' || cp == '\u{00A5}' { return true; }
+
+    false
+}
+
+/// Dependent vowel (U+17B6..U+17C5); one with no base before it is what
+/// repair mode treats as orphaned.
+pub fn is_dependent_vowel(cp: char) -> bool {
+    ('\u{17B6}'..='\u{17C5}').contains(&cp)
+}
+
+#[inline]
+pub fn is_valid_single_base_char(cp: char) -> bool {
+    match char_classes(cp) {
+        Some(bits) => bits & CLASS_SINGLE_BASE != 0,
+        None => is_single_base_slow(cp),
+    }
+}
+
+const fn is_single_base_slow(cp: char) -> bool {
+    // Consonants: 0x1780 - 0x17A2
+    if cp >= '\u{1780}' && cp <= '\u{17A2}' { return true; }
+    // Independent Vowels: 0x17A3 - 0x17B3
+    if cp >= '\u{17A3}' && cp <= '\u{17B3}' { return true; }
+    false
+}
+
+/// Coarse script of a character, for grouping tokens into runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    Khmer,
+    Latin,
+    /// ASCII or Khmer digits.
+    Digits,
+    /// Separators (punctuation, whitespace, currency); they belong to
+    /// whichever run they sit in.
+    Common,
+    Other,
+}
+
+impl Script {
+    pub fn of(cp: char) -> Script {
+        if is_digit_cp(cp) { return Script::Digits; }
+        if is_separator_cp(cp) { return Script::Common; }
+        if is_khmer_char(cp) { return Script::Khmer; }
+        if cp.is_ascii_alphabetic() || (('\u{00C0}'..='\u{024F}').contains(&cp) && cp.is_alphabetic()) {
+            return Script::Latin;
+        }
+        Script::Other
+    }
+}
+
+pub fn get_khmer_cluster_length(text: &str) -> usize {
+    let mut chars = text.chars();
+    let first = match chars.next() {
+        Some(c) => c,
+        None => return 0,
+    };
+
+    // Must start with Base or Indep Vowel
+    if !('\u{1780}'..='\u{17B3}').contains(&first) {
+        // If it's a coeng/vowel at start, invalid but consume 1 char
+        return first.len_utf8();
+    }
+
+    let mut len = first.len_utf8();
+    let mut iter = text[len..].chars();
+    
+    while let Some(next_cp) = iter.next() {
+        // Coeng (0x17D2) handling
+        if next_cp == '\u{17D2}' {
+             // Check next next
+             let next_len = next_cp.len_utf8();
+             let mut lookahead = iter.clone();
+             if let Some(sub_cp) = lookahead.next() {
+                 if ('\u{1780}'..='\u{17A2}').contains(&sub_cp) {
+                     len += next_len + sub_cp.len_utf8();
+                     iter.next(); // consume sub_cp
+                     continue;
+                 }
+             }
+             break; // Trailing coeng or invalid
+        }
+
+        // Vowels/Signs
+        // 0x17B6 - 0x17D1, 0x17D3, 0x17DD
+        if ('\u{17B6}'..='\u{17D1}').contains(&next_cp) || next_cp == '\u{17D3}' || next_cp == '\u{17DD}' {
+            len += next_cp.len_utf8();
+            continue;
+        }
+
+        break;
+    }
+    
+    len
+}
+
+/// Checks that every Khmer dependent character sits in a well-formed
+/// cluster, returning the byte offset of the first violation. Violations:
+/// - a dependent vowel, sign or register shifter (U+17B6..U+17D1, U+17D3,
+///   U+17DD) with no base before it, or after a cluster already ended;
+/// - a coeng (U+17D2) not followed by a consonant (U+1780..U+17A2);
+/// - the deprecated inherent vowels U+17B4/U+17B5 anywhere
+///   (`khmer_normalize` drops them).
+///
+/// Clusters follow `get_khmer_cluster_length`. Vowel and sign order inside
+/// a cluster is not checked; that is what normalization fixes. Everything
+/// outside the Khmer dependent range (digits, punctuation, other scripts)
+/// is always valid.
+pub fn validate_khmer_clusters(text: &str) -> Result<(), usize> {
+    let is_dependent = |c: char| ('\u{17B4}'..='\u{17D3}').contains(&c) || c == '\u{17DD}';
+    let mut pos = 0;
+    while let Some(c) = text[pos..].chars().next() {
+        if ('\u{1780}'..='\u{17B3}').contains(&c) {
+            pos += get_khmer_cluster_length(&text[pos..]);
+        } else if is_dependent(c) {
+            return Err(pos);
+        } else {
+            pos += c.len_utf8();
+        }
+    }
+    Ok(())
+}
+
+/// Byte length of the first extended grapheme cluster of `text` (UAX #29),
+/// 0 if `text` is empty: a letter with its combining marks, a ZWJ emoji
+/// sequence, a flag pair. Khmer text should use `get_khmer_cluster_length`.
+#[cfg(feature = "grapheme-clusters")]
+pub fn get_grapheme_length(text: &str) -> usize {
+    use unicode_segmentation::UnicodeSegmentation;
+    text.graphemes(true).next().map_or(0, str::len)
+}
+
+/// Step over the non-Khmer character `c` at the start of `text`: its whole
+/// grapheme cluster with the `grapheme-clusters` feature, otherwise `c` alone.
+/// The cluster stops before any Khmer character, so a Khmer vowel after a
+/// space or Latin letter is still left to the orphan rules.
+#[cfg(feature = "grapheme-clusters")]
+#[inline]
+pub(crate) fn non_khmer_len(text: &str, c: char) -> usize {
+    let len = get_grapheme_length(text);
+    let first = c.len_utf8();
+    text[first..len].find(is_khmer_char).map_or(len, |k| first + k)
+}
+
+#[cfg(not(feature = "grapheme-clusters"))]
+#[inline]
+pub(crate) fn non_khmer_len(_text: &str, c: char) -> usize {
+    c.len_utf8()
+}
+
+/// Number of clusters in `text`, using `get_khmer_cluster_length` for Khmer
+/// and counting every other character on its own (every grapheme cluster
+/// with the `grapheme-clusters` feature).
+pub fn cluster_count(text: &str) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < text.len() {
+        let c = text[i..].chars().next().unwrap();
+        i += if is_khmer_char(c) {
+            get_khmer_cluster_length(&text[i..])
+        } else {
+            non_khmer_len(&text[i..], c)
+        };
+        count += 1;
+    }
+    count
+}
+
+/// `get_number_length_with` under the default `DecimalStyle::Dot`.
+pub fn get_number_length(text: &str) -> usize {
+    get_number_length_with(text, DecimalStyle::Dot)
+}
+
+/// Byte length of the number at the start of `text` (ASCII or Khmer digits),
+/// 0 if it does not start with a digit. Which separators join depends on
+/// `style`:
+/// - the grouping mark joins only a group of exactly three digits, and only
+///   before the decimal mark: `1,000` is one number under `Dot`, `1,5` is not;
+/// - the decimal mark joins any digits and may repeat, so dotted ids like
+///   `1.2.3` stay whole; a grouping mark after it ends the number;
+/// - under `Dot`, a leading `.` chain of two or more three-digit groups
+///   (`1.000.000`) is read as grouping instead, and a `,` after it is then the
+///   decimal point.
+///
+/// The number always ends on a digit.
+pub fn get_number_length_with(text: &str, style: DecimalStyle) -> usize {
+    let (count, mut len) = digit_run(text);
+    if count == 0 { return 0; }
+
+    let (decimal, grouping) = number_marks(text, style);
+    let mut seen_decimal = false;
+    while let Some(sep) = text[len..].chars().next().filter(|&c| c == decimal || c == grouping) {
+        // Both marks are one byte
+        let (count, digits_len) = digit_run(&text[len + 1..]);
+        let joins = if sep == grouping { !seen_decimal && count == 3 } else { count > 0 };
+        if !joins { break; }
+        seen_decimal |= sep == decimal;
+        len += 1 + digits_len;
+    }
+    len
+}
+
+/// Number of ASCII or Khmer digits at the start of `s` and their byte length.
+fn digit_run(s: &str) -> (usize, usize) {
+    s.chars()
+        .take_while(|&c| is_digit_cp(c))
+        .fold((0, 0), |(count, len), c| (count + 1, len + c.len_utf8()))
+}
+
+/// (decimal, grouping) marks for the number at the start of `text`: those of
+/// `style`, except that under `Dot` a leading run followed by two or more
+/// `.`-separated groups of exactly three digits swaps them.
+fn number_marks(text: &str, style: DecimalStyle) -> (char, char) {
+    let dot_grouped = style == DecimalStyle::Dot && {
+        let (_, mut len) = digit_run(text);
+        let mut groups = 0;
+        loop {
+            if !text[len..].starts_with('.') { break groups >= 2; }
+            match digit_run(&text[len + 1..]) {
+                (3, digits_len) => { len += 1 + digits_len; groups += 1; }
+                (0, _) => break groups >= 2,
+                _ => break false,
+            }
+        }
+    };
+    if dot_grouped {
+        (DecimalStyle::Comma.decimal_mark(), DecimalStyle::Comma.grouping_mark())
+    } else {
+        (style.decimal_mark(), style.grouping_mark())
+    }
+}
+
+/// Byte length of a clock time at the start of `text`: `H:MM` or `HH:MM`,
+/// optionally followed by `:SS`, in ASCII or Khmer digits, with hours up to
+/// 24 and minutes/seconds below 60. Returns 0 when `text` does not start with
+/// one. A colon not followed by two digits is left out, so `10:30:` gives the
+/// length of `10:30`.
+pub fn get_time_length(text: &str) -> usize {
+    // Digit run at the start of `s`: (value, digit count, byte length). Stops
+    // after a third digit, which is already too many for any field.
+    let run = |s: &str| {
+        let (mut value, mut count, mut len) = (0, 0, 0);
+        for c in s.chars() {
+            let d = match c {
+                '0'..='9' => c as u32 - '0' as u32,
+                '\u{17E0}'..='\u{17E9}' => c as u32 - 0x17E0,
+                _ => break,
+            };
+            value = value * 10 + d;
+            count += 1;
+            len += c.len_utf8();
+            if count > 2 { break; }
+        }
+        (value, count, len)
+    };
+
+    let (hours, count, mut len) = run(text);
+    if count == 0 || count > 2 || hours > 24 { return 0; }
+
+    let mut fields = 0;
+    while fields < 2 && text[len..].starts_with(':') {
+        let (value, count, digits_len) = run(&text[len + 1..]);
+        if count != 2 || value >= 60 { break; }
+        len += 1 + digits_len;
+        fields += 1;
+    }
+    if fields == 0 { 0 } else { len }
+}
+
+/// Number length when dotted numeric sequences are split: inside a run with
+/// two or more `.` (`1.2.3`, `192.168.0.1`) every number stops at its next
+/// `.`, so each component becomes its own token. `after_dot` says whether
+/// `text` directly follows `<digit>.`, i.e. starts a later component.
+pub fn get_split_number_length(text: &str, after_dot: bool, style: DecimalStyle) -> usize {
+    let len = get_number_length_with(text, style);
+    let dots = text[..len].matches('.').count();
+    if dots >= 2 || (after_dot && dots == 1) {
+        return text[..len].find('.').unwrap();
+    }
+    len
+}
+
+/// Which of `.` and `,` is the decimal point in numbers; the other one
+/// groups thousands. It decides both where a number token ends
+/// (`get_number_length_with`) and the value `parse_number_with` reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DecimalStyle {
+    /// `10,000.50`
+    #[default]
+    Dot,
+    /// `10.000,50`, the continental European convention.
+    Comma,
+}
+
+impl DecimalStyle {
+    pub fn decimal_mark(self) -> char {
+        match self {
+            DecimalStyle::Dot => '.',
+            DecimalStyle::Comma => ',',
+        }
+    }
+
+    pub fn grouping_mark(self) -> char {
+        match self {
+            DecimalStyle::Dot => ',',
+            DecimalStyle::Comma => '.',
+        }
+    }
+}
+
+/// Value of a whole number token as matched by `get_number_length`: Khmer
+/// digits map to ASCII, `,` is a grouping separator and a single `.` is the
+/// decimal point, except in `1.000.000`-style groups where `.` groups and `,`
+/// is the decimal point. `None` if `token` is not exactly one number, has
+/// more than one decimal point, or does not fit in an `f64`.
+pub fn parse_number(token: &str) -> Option<f64> {
+    parse_number_with(token, DecimalStyle::Dot)
+}
+
+/// `parse_number` with the roles of `,` and `.` given by `style`, so
+/// `1.000` is 1000 under `DecimalStyle::Comma` and 1 under `Dot`. `token`
+/// must be one number under `get_number_length_with` for the same style.
+pub fn parse_number_with(token: &str, style: DecimalStyle) -> Option<f64> {
+    if token.is_empty() || get_number_length_with(token, style) != token.len() { return None; }
+
+    let (decimal, grouping) = number_marks(token, style);
+    let mut ascii = String::with_capacity(token.len());
+    let mut seen_decimal = false;
+    for c in token.chars() {
+        match c {
+            '0'..='9' => ascii.push(c),
+            '\u{17E0}'..='\u{17E9}' => ascii.push((b'0' + (c as u32 - 0x17E0) as u8) as char),
+            c if c == grouping && !seen_decimal => {}
+            c if c == decimal && !seen_decimal => { seen_decimal = true; ascii.push('.'); }
+            _ => return None,
+        }
+    }
+    ascii.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// What ends each cluster of an acronym by default (`ក.ខ.`).
+pub const DEFAULT_ACRONYM_TERMINATORS: &[char] = &['.'];
+
+/// Whether `text` opens with a Khmer cluster followed by one of
+/// `terminators`.
+pub fn is_acronym_start(text: &str, terminators: &[char]) -> bool {
+    let mut chars = text.chars();
+    let first = match chars.next() {
+        Some(c) => c,
+        None => return false,
+    };
+    
+    // Must start with Khmer Consonant or Independent Vowel
+    if !('\u{1780}'..='\u{17B3}').contains(&first) { return false; }
+    
+    let cluster_bytes = get_khmer_cluster_length(text);
+    if cluster_bytes == 0 { return false; }
+    
+    if let Some(c) = text[cluster_bytes..].chars().next() {
+        if terminators.contains(&c) { return true; }
+    }
+    
+    false
+}
+
+/// Byte length of the run of clusters at the start of `text` that are each
+/// followed by one of `terminators`; 0 if there is none.
+pub fn get_acronym_length(text: &str, terminators: &[char]) -> usize {
+    let mut len = 0;
+    let mut rest = text;
+    
+    loop {
+        let mut chars = rest.chars();
+        let first = match chars.next() {
+            Some(c) => c,
+            None => break,
+        };
+        
+        if !('\u{1780}'..='\u{17B3}').contains(&first) { break; }
+        
+        let cluster_bytes = get_khmer_cluster_length(rest);
+        if cluster_bytes == 0 { break; }
+        
+        if let Some(c) = rest[cluster_bytes..].chars().next() {
+            if terminators.contains(&c) {
+                let dot_len = c.len_utf8();
+                len += cluster_bytes + dot_len;
+                rest = &rest[cluster_bytes+dot_len..];
+                continue;
+            }
+        }
+        break;
+    }
+    
+    len
+}
+
+pub fn djb2_hash(str: &[u8]) -> u32 {
+    let mut hash: u32 = 5381;
+    for &c in str {
+        hash = (hash << 5).wrapping_add(hash).wrapping_add(c as u32);
+    }
+    hash
+}
+
+/// Boundary-level precision, recall and F1 between a hypothesis and a gold
+/// segmentation of the same text.
+///
+/// Both inputs are sets of byte offsets where a token boundary falls (order and
+/// duplicates don't matter). Returns `(precision, recall, f1)`.
+///
+/// Edge cases:
+/// - Both sets empty (single-token vs single-token): perfect agreement, `(1.0, 1.0, 1.0)`.
+/// - Hypothesis empty, gold not: precision is vacuously `1.0`, recall `0.0`.
+/// - Gold empty, hypothesis not: precision `0.0`, recall vacuously `1.0`.
+pub fn boundary_f1(hyp: &[usize], gold: &[usize]) -> (f32, f32, f32) {
+    let mut h = hyp.to_vec();
+    h.sort_unstable();
+    h.dedup();
+    let mut g = gold.to_vec();
+    g.sort_unstable();
+    g.dedup();
+
+    if h.is_empty() && g.is_empty() {
+        return (1.0, 1.0, 1.0);
+    }
+
+    // Both sorted: count the intersection with a merge walk
+    let (mut a, mut b, mut tp) = (0, 0, 0usize);
+    while a < h.len() && b < g.len() {
+        if h[a] == g[b] {
+            tp += 1;
+            a += 1;
+            b += 1;
+        } else if h[a] < g[b] {
+            a += 1;
+        } else {
+            b += 1;
+        }
+    }
+
+    let precision = if h.is_empty() { 1.0 } else { tp as f32 / h.len() as f32 };
+    let recall = if g.is_empty() { 1.0 } else { tp as f32 / g.len() as f32 };
+    let f1 = if precision + recall == 0.0 { 0.0 } else { 2.0 * precision * recall / (precision + recall) };
+
+    (precision, recall, f1)
+}
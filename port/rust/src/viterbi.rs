@@ -0,0 +1,213 @@
+// Generic Viterbi core over byte positions.
+//
+// The Khmer-specific candidate generation lives in `khmer_segmenter`; this
+// module only knows how to relax transitions and backtrack the cheapest path,
+// so alternate tokenizers can plug in their own `Transitions`.
+
+/// A candidate edge from some position to `next` (exclusive end, in bytes).
+#[derive(Debug, Clone, Copy)]
+pub struct Transition<K> {
+    pub next: usize,
+    pub cost: f32,
+    pub kind: K,
+}
+
+pub trait Transitions {
+    type Kind: Copy + Default;
+
+    /// Emits every candidate transition leaving `pos`. Only called for
+    /// positions already reachable from 0. Transitions past `n` are ignored.
+    fn transitions<F: FnMut(Transition<Self::Kind>)>(&self, pos: usize, emit: F);
+
+    /// Extra cost for taking a `kind` transition after `run` consecutive
+    /// transitions that also got `Some` here, for penalties that grow with a
+    /// run's length. `None` (the default) means `kind` is not part of a run
+    /// and resets the count.
+    ///
+    /// Each position keeps only its best path, so a penalized run is compared
+    /// against alternatives at the run's end, not re-decided from its start.
+    fn run_penalty(&self, _kind: Self::Kind, _run: u32) -> Option<f32> {
+        None
+    }
+}
+
+impl<T: Transitions> Transitions for &T {
+    type Kind = T::Kind;
+
+    fn transitions<F: FnMut(Transition<Self::Kind>)>(&self, pos: usize, emit: F) {
+        (**self).transitions(pos, emit)
+    }
+
+    fn run_penalty(&self, kind: Self::Kind, run: u32) -> Option<f32> {
+        (**self).run_penalty(kind, run)
+    }
+}
+
+/// Best known way to reach a position: accumulated cost, the position it was
+/// reached from (-1 if unreached), the kind of the final transition, the
+/// number of tokens on the path and how many of the last ones formed a run
+/// (see `Transitions::run_penalty`).
+#[derive(Debug, Clone, Copy)]
+pub struct State<K> {
+    pub cost: f32,
+    pub prev_idx: isize,
+    pub kind: K,
+    pub tokens: u32,
+    pub run: u32,
+}
+
+impl<K> State<K> {
+    /// Strict preference order used when relaxing a cell, so ties never depend
+    /// on the order transitions are emitted in:
+    /// 1. lower cost;
+    /// 2. on equal cost, fewer tokens (longer tokens overall);
+    /// 3. then the longer final token (smaller `prev_idx`).
+    ///
+    /// Only transitions over the exact same span with the same cost remain
+    /// tied; the first one emitted is kept.
+    pub fn beats(&self, other: &Self) -> bool {
+        if self.cost != other.cost { return self.cost < other.cost; }
+        if self.tokens != other.tokens { return self.tokens < other.tokens; }
+        self.prev_idx < other.prev_idx
+    }
+}
+
+pub const UNREACHED: f32 = 1e9;
+
+/// Cheapest segmentation of `0..n` as `(start, end)` spans. Returns an empty
+/// vector when `n` is 0 or unreachable.
+pub fn viterbi<T: Transitions>(transitions: T, n: usize) -> Vec<(usize, usize)> {
+    let mut dp = Vec::new();
+    let mut out = Vec::new();
+    viterbi_into(&transitions, n, &mut dp, &mut out);
+    out
+}
+
+/// Buffer-reusing form of `viterbi`. Leaves the filled table in `dp` so callers
+/// can inspect costs and kinds, and returns whether `n` was reached.
+pub fn viterbi_into<T: Transitions>(
+    transitions: &T,
+    n: usize,
+    dp: &mut Vec<State<T::Kind>>,
+    out: &mut Vec<(usize, usize)>,
+) -> bool {
+    out.clear();
+    dp.clear();
+    dp.resize(n + 1, State { cost: UNREACHED, prev_idx: -1, kind: T::Kind::default(), tokens: 0, run: 0 });
+    dp[0].cost = 0.0;
+
+    for i in 0..n {
+        // Skip unreachable (this also skips positions inside a UTF-8 sequence)
+        if dp[i].cost >= UNREACHED { continue; }
+
+        let (base, tokens, run) = (dp[i].cost, dp[i].tokens, dp[i].run);
+        transitions.transitions(i, |t| {
+            if t.next > n { return; }
+            let (extra, run) = match transitions.run_penalty(t.kind, run) {
+                Some(extra) => (extra, run + 1),
+                None => (0.0, 0),
+            };
+            let cand = State { cost: base + t.cost + extra, prev_idx: i as isize, kind: t.kind, tokens: tokens + 1, run };
+            if cand.beats(&dp[t.next]) {
+                dp[t.next] = cand;
+            }
+        });
+    }
+
+    // Backtrack
+    if n == 0 || dp[n].prev_idx == -1 {
+        return false;
+    }
+
+    let mut curr = n;
+    while curr > 0 {
+        let prev = dp[curr].prev_idx as usize;
+        out.push((prev, curr));
+        curr = prev;
+    }
+    out.reverse();
+    true
+}
+
+/// One of the best ways to reach a position in `viterbi_nbest`: a `State`
+/// plus the rank, within its predecessor's candidates, of the path it extends.
+#[derive(Debug, Clone, Copy)]
+struct Candidate<K> {
+    state: State<K>,
+    prev_rank: usize,
+}
+
+impl<K> Candidate<K> {
+    // `State::beats`, with remaining ties going to the better-ranked
+    // predecessor path.
+    fn beats(&self, other: &Self) -> bool {
+        if self.state.beats(&other.state) { return true; }
+        if other.state.beats(&self.state) { return false; }
+        self.prev_rank < other.prev_rank
+    }
+}
+
+// Keeps `cell` as the best `k` candidates in preference order. A second
+// transition over the same span from the same path (another kind) would
+// yield the same spans, so only the better of the two is kept.
+fn offer<K>(cell: &mut Vec<Candidate<K>>, cand: Candidate<K>, k: usize) {
+    if let Some(pos) = cell.iter().position(|c| c.state.prev_idx == cand.state.prev_idx && c.prev_rank == cand.prev_rank) {
+        if !cand.beats(&cell[pos]) { return; }
+        cell.remove(pos);
+    }
+    let at = cell.iter().position(|c| cand.beats(c)).unwrap_or(cell.len());
+    if at < k {
+        cell.insert(at, cand);
+        cell.truncate(k);
+    }
+}
+
+/// The `k` cheapest segmentations of `0..n`, cheapest first, each with its
+/// total cost. Every position keeps its `k` best candidates, ordered as
+/// `State::beats` orders them and then by the rank of the path they extend,
+/// so the result does not depend on emission order beyond what `viterbi`
+/// already allows; with `k == 1` it is `viterbi`'s path. Fewer than `k` come
+/// back when there are not that many distinct paths; none when `n` is 0 or
+/// unreachable.
+pub fn viterbi_nbest<T: Transitions>(transitions: T, n: usize, k: usize) -> Vec<(f32, Vec<(usize, usize)>)> {
+    if n == 0 || k == 0 { return Vec::new(); }
+    let start = State { cost: 0.0, prev_idx: -1, kind: T::Kind::default(), tokens: 0, run: 0 };
+    let mut cells: Vec<Vec<Candidate<T::Kind>>> = vec![Vec::new(); n + 1];
+    cells[0].push(Candidate { state: start, prev_rank: 0 });
+
+    let mut edges = Vec::new();
+    for i in 0..n {
+        if cells[i].is_empty() { continue; }
+        edges.clear();
+        transitions.transitions(i, |t| if t.next <= n { edges.push(t) });
+
+        let (done, rest) = cells.split_at_mut(i + 1);
+        for (rank, from) in done[i].iter().enumerate() {
+            let (base, tokens, run) = (from.state.cost, from.state.tokens, from.state.run);
+            for t in &edges {
+                let (extra, run) = match transitions.run_penalty(t.kind, run) {
+                    Some(extra) => (extra, run + 1),
+                    None => (0.0, 0),
+                };
+                let state = State { cost: base + t.cost + extra, prev_idx: i as isize, kind: t.kind, tokens: tokens + 1, run };
+                offer(&mut rest[t.next - i - 1], Candidate { state, prev_rank: rank }, k);
+            }
+        }
+    }
+
+    cells[n]
+        .iter()
+        .map(|last| {
+            let mut path = Vec::with_capacity(last.state.tokens as usize);
+            let (mut curr, mut cand) = (n, last);
+            while curr > 0 {
+                let prev = cand.state.prev_idx as usize;
+                path.push((prev, curr));
+                cand = &cells[prev][cand.prev_rank];
+                curr = prev;
+            }
+            path.reverse();
+            (last.state.cost, path)
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+// Token-to-ID vocabulary for `KhmerSegmenter::segment_ids`.
+
+use std::collections::HashMap;
+
+use crate::kdict::KDict;
+
+/// Maps token text to dense `u32` IDs for model input. IDs 0-2 are reserved
+/// for the special tokens; entries added afterwards are numbered in order.
+#[derive(Debug, Clone)]
+pub struct Vocab {
+    ids: HashMap<String, u32>,
+    tokens: Vec<String>,
+}
+
+impl Vocab {
+    /// Out-of-vocabulary and unknown-cluster tokens.
+    pub const UNK: u32 = 0;
+    /// Number and clock-time tokens.
+    pub const NUMBER: u32 = 1;
+    /// Dotted acronym tokens.
+    pub const ACRONYM: u32 = 2;
+
+    const SPECIALS: [&'static str; 3] = ["<unk>", "<num>", "<acronym>"];
+
+    /// A vocabulary holding only the special tokens.
+    pub fn new() -> Self {
+        let mut vocab = Self { ids: HashMap::new(), tokens: Vec::new() };
+        for special in Self::SPECIALS {
+            vocab.insert(special);
+        }
+        vocab
+    }
+
+    /// The special tokens plus every dictionary word, in sorted order so the
+    /// IDs do not depend on the hash table layout. Words that are not valid
+    /// UTF-8 are skipped.
+    pub fn from_dict(kdict: &KDict) -> Self {
+        let mut words: Vec<&str> = kdict.words().filter_map(|(bytes, _)| std::str::from_utf8(bytes).ok()).collect();
+        words.sort_unstable();
+        let mut vocab = Self::new();
+        for word in words {
+            vocab.insert(word);
+        }
+        vocab
+    }
+
+    /// ID of `token`, adding it with the next free ID if it is new.
+    pub fn insert(&mut self, token: &str) -> u32 {
+        if let Some(&id) = self.ids.get(token) {
+            return id;
+        }
+        let id = self.tokens.len() as u32;
+        self.ids.insert(token.to_string(), id);
+        self.tokens.push(token.to_string());
+        id
+    }
+
+    #[must_use]
+    pub fn get(&self, token: &str) -> Option<u32> {
+        self.ids.get(token).copied()
+    }
+
+    /// Text of `id` (the special tokens read `<unk>`, `<num>`, `<acronym>`).
+    #[must_use]
+    pub fn token(&self, id: u32) -> Option<&str> {
+        self.tokens.get(id as usize).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+impl Default for Vocab {
+    fn default() -> Self {
+        Self::new()
+    }
+}
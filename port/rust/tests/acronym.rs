@@ -0,0 +1,93 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+
+const WORDS: &[(&str, f32)] = &[("បាន", 3.0)];
+
+#[test]
+fn acronym_is_single_token_when_enabled() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ស.ភ.ភ.ព. បាន"), ["ស.ភ.ភ.ព.", " ", "បាន"]);
+}
+
+#[test]
+fn acronym_splits_on_every_dot_when_disabled() {
+    let config = SegmenterConfig { enable_acronym_detection: false, ..Default::default() };
+    let seg = segmenter(WORDS, config);
+    assert_eq!(
+        tokens(&seg, "ស.ភ.ភ.ព. បាន"),
+        ["ស", ".", "ភ", ".", "ភ", ".", "ព", ".", " ", "បាន"]
+    );
+}
+
+#[test]
+fn disabled_acronym_does_not_glue_unknown_clusters_across_dots() {
+    // Multi-character clusters are unknown, but the dots between them are
+    // separators, so unknown-merging must not weld them back together.
+    let config = SegmenterConfig { enable_acronym_detection: false, ..Default::default() };
+    let seg = segmenter(WORDS, config);
+    assert_eq!(tokens(&seg, "ស៊ី.ភី."), ["ស៊ី", ".", "ភី", "."]);
+}
+
+#[test]
+fn single_cluster_acronym_can_outbid_word_before_full_stop() {
+    let words = &[("ទៅ", 12.0)];
+    let seg = segmenter(words, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ទៅ."), ["ទៅ."]);
+}
+
+#[test]
+fn min_acronym_clusters_keeps_word_before_full_stop() {
+    let words = &[("ទៅ", 12.0)];
+    let config = SegmenterConfig { min_acronym_clusters: 2, ..Default::default() };
+    let seg = segmenter(words, config);
+    assert_eq!(tokens(&seg, "ទៅ."), ["ទៅ", "."]);
+    assert_eq!(tokens(&seg, "ស.ភ.ភ.ព. ទៅ."), ["ស.ភ.ភ.ព.", " ", "ទៅ", "."]);
+}
+
+#[test]
+fn middle_dot_is_not_a_terminator_by_default() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ស·ភ·ព·")[..2], ["ស", "·"]);
+}
+
+#[test]
+fn middle_dot_acronym_with_extended_terminators() {
+    let config = SegmenterConfig { acronym_terminators: vec!['.', '\u{00B7}'], ..Default::default() };
+    let seg = segmenter(WORDS, config);
+    assert_eq!(tokens(&seg, "ស·ភ·ព· បាន"), ["ស·ភ·ព·", " ", "បាន"]);
+    assert_eq!(tokens(&seg, "ស.ភ.ភ.ព. បាន"), ["ស.ភ.ភ.ព.", " ", "បាន"]);
+    // Terminators may be mixed within one acronym
+    assert_eq!(tokens(&seg, "ស.ភ·ព."), ["ស.ភ·ព."]);
+}
+
+#[test]
+fn min_acronym_clusters_counts_every_terminator() {
+    let config = SegmenterConfig {
+        acronym_terminators: vec!['\u{00B7}'],
+        min_acronym_clusters: 3,
+        ..Default::default()
+    };
+    let seg = segmenter(WORDS, config);
+    assert_eq!(tokens(&seg, "ស·ភ·ព·"), ["ស·ភ·ព·"]);
+    assert_eq!(tokens(&seg, "ស·ភ·")[..2], ["ស", "·"]);
+}
+
+#[test]
+fn long_dotted_chain_is_one_token_without_a_cap() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ក.ខ.គ.ឃ.ង.ច.ឆ.ជ."), ["ក.ខ.គ.ឃ.ង.ច.ឆ.ជ."]);
+}
+
+#[test]
+fn max_acronym_clusters_stops_runaway_chains() {
+    let config = SegmenterConfig { max_acronym_clusters: 4, ..Default::default() };
+    let seg = segmenter(WORDS, config);
+    assert_eq!(
+        tokens(&seg, "ក.ខ.គ.ឃ.ង.ច.ឆ.ជ."),
+        ["ក", ".", "ខ", ".", "គ", ".", "ឃ", ".", "ង", ".", "ច", ".", "ឆ", ".", "ជ", "."]
+    );
+    // Short acronyms are unaffected
+    assert_eq!(tokens(&seg, "ស.ភ.ភ.ព. បាន"), ["ស.ភ.ភ.ព.", " ", "បាន"]);
+}
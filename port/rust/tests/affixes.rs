@@ -0,0 +1,68 @@
+mod common;
+
+use common::tokens;
+use khmer_segmenter::kdict::build::KDictBuilder;
+use khmer_segmenter::kdict::{KDict, FLAG_PREFIX, FLAG_STANDALONE, FLAG_SUFFIX};
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+
+fn dict_with_flags(flagged: bool) -> Vec<u8> {
+    let mut builder = KDictBuilder::new(10.0, 15.0);
+    let (prefix, suffix) = if flagged { (FLAG_PREFIX | FLAG_STANDALONE, FLAG_SUFFIX) } else { (0, 0) };
+    builder.insert_with_flags("អ្នក", 3.0, 0, prefix);
+    builder.insert_with_flags("និយម", 3.0, 0, suffix);
+    builder.insert("លក់", 3.0);
+    builder.insert("សង្គម", 3.0);
+    builder.build()
+}
+
+fn segmenter(flagged: bool) -> KhmerSegmenter {
+    let config = SegmenterConfig { enable_unknown_merging: false, ..Default::default() };
+    KhmerSegmenter::new_with_dict(Some(KDict::from_bytes(dict_with_flags(flagged)).unwrap()), config)
+}
+
+#[test]
+fn flags_need_version_three() {
+    assert_eq!(dict_with_flags(true)[4], 3);
+    assert_eq!(dict_with_flags(false)[4], 1);
+
+    let dict = KDict::from_bytes(dict_with_flags(true)).unwrap();
+    assert!(dict.has_flags());
+    assert_eq!(dict.flags(dict.lookup("និយម".as_bytes()).unwrap()), FLAG_SUFFIX);
+    assert_eq!(dict.flags(dict.lookup("លក់".as_bytes()).unwrap()), 0);
+
+    let rebuilt = KDict::from_bytes(KDictBuilder::from_dict(&dict).build()).unwrap();
+    assert_eq!(rebuilt.flags(rebuilt.lookup("អ្នក".as_bytes()).unwrap()), FLAG_PREFIX | FLAG_STANDALONE);
+}
+
+#[test]
+fn prefix_and_suffix_entries_attach() {
+    let seg = segmenter(true);
+    assert_eq!(tokens(&seg, "អ្នកលក់"), ["អ្នកលក់"]);
+    assert_eq!(tokens(&seg, "សង្គមនិយម"), ["សង្គមនិយម"]);
+    // Never across a separator
+    assert_eq!(tokens(&seg, "អ្នក លក់"), ["អ្នក", " ", "លក់"]);
+    assert_eq!(tokens(&seg, "សង្គម និយម"), ["សង្គម", " ", "និយម"]);
+}
+
+#[test]
+fn standalone_affix_only_joins_known_words() {
+    let seg = segmenter(true);
+    assert_eq!(tokens(&seg, "អ្នកគ"), ["អ្នក", "គ"]);
+    // The suffix is not standalone, so it takes any token
+    assert_eq!(tokens(&seg, "គនិយម"), ["គនិយម"]);
+}
+
+#[test]
+fn unflagged_dictionary_is_unaffected() {
+    let seg = segmenter(false);
+    assert_eq!(tokens(&seg, "អ្នកលក់"), ["អ្នក", "លក់"]);
+    assert_eq!(tokens(&seg, "សង្គមនិយម"), ["សង្គម", "និយម"]);
+}
+
+#[test]
+fn detailed_tokens_expose_flags() {
+    let seg = segmenter(true);
+    let detailed = seg.segment_detailed("និយម លក់");
+    let flags: Vec<_> = detailed.iter().map(|t| t.flags).collect();
+    assert_eq!(flags, [Some(FLAG_SUFFIX), None, None]);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::segmenter;
+use khmer_segmenter::khmer_segmenter::{BatchStats, SegmenterConfig, TokenKind};
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)];
+
+#[test]
+fn batch_stats_count_kinds_across_lines() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let lines = ["ខ្ញុំទៅ", "ខ្ញុំ ១២", "ឆ្កែ", ""];
+    let (outputs, stats) = seg.segment_batch_stats(&lines, Some("|"));
+
+    let expected: Vec<String> = lines.iter().map(|l| seg.segment(l, Some("|"))).collect();
+    assert_eq!(outputs, expected);
+
+    assert_eq!(stats.lines, 4);
+    assert_eq!(stats.tokens, 6);
+    assert_eq!(stats.count(TokenKind::Word), 3);
+    assert_eq!(stats.count(TokenKind::Separator), 1);
+    assert_eq!(stats.count(TokenKind::Number), 1);
+    assert_eq!(stats.count(TokenKind::Unknown), 1);
+    assert_eq!(stats.chars, 5 + 2 + 5 + 1 + 2 + 4);
+    assert_eq!(stats.unknown_chars, 4);
+    assert!((stats.unknown_ratio() - 4.0 / 19.0).abs() < 1e-9);
+}
+
+#[test]
+fn batch_stats_merge_with_add_assign() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let (_, a) = seg.segment_batch_stats(&["ខ្ញុំទៅ"], None);
+    let (_, b) = seg.segment_batch_stats(&["ឆ្កែ"], None);
+    let (_, both) = seg.segment_batch_stats(&["ខ្ញុំទៅ", "ឆ្កែ"], None);
+
+    let mut merged = BatchStats::default();
+    merged += a;
+    merged += b;
+    assert_eq!(merged, both);
+    assert_eq!(BatchStats::default().unknown_ratio(), 0.0);
+}
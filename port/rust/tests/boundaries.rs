@@ -0,0 +1,43 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::{SegmenterConfig, SeparatorPolicy, TokenKind};
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0), ("ខ្ញុំទៅ", 2.0)];
+const RS: char = '\u{1E}';
+
+fn forcing(chars: &[char]) -> SegmenterConfig {
+    SegmenterConfig { force_boundary_chars: chars.iter().copied().collect(), ..Default::default() }
+}
+
+#[test]
+fn custom_delimiter_always_stands_alone() {
+    let seg = segmenter(WORDS, forcing(&[RS]));
+    assert_eq!(tokens(&seg, "ខ្ញុំទៅ\u{1E}ទៅ\u{1E}\u{1E}ខ្ញុំ"), ["ខ្ញុំទៅ", "\u{1E}", "ទៅ", "\u{1E}", "\u{1E}", "ខ្ញុំ"]);
+    let kinds: Vec<_> = seg.segment_detailed("ទៅ\u{1E}ទៅ").into_iter().map(|t| t.kind).collect();
+    assert_eq!(kinds, [TokenKind::Word, TokenKind::Separator, TokenKind::Word]);
+}
+
+#[test]
+fn no_token_spans_a_forced_boundary() {
+    // A dictionary word is cut in two by a delimiter inside it
+    let words = &[("ទៅ\u{1E}ទៅ", 1.0), ("ទៅ", 3.0)];
+    assert_eq!(tokens(&segmenter(words, SegmenterConfig::default()), "ទៅ\u{1E}ទៅ"), ["ទៅ\u{1E}ទៅ"]);
+    assert_eq!(tokens(&segmenter(words, forcing(&[RS])), "ទៅ\u{1E}ទៅ"), ["ទៅ", "\u{1E}", "ទៅ"]);
+
+    // Unknown runs, Latin runs and unknown merging stop at it too, even
+    // when the merge policy would allow crossing it
+    let config = SegmenterConfig { merge_unknowns_across: SeparatorPolicy::Allow(vec!['x']), ..forcing(&['x']) };
+    let seg = segmenter(WORDS, config);
+    assert_eq!(tokens(&seg, "abxcd"), ["ab", "x", "cd"]);
+    assert_eq!(tokens(&seg, "ឆ្កែxឆ្មា"), ["ឆ្កែ", "x", "ឆ្មា"]);
+}
+
+#[test]
+fn rules_do_not_merge_into_a_forced_boundary() {
+    // Rule 4 would fold the lone ន into the token before it
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ទៅ\u{1E}ន"), ["ទៅ", "\u{1E}ន"]);
+    let seg = segmenter(WORDS, forcing(&[RS]));
+    assert_eq!(tokens(&seg, "ទៅ\u{1E}ន"), ["ទៅ", "\u{1E}", "ន"]);
+}
@@ -0,0 +1,32 @@
+use khmer_segmenter::utils::boundary_f1;
+
+fn assert_close((p, r, f): (f32, f32, f32), expected: (f32, f32, f32)) {
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+    assert!(close(p, expected.0) && close(r, expected.1) && close(f, expected.2), "got {:?}, expected {:?}", (p, r, f), expected);
+}
+
+#[test]
+fn both_empty_is_perfect_agreement() {
+    assert_close(boundary_f1(&[], &[]), (1.0, 1.0, 1.0));
+}
+
+#[test]
+fn empty_hypothesis_has_vacuous_precision() {
+    assert_close(boundary_f1(&[], &[3, 6]), (1.0, 0.0, 0.0));
+}
+
+#[test]
+fn empty_gold_has_vacuous_recall() {
+    assert_close(boundary_f1(&[3, 6], &[]), (0.0, 1.0, 0.0));
+}
+
+#[test]
+fn partial_overlap() {
+    // 2 of 4 hypothesized boundaries are right, 2 of 3 gold ones are found
+    let (p, r) = (2.0 / 4.0, 2.0 / 3.0);
+    assert_close(boundary_f1(&[3, 6, 9, 12], &[3, 9, 15]), (p, r, 2.0 * p * r / (p + r)));
+    // Order and duplicates do not matter
+    assert_close(boundary_f1(&[12, 9, 9, 3, 6], &[15, 3, 9, 3]), (p, r, 2.0 * p * r / (p + r)));
+    assert_close(boundary_f1(&[3, 9], &[9, 3]), (1.0, 1.0, 1.0));
+    assert_close(boundary_f1(&[1], &[2]), (0.0, 0.0, 0.0));
+}
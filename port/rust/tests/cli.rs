@@ -0,0 +1,207 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use common::dict_bytes;
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0), ("ផ្ទះ", 3.0)];
+
+// A scratch working directory named after the test, holding the
+// `khmer_dictionary.kdict` the CLI looks for first.
+fn workdir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("khmer_segmenter_cli_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("khmer_dictionary.kdict"), dict_bytes(WORDS)).unwrap();
+    dir
+}
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_khmer_segmenter")).current_dir(dir).args(args).output().unwrap()
+}
+
+fn stdout(out: &Output) -> String {
+    String::from_utf8(out.stdout.clone()).unwrap()
+}
+
+fn stderr(out: &Output) -> String {
+    String::from_utf8(out.stderr.clone()).unwrap()
+}
+
+#[test]
+fn attach_punct_accepts_its_values() {
+    let dir = workdir("attach_punct");
+    let out = run(&dir, &["--attach-punct", "left", "--output", "-", "ខ្ញុំទៅ។ផ្ទះ"]);
+    assert!(out.status.success());
+    assert!(stdout(&out).contains("Segmented: ខ្ញុំ | ទៅ។ | ផ្ទះ\n"));
+
+    let out = run(&dir, &["--attach-punct", "none", "--output", "-", "ខ្ញុំទៅ។ផ្ទះ"]);
+    assert!(stdout(&out).contains("Segmented: ខ្ញុំ | ទៅ | ។ | ផ្ទះ\n"));
+}
+
+#[test]
+fn attach_punct_rejects_unknown_values() {
+    let dir = workdir("attach_punct_unknown");
+    for args in [&["--attach-punct", "lefty", "ខ្ញុំ"][..], &["--attach-punct"][..]] {
+        let out = run(&dir, args);
+        assert_eq!(out.status.code(), Some(2));
+        assert!(stderr(&out).contains("--attach-punct expects left, right or none"));
+        assert!(!dir.join("segmentation_results.txt").exists());
+    }
+}
+
+#[test]
+fn input_takes_every_path_up_to_the_next_flag() {
+    let dir = workdir("input_paths");
+    std::fs::write(dir.join("a.txt"), "ខ្ញុំទៅ\n").unwrap();
+    std::fs::write(dir.join("b.txt"), "ផ្ទះ\n").unwrap();
+
+    let out = run(&dir, &["--input", "a.txt", "b.txt", "--output", "-"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(stdout(&out).matches("Segmented: ").count(), 2);
+    assert!(stdout(&out).contains("Segmented: ខ្ញុំ | ទៅ\n"));
+    assert!(stdout(&out).contains("Segmented: ផ្ទះ\n"));
+    assert!(!stderr(&out).contains("DEBUG"));
+}
+
+#[test]
+fn misspelled_second_input_is_an_error() {
+    let dir = workdir("input_typo");
+    std::fs::write(dir.join("a.txt"), "ខ្ញុំទៅ\n").unwrap();
+    let out = run(&dir, &["--input", "a.txt", "typo.txt"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr(&out).contains("input file not found: typo.txt"));
+    assert!(!dir.join("segmentation_results.txt").exists());
+}
+
+#[test]
+fn text_after_double_dash_is_never_an_input() {
+    let dir = workdir("input_dashdash");
+    std::fs::write(dir.join("a.txt"), "ខ្ញុំទៅ\n").unwrap();
+    let out = run(&dir, &["--input", "a.txt", "--output", "-", "--", "some", "text"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Segmented: ខ្ញុំ | ទៅ\n"));
+    assert!(stderr(&out).contains("positional text is ignored"));
+}
+
+#[test]
+fn missing_input_file_is_an_error() {
+    let dir = workdir("input_missing");
+    let out = run(&dir, &["--input", "missing.txt"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr(&out).contains("input file not found: missing.txt"));
+    assert!(!dir.join("segmentation_results.txt").exists());
+}
+
+#[test]
+fn template_formats_every_record() {
+    let dir = workdir("template");
+    std::fs::write(dir.join("in.txt"), "ខ្ញុំទៅ\nផ្ទះ\n").unwrap();
+    let out = run(&dir, &["--input", "in.txt", "--template", "{original}\\t{segmented}\\n", "--output", "-"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(stdout(&out), "ខ្ញុំទៅ\tខ្ញុំ | ទៅ\nផ្ទះ\tផ្ទះ\n");
+
+    // Placeholder-looking input is copied, not expanded again
+    std::fs::write(dir.join("braces.txt"), "{segmented}\n").unwrap();
+    let out = run(&dir, &["--input", "braces.txt", "--template", "[{original}]\\n", "--output", "-"]);
+    assert_eq!(stdout(&out), "[{segmented}]\n");
+}
+
+#[test]
+fn every_mode_writes_the_same_default_output_file() {
+    let dir = workdir("default_output");
+    let results = dir.join("segmentation_results.txt");
+    std::fs::write(dir.join("in.txt"), "ផ្ទះ\n").unwrap();
+
+    let out = run(&dir, &["ខ្ញុំទៅ"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(std::fs::read_to_string(&results).unwrap().contains("Segmented: ខ្ញុំ | ទៅ\n"));
+    std::fs::remove_file(&results).unwrap();
+
+    let out = run(&dir, &["--input", "in.txt"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(std::fs::read_to_string(&results).unwrap().contains("Segmented: ផ្ទះ\n"));
+    assert!(stderr(&out).contains("Results saved to segmentation_results.txt"));
+    assert!(!dir.join("benchmark_results.txt").exists());
+}
+
+#[test]
+fn output_dash_writes_records_to_stdout() {
+    let dir = workdir("output_stdout");
+    std::fs::write(dir.join("in.txt"), "ផ្ទះ\n").unwrap();
+    let out = run(&dir, &["--input", "in.txt", "--output", "-"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(stdout(&out), "Original:  ផ្ទះ\nSegmented: ផ្ទះ\n----------------------------------------\n");
+    assert!(!stderr(&out).contains("Results saved"));
+    assert!(!dir.join("segmentation_results.txt").exists());
+    assert!(!dir.join("-").exists());
+}
+
+#[test]
+fn segmented_only_writes_one_line_per_input_line() {
+    let dir = workdir("segmented_only");
+    // Lines that look like the verbose record format must come through as is
+    std::fs::write(dir.join("in.txt"), "ខ្ញុំទៅ\nOriginal: ផ្ទះ\n----\n").unwrap();
+    let out = run(&dir, &["--input", "in.txt", "--segmented-only", "--output", "-"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(stdout(&out), "ខ្ញុំ | ទៅ\nOriginal | : |   | ផ្ទះ\n- | - | - | -\n");
+}
+
+fn run_debug_dict(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_debug_dict")).current_dir(dir).args(args).output().unwrap()
+}
+
+#[test]
+fn debug_dict_reports_a_missing_dictionary() {
+    let dir = workdir("debug_dict_missing");
+    let out = run_debug_dict(&dir, &["missing.kdict"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr(&out).starts_with("Error: cannot load missing.kdict: "), "{}", stderr(&out));
+}
+
+#[test]
+fn debug_dict_build_names_the_bad_line() {
+    let dir = workdir("debug_dict_build");
+    std::fs::write(dir.join("words.tsv"), "ខ្ញុំ\t3\nទៅ 3\n").unwrap();
+    let out = run_debug_dict(&dir, &["--build", "words.tsv", "out.kdict", "6", "11"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr(&out).starts_with("Error: words.tsv:2: "), "{}", stderr(&out));
+
+    let out = run_debug_dict(&dir, &["--build", "nope.tsv", "out.kdict", "6", "11"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr(&out).starts_with("Error: cannot read nope.tsv: "), "{}", stderr(&out));
+
+    let out = run_debug_dict(&dir, &["--build", "words.tsv", "out.kdict", "six", "11"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(!dir.join("out.kdict").exists());
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip_input_is_decompressed() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let gzip = |text: &str| {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        std::io::Write::write_all(&mut encoder, text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    };
+    let dir = workdir("gzip_input");
+    // Two concatenated members, and a name without `.gz` found by its magic bytes
+    std::fs::write(dir.join("in.txt.gz"), [gzip("\u{FEFF}ខ្ញុំទៅ\n"), gzip("ផ្ទះ\n")].concat()).unwrap();
+    std::fs::write(dir.join("in.dat"), gzip("ទៅផ្ទះ\n")).unwrap();
+    let out = run(&dir, &["--input", "in.txt.gz", "in.dat", "--segmented-only", "--output", "-"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(stdout(&out), "ខ្ញុំ | ទៅ\nផ្ទះ\nទៅ | ផ្ទះ\n");
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn gzip_input_needs_the_feature() {
+    let dir = workdir("gzip_unsupported");
+    std::fs::write(dir.join("in.gz"), [0x1F, 0x8B, 0x08, 0x00]).unwrap();
+    let out = run(&dir, &["--input", "in.gz", "--output", "-"]);
+    assert!(stderr(&out).contains("Warning: cannot read in.gz: file is gzip-compressed; rebuild with --features gzip"), "{}", stderr(&out));
+    assert_eq!(stdout(&out), "");
+}
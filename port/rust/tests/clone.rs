@@ -0,0 +1,41 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{dict, segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)];
+
+#[test]
+fn clone_shares_the_dictionary() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let copy = seg.clone();
+    assert!(std::ptr::eq(seg.dictionary().unwrap(), copy.dictionary().unwrap()));
+    assert_eq!(tokens(&copy, "ខ្ញុំទៅ"), tokens(&seg, "ខ្ញុំទៅ"));
+}
+
+#[test]
+fn with_config_changes_only_the_config() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let split = seg.with_config(SegmenterConfig { enable_acronym_detection: false, ..Default::default() });
+    assert!(std::ptr::eq(seg.dictionary().unwrap(), split.dictionary().unwrap()));
+    assert_eq!(tokens(&seg, "ក.ខ."), ["ក.ខ."]);
+    assert_eq!(tokens(&split, "ក.ខ."), ["ក", ".", "ខ", "."]);
+}
+
+#[test]
+fn shared_dict_fans_out_to_threads() {
+    let shared = Arc::new(dict(WORDS));
+    let handles: Vec<_> = [true, false]
+        .into_iter()
+        .map(|acronyms| {
+            let config = SegmenterConfig { enable_acronym_detection: acronyms, ..Default::default() };
+            let seg = KhmerSegmenter::new_with_shared_dict(Some(Arc::clone(&shared)), config);
+            std::thread::spawn(move || seg.segment("ខ្ញុំក.ខ.", Some("|")))
+        })
+        .collect();
+    let results: Vec<String> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(results, ["ខ្ញុំ|ក.ខ.", "ខ្ញុំ|ក|.|ខ|."]);
+    assert_eq!(Arc::strong_count(&shared), 1);
+}
@@ -0,0 +1,28 @@
+#![allow(dead_code)]
+
+use khmer_segmenter::kdict::build::KDictBuilder;
+use khmer_segmenter::kdict::KDict;
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+
+pub const DEFAULT_COST: f32 = 10.0;
+pub const UNKNOWN_COST: f32 = 15.0;
+
+pub fn dict_bytes(words: &[(&str, f32)]) -> Vec<u8> {
+    let mut builder = KDictBuilder::new(DEFAULT_COST, UNKNOWN_COST);
+    for &(word, cost) in words {
+        builder.insert(word, cost);
+    }
+    builder.build()
+}
+
+pub fn dict(words: &[(&str, f32)]) -> KDict {
+    KDict::from_bytes(dict_bytes(words)).unwrap()
+}
+
+pub fn segmenter(words: &[(&str, f32)], config: SegmenterConfig) -> KhmerSegmenter {
+    KhmerSegmenter::new_with_dict(Some(dict(words)), config)
+}
+
+pub fn tokens(seg: &KhmerSegmenter, text: &str) -> Vec<String> {
+    seg.segment(text, Some("|")).split('|').map(str::to_string).collect()
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+
+const WORDS: &[(&str, f32)] = &[("ផ្ទះ", 3.0), ("បាយ", 3.0), ("ថ្មី", 3.0), ("ទៅ", 3.0)];
+
+fn with_pairs(pairs: &[(&str, &str)]) -> SegmenterConfig {
+    let compound_pairs = pairs.iter().map(|&(a, b)| (a.to_string(), b.to_string())).collect();
+    SegmenterConfig { compound_pairs, ..Default::default() }
+}
+
+#[test]
+fn listed_pair_merges_into_one_token() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ផ្ទះបាយ"), ["ផ្ទះ", "បាយ"]);
+
+    let seg = segmenter(WORDS, with_pairs(&[("ផ្ទះ", "បាយ")]));
+    assert_eq!(tokens(&seg, "ទៅផ្ទះបាយ"), ["ទៅ", "ផ្ទះបាយ"]);
+    // Order matters
+    assert_eq!(tokens(&seg, "បាយផ្ទះ"), ["បាយ", "ផ្ទះ"]);
+}
+
+#[test]
+fn pairs_chain_across_three_words() {
+    let seg = segmenter(WORDS, with_pairs(&[("ផ្ទះ", "បាយ"), ("បាយ", "ថ្មី")]));
+    assert_eq!(tokens(&seg, "ផ្ទះបាយថ្មីទៅ"), ["ផ្ទះបាយថ្មី", "ទៅ"]);
+}
+
+#[test]
+fn never_merges_across_a_separator() {
+    let seg = segmenter(WORDS, with_pairs(&[("ផ្ទះ", "បាយ")]));
+    assert_eq!(tokens(&seg, "ផ្ទះ បាយ"), ["ផ្ទះ", " ", "បាយ"]);
+    assert_eq!(tokens(&seg, "ផ្ទះ។បាយ"), ["ផ្ទះ", "។", "បាយ"]);
+}
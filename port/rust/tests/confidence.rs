@@ -0,0 +1,42 @@
+mod common;
+
+use common::segmenter;
+use khmer_segmenter::khmer_segmenter::{ConfidenceMapping, SegmenterConfig};
+
+const WORDS: &[(&str, f32)] = &[("បាន", 3.0)];
+
+fn scores(config: SegmenterConfig, text: &str) -> Vec<(String, f32)> {
+    segmenter(WORDS, config).segment_confidence(text)
+}
+
+fn approx(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-5
+}
+
+#[test]
+fn words_score_high_and_unknowns_low() {
+    let out = scores(SegmenterConfig::default(), "បានក្ស ១២");
+    let texts: Vec<&str> = out.iter().map(|(t, _)| t.as_str()).collect();
+    assert_eq!(texts, ["បាន", "ក្ស", " ", "១២"]);
+    // 1 - 3 / 15 with the test dictionary's unknown cost of 15
+    assert!(approx(out[0].1, 0.8));
+    assert!(approx(out[1].1, 0.0));
+    assert!(approx(out[2].1, 1.0));
+    assert!(approx(out[3].1, 1.0));
+}
+
+#[test]
+fn mapping_is_configurable() {
+    let confidence = ConfidenceMapping { zero_at: Some(6.0), gamma: 2.0, number: 0.5, separator: 0.25 };
+    let config = SegmenterConfig { confidence, ..Default::default() };
+    let out = scores(config, "បាន ១");
+    assert!(approx(out[0].1, 0.25));
+    assert!(approx(out[1].1, 0.25));
+    assert!(approx(out[2].1, 0.5));
+}
+
+#[test]
+fn scores_stay_in_unit_range() {
+    let out = scores(SegmenterConfig::default(), "ាបានabc ៗ!");
+    assert!(out.iter().all(|(_, c)| (0.0..=1.0).contains(c)), "{:?}", out);
+}
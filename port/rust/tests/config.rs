@@ -0,0 +1,49 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+
+#[test]
+fn all_disabled_runs_the_bare_dp() {
+    let config = SegmenterConfig::all_disabled();
+    assert!(!config.enable_normalization && !config.enable_repair_mode && !config.enable_unknown_merging);
+    let seg = segmenter(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)], config);
+    // Zero-width characters are not stripped and unknowns stay apart
+    assert_eq!(tokens(&seg, "ខ្ញុំ\u{200B}ទៅ"), ["ខ្ញុំ", "\u{200B}", "ទៅ"]);
+    assert_eq!(tokens(&seg, "abc"), ["a", "b", "c"]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn missing_fields_take_their_defaults() {
+    use khmer_segmenter::khmer_segmenter::OrphanedVowels;
+
+    let config: SegmenterConfig = serde_json::from_str(r#"{ "enable_repair_mode": false, "orphaned_vowels": "Flag" }"#).unwrap();
+    assert!(!config.enable_repair_mode);
+    assert_eq!(config.orphaned_vowels, OrphanedVowels::Flag);
+    assert!(config.enable_normalization);
+    assert_eq!(config.max_acronym_clusters, usize::MAX);
+    assert_eq!(config.normalization.max_cluster_parts, 16);
+
+    let config: SegmenterConfig = serde_json::from_str(r#"{ "normalization": { "fold_table": { "ឣ": "អ" } } }"#).unwrap();
+    assert_eq!(config.normalization.fold_table[&'ឣ'], "អ");
+    assert!(!config.normalization.strip_chars.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn configs_round_trip_through_json() {
+    use khmer_segmenter::khmer_segmenter::SeparatorPolicy;
+
+    let mut config = SegmenterConfig::all_disabled();
+    config.merge_unknowns_across = SeparatorPolicy::Allow(vec!['-']);
+    config.compound_pairs.insert(("ខ្ញុំ".to_string(), "ទៅ".to_string()));
+    config.unknown_ramp = Some(0.5);
+
+    // Compared as values: map and set order is not stable across instances
+    let json = serde_json::to_value(&config).unwrap();
+    let back: SegmenterConfig = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&back).unwrap(), json);
+    assert_eq!(back.merge_unknowns_across, SeparatorPolicy::Allow(vec!['-']));
+    assert!(!back.enable_normalization);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::{SegmenterConfig, TokenKind};
+
+#[test]
+fn unknown_cost_override_replaces_header_value() {
+    let base = SegmenterConfig { enable_unknown_merging: false, ..Default::default() };
+    let seg = segmenter(&[("ការ", 12.0)], base.clone());
+    assert_eq!(tokens(&seg, "ការ"), ["ការ"]);
+
+    // Two unknown clusters at 1.0 each now undercut the 12.0 dictionary word.
+    let cheap = SegmenterConfig { unknown_cost_override: Some(1.0), ..base };
+    let seg = segmenter(&[("ការ", 12.0)], cheap);
+    assert_eq!(tokens(&seg, "ការ"), ["កា", "រ"]);
+}
+
+#[test]
+fn default_cost_override_applies_to_acronyms() {
+    let seg = segmenter(&[], SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ក.ខ."), ["ក.ខ."]);
+
+    let config = SegmenterConfig { default_cost_override: Some(100.0), ..Default::default() };
+    let seg = segmenter(&[], config);
+    assert_eq!(tokens(&seg, "ក.ខ."), ["ក", ".", "ខ", "."]);
+}
+
+#[test]
+fn unknown_ramp_penalizes_long_unknown_runs() {
+    let base = SegmenterConfig { enable_unknown_merging: false, ..Default::default() };
+    // Three unknowns at 15.0 undercut the 50.0 word...
+    let seg = segmenter(&[("កខគ", 50.0)], base.clone());
+    assert_eq!(tokens(&seg, "កខគ"), ["ក", "ខ", "គ"]);
+
+    // ...until each further unknown in the run costs 5.0 more: 15 + 20 + 25
+    let ramped = SegmenterConfig { unknown_ramp: Some(5.0), ..base };
+    let seg = segmenter(&[("កខគ", 50.0)], ramped);
+    assert_eq!(tokens(&seg, "កខគ"), ["កខគ"]);
+}
+
+#[test]
+fn unknown_ramp_restarts_after_a_known_token() {
+    let config = SegmenterConfig { enable_unknown_merging: false, unknown_ramp: Some(5.0), ..Default::default() };
+    let seg = segmenter(&[("ទៅ", 3.0)], config);
+    let costs: Vec<_> = seg.segment_detailed("abទៅc").iter().map(|t| t.path_cost.unwrap()).collect();
+    assert_eq!(costs, [15.0, 20.0, 3.0, 15.0]);
+}
+
+#[test]
+fn min_dict_match_chars_skips_single_char_entries() {
+    let words = &[("ក", 1.0), ("ទៅ", 3.0)];
+    let kinds = |config: SegmenterConfig| {
+        let seg = segmenter(words, config);
+        seg.segment_detailed("កទៅ").into_iter().map(|t| (t.text, t.kind)).collect::<Vec<_>>()
+    };
+    let base = SegmenterConfig { enable_unknown_merging: false, ..Default::default() };
+    assert_eq!(kinds(base.clone()), [("ក".to_string(), TokenKind::Word), ("ទៅ".to_string(), TokenKind::Word)]);
+
+    let config = SegmenterConfig { min_dict_match_chars: 2, ..base };
+    assert_eq!(kinds(config), [("ក".to_string(), TokenKind::Unknown), ("ទៅ".to_string(), TokenKind::Word)]);
+}
+
+#[test]
+fn segment_with_cost_reports_the_viterbi_optimum() {
+    let seg = segmenter(&[("ខ្ញុំ", 3.0), ("ទៅ", 4.0)], SegmenterConfig::default());
+    assert_eq!(seg.segment_with_cost("ខ្ញុំទៅ", Some("|")), ("ខ្ញុំ|ទៅ".to_string(), 7.0));
+    let (joined, cost) = seg.segment_with_cost("ខ្ញុំ ទៅ", Some("|"));
+    assert_eq!(joined, seg.segment("ខ្ញុំ ទៅ", Some("|")));
+    assert!((cost - 7.1).abs() < 1e-4);
+
+    // Unknown clusters merged afterwards still cost what the DP paid for
+    // each of them
+    let (joined, cost) = seg.segment_with_cost("ខ្ញុំឆ្កែឆ្មា", Some("|"));
+    assert_eq!(joined, "ខ្ញុំ|ឆ្កែឆ្មា");
+    assert_eq!(cost, 3.0 + 2.0 * 15.0);
+
+    assert_eq!(seg.segment_with_cost("", None), (String::new(), 0.0));
+}
@@ -0,0 +1,114 @@
+use khmer_segmenter::kdict::build::KDictBuilder;
+use khmer_segmenter::kdict::KDict;
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig, TokenKind};
+
+const NOUN: u8 = 1;
+const VERB: u8 = 2;
+
+fn segmenter(with_categories: bool) -> KhmerSegmenter {
+    let mut builder = KDictBuilder::new(10.0, 15.0);
+    if with_categories {
+        builder.insert_with_category("ខ្ញុំ", 3.0, NOUN);
+        builder.insert_with_category("ទៅ", 4.0, VERB);
+    } else {
+        builder.insert("ខ្ញុំ", 3.0);
+        builder.insert("ទៅ", 4.0);
+    }
+    builder.insert("ផ្ទះ", 5.0);
+    KhmerSegmenter::new_with_dict(Some(KDict::from_bytes(builder.build()).unwrap()), SegmenterConfig::default())
+}
+
+#[test]
+fn dictionary_words_carry_cost_and_category() {
+    let tokens = segmenter(true).segment_detailed("ខ្ញុំទៅផ្ទះ ១២");
+    let summary: Vec<_> = tokens.iter().map(|t| (t.text.as_str(), t.kind, t.cost, t.category)).collect();
+    assert_eq!(summary, [
+        ("ខ្ញុំ", TokenKind::Word, Some(3.0), Some(NOUN)),
+        ("ទៅ", TokenKind::Word, Some(4.0), Some(VERB)),
+        ("ផ្ទះ", TokenKind::Word, Some(5.0), None),
+        (" ", TokenKind::Separator, None, None),
+        ("១២", TokenKind::Number, None, None),
+    ]);
+}
+
+#[test]
+fn version_one_dictionary_has_no_categories() {
+    let mut builder = KDictBuilder::new(10.0, 15.0);
+    builder.insert("ទៅ", 4.0);
+    let bytes = builder.build();
+    assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 1);
+
+    let tokens = segmenter(false).segment_detailed("ខ្ញុំទៅ");
+    assert!(tokens.iter().all(|t| t.kind == TokenKind::Word && t.category.is_none()));
+}
+
+#[test]
+fn categories_survive_pruning() {
+    let mut builder = KDictBuilder::new(10.0, 15.0);
+    builder.insert_with_category("ខ្ញុំ", 3.0, NOUN);
+    builder.insert_with_category("ទៅ", 9.0, VERB);
+    let full = KDict::from_bytes(builder.build()).unwrap();
+    let slim = KDict::from_bytes(khmer_segmenter::kdict::build::prune(&full, 5.0)).unwrap();
+    let slot = slim.lookup("ខ្ញុំ".as_bytes()).unwrap();
+    assert_eq!(slim.category(slot), NOUN);
+    assert_eq!(slim.lookup("ទៅ".as_bytes()), None);
+}
+
+#[test]
+fn rejects_unknown_version() {
+    let mut bytes = KDictBuilder::new(10.0, 15.0).build();
+    bytes[4..8].copy_from_slice(&9u32.to_le_bytes());
+    assert!(KDict::from_bytes(bytes).is_err());
+}
+
+#[test]
+fn html_wraps_tokens_and_leaves_separators_bare() {
+    let html = segmenter(false).segment_html("ខ្ញុំទៅ ១២");
+    assert_eq!(
+        html,
+        "<span class=\"tok kind-word\">ខ្ញុំ</span><span class=\"tok kind-word\">ទៅ</span> <span class=\"tok kind-number\">១២</span>"
+    );
+}
+
+#[test]
+fn html_escapes_markup_characters() {
+    let html = segmenter(false).segment_html("ទៅ <b>&");
+    assert!(html.contains("&lt;"));
+    assert!(html.contains("&gt;"));
+    assert!(html.contains("&amp;"));
+    assert!(!html.contains("<b>"));
+}
+
+#[test]
+fn dictionary_words_carry_their_table_slot() {
+    let seg = segmenter(true);
+    let dict = seg.dictionary().unwrap();
+    for token in seg.segment_detailed("ខ្ញុំទៅ ១២") {
+        match token.kind {
+            TokenKind::Word => {
+                let slot = token.slot.unwrap();
+                assert_eq!(dict.get_pool_bytes(dict.entry(slot).name_offset), token.text.as_bytes());
+                assert_eq!({ dict.entry(slot).cost }, token.cost.unwrap());
+            }
+            _ => assert_eq!(token.slot, None),
+        }
+    }
+}
+
+#[test]
+fn stop_words_are_flagged_without_changing_tokens() {
+    let plain = segmenter(false);
+    let mut builder = KDictBuilder::new(10.0, 15.0);
+    builder.insert("ខ្ញុំ", 3.0);
+    builder.insert("ទៅ", 4.0);
+    builder.insert("ផ្ទះ", 5.0);
+    let config = SegmenterConfig { stop_words: ["ទៅ".to_string()].into_iter().collect(), ..Default::default() };
+    let seg = KhmerSegmenter::new_with_dict(Some(KDict::from_bytes(builder.build()).unwrap()), config);
+
+    let text = "ខ្ញុំទៅផ្ទះ ទៅ";
+    assert_eq!(seg.segment(text, None), plain.segment(text, None));
+    let stops: Vec<(String, bool)> = seg.segment_detailed(text).into_iter().map(|t| (t.text, t.stop)).collect();
+    let expected = [("ខ្ញុំ", false), ("ទៅ", true), ("ផ្ទះ", false), (" ", false), ("ទៅ", true)];
+    assert_eq!(stops, expected.map(|(t, stop)| (t.to_string(), stop)));
+    assert!(plain.segment_detailed(text).iter().all(|t| !t.stop));
+}
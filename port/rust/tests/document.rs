@@ -0,0 +1,34 @@
+mod common;
+
+use common::segmenter;
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("បាន", 3.0), ("ទៅ", 3.0), ("ផ្ទះ", 3.0)];
+
+#[test]
+fn splits_on_khan_and_keeps_terminator() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let doc = seg.segment_document("ខ្ញុំទៅ។ ខ្ញុំបាន៕");
+    assert_eq!(doc, [vec!["ខ្ញុំ", "ទៅ", "។"], vec!["ខ្ញុំ", "បាន", "៕"]]);
+}
+
+#[test]
+fn acronym_dots_do_not_end_sentences() {
+    let config = SegmenterConfig { latin_sentence_terminators: true, ..Default::default() };
+    let seg = segmenter(WORDS, config);
+    let doc = seg.segment_document("ស.ភ.ភ.ព. ទៅផ្ទះ. ខ្ញុំទៅ");
+    assert_eq!(doc, [vec!["ស.ភ.ភ.ព.", " ", "ទៅ", "ផ្ទះ", "."], vec!["ខ្ញុំ", "ទៅ"]]);
+}
+
+#[test]
+fn latin_terminators_are_opt_in() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(seg.segment_document("ទៅ! ទៅ").len(), 1);
+}
+
+#[test]
+fn etc_abbreviation_does_not_end_sentence() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let doc = seg.segment_document("ផ្ទះ ។ល។ ទៅ។");
+    assert_eq!(doc, [vec!["ផ្ទះ", " ", "។", "ល", "។", " ", "ទៅ", "។"]]);
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::{Fallback, SegmenterConfig};
+use khmer_segmenter::utils::contains_khmer;
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)];
+
+fn config(fallback: Fallback) -> SegmenterConfig {
+    SegmenterConfig { non_khmer_fallback: fallback, enable_unknown_merging: false, ..Default::default() }
+}
+
+#[test]
+fn per_char_splits_every_character() {
+    let seg = segmenter(WORDS, config(Fallback::PerChar));
+    assert_eq!(tokens(&seg, "ສະບາຍ").len(), "ສະບາຍ".chars().count());
+}
+
+#[test]
+fn whitespace_split_keeps_words_and_spaces() {
+    let seg = segmenter(WORDS, config(Fallback::WhitespaceSplit));
+    assert_eq!(tokens(&seg, "สวัสดี  ครับ"), ["สวัสดี", "  ", "ครับ"]);
+    assert_eq!(tokens(&seg, " مرحبا"), [" ", "مرحبا"]);
+}
+
+#[test]
+fn single_token_keeps_the_whole_line() {
+    let seg = segmenter(WORDS, config(Fallback::SingleToken));
+    assert_eq!(tokens(&seg, "สวัสดี ครับ"), ["สวัสดี ครับ"]);
+}
+
+#[test]
+fn text_with_khmer_is_segmented_normally() {
+    let seg = segmenter(WORDS, config(Fallback::SingleToken));
+    assert_eq!(tokens(&seg, "ខ្ញុំទៅ"), ["ខ្ញុំ", "ទៅ"]);
+    assert_eq!(tokens(&seg, "abc ទៅ")[..2], ["a", "b"]);
+}
+
+#[test]
+fn contains_khmer_checks_both_blocks() {
+    assert!(contains_khmer("abc ក"));
+    assert!(contains_khmer("\u{19E0}"));
+    assert!(!contains_khmer("สวัสดี"));
+    assert!(!contains_khmer(""));
+}
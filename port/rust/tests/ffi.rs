@@ -0,0 +1,62 @@
+#![cfg(feature = "ffi")]
+
+mod common;
+
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use common::dict_bytes;
+use khmer_segmenter::ffi::*;
+
+// Writes a small .kdict under the temp dir, named after the test.
+fn write_dict(name: &str) -> CString {
+    let path = std::env::temp_dir().join(format!("khmer_segmenter_{}_{}.kdict", name, std::process::id()));
+    std::fs::write(&path, dict_bytes(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)])).unwrap();
+    CString::new(path.to_string_lossy().into_owned()).unwrap()
+}
+
+// Copies and frees a string returned by `khmer_segmenter_segment`.
+unsafe fn take(s: *mut std::ffi::c_char) -> String {
+    assert!(!s.is_null());
+    let out = CStr::from_ptr(s).to_str().unwrap().to_string();
+    khmer_segmenter_free_string(s);
+    out
+}
+
+#[test]
+fn segments_through_the_c_abi() {
+    unsafe {
+        let seg = khmer_segmenter_new(write_dict("ffi_segments").as_ptr());
+        assert!(!seg.is_null());
+
+        let text = CString::new("ខ្ញុំទៅ").unwrap();
+        let sep = CString::new(" | ").unwrap();
+        assert_eq!(take(khmer_segmenter_segment(seg, text.as_ptr(), sep.as_ptr())), "ខ្ញុំ | ទៅ");
+        // NULL separator: zero width space, like the C port
+        assert_eq!(take(khmer_segmenter_segment(seg, text.as_ptr(), ptr::null())), "ខ្ញុំ\u{200B}ទៅ");
+
+        khmer_segmenter_free(seg);
+    }
+}
+
+#[test]
+fn bad_input_gives_null() {
+    unsafe {
+        let missing = CString::new("/nonexistent/khmer.kdict").unwrap();
+        assert!(khmer_segmenter_new(missing.as_ptr()).is_null());
+        assert!(khmer_segmenter_new(ptr::null()).is_null());
+
+        let seg = khmer_segmenter_new(write_dict("ffi_bad_input").as_ptr());
+        let text = CString::new("ទៅ").unwrap();
+        let invalid = CString::new(vec![0xffu8, 0xfe]).unwrap();
+        assert!(khmer_segmenter_segment(ptr::null(), text.as_ptr(), ptr::null()).is_null());
+        assert!(khmer_segmenter_segment(seg, ptr::null(), ptr::null()).is_null());
+        assert!(khmer_segmenter_segment(seg, invalid.as_ptr(), ptr::null()).is_null());
+        assert!(khmer_segmenter_segment(seg, text.as_ptr(), invalid.as_ptr()).is_null());
+
+        // Freeing NULL is a no-op
+        khmer_segmenter_free_string(ptr::null_mut());
+        khmer_segmenter_free(ptr::null_mut());
+        khmer_segmenter_free(seg);
+    }
+}
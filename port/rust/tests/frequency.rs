@@ -0,0 +1,62 @@
+mod common;
+
+use common::tokens;
+use khmer_segmenter::kdict::build::{prune, KDictBuilder};
+use khmer_segmenter::kdict::KDict;
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+
+// "កខ" ties with "ក" + "ខ" on cost; the split halves are frequent.
+fn tied_dict() -> KDict {
+    let mut builder = KDictBuilder::new(10.0, 15.0);
+    builder.insert("កខ", 4.0);
+    builder.insert_with_frequency("ក", 2.0, 1000);
+    builder.insert_with_frequency("ខ", 2.0, 1000);
+    KDict::from_bytes(builder.build()).unwrap()
+}
+
+#[test]
+fn frequencies_are_stored_in_version_4() {
+    let dict = tied_dict();
+    assert_eq!({ unsafe { *dict.header }.version }, 4);
+    assert!(dict.has_frequencies());
+    let freq = |w: &str| dict.frequency(dict.lookup(w.as_bytes()).unwrap());
+    assert_eq!((freq("ក"), freq("ខ"), freq("កខ")), (1000, 1000, 0));
+    assert_eq!(dict.validate(), Ok(()));
+
+    // Rebuilding keeps them
+    let pruned = KDict::from_bytes(prune(&dict, 3.0)).unwrap();
+    assert_eq!(pruned.frequency(pruned.lookup("ក".as_bytes()).unwrap()), 1000);
+    assert!(pruned.lookup("កខ".as_bytes()).is_none());
+}
+
+#[test]
+fn older_versions_have_no_frequencies() {
+    let mut builder = KDictBuilder::new(10.0, 15.0);
+    builder.insert_with_flags("ក", 2.0, 1, 0);
+    let dict = KDict::from_bytes(builder.build()).unwrap();
+    assert_eq!({ unsafe { *dict.header }.version }, 2);
+    assert!(!dict.has_frequencies());
+    assert_eq!(dict.frequency(dict.lookup("ក".as_bytes()).unwrap()), 0);
+}
+
+#[test]
+fn frequency_bonus_decides_close_calls_only_when_enabled() {
+    let seg = KhmerSegmenter::new_with_dict(Some(tied_dict()), SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "កខ"), ["ក", "ខ"]);
+    let (_, cost) = seg.segment_with_cost("កខ", None);
+    assert!((cost - (4.0 - 0.2 * 1001f32.ln())).abs() < 1e-4);
+
+    let off = SegmenterConfig { enable_frequency_costs: false, ..Default::default() };
+    let seg = KhmerSegmenter::new_with_dict(Some(tied_dict()), off);
+    assert_eq!(tokens(&seg, "កខ"), ["កខ"]);
+    assert_eq!(seg.segment_with_cost("កខ", None).1, 4.0);
+
+    // A tiny weight is not enough to beat a clearly cheaper path
+    let mut builder = KDictBuilder::new(10.0, 15.0);
+    builder.insert("កខ", 3.0);
+    builder.insert_with_frequency("ក", 2.0, 1000);
+    builder.insert_with_frequency("ខ", 2.0, 1000);
+    let dict = KDict::from_bytes(builder.build()).unwrap();
+    let config = SegmenterConfig { frequency_weight: 0.01, ..Default::default() };
+    assert_eq!(tokens(&KhmerSegmenter::new_with_dict(Some(dict), config), "កខ"), ["កខ"]);
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "grapheme-clusters")]
+
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+use khmer_segmenter::normalization::khmer_normalize;
+use khmer_segmenter::utils::get_grapheme_length;
+
+const FAMILY: &str = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+
+fn config() -> SegmenterConfig {
+    SegmenterConfig { enable_unknown_merging: false, ..Default::default() }
+}
+
+#[test]
+fn combining_marks_stay_with_base() {
+    assert_eq!(get_grapheme_length("e\u{0301}x"), "e\u{0301}".len());
+    assert_eq!(get_grapheme_length("a\u{0308}\u{0304}"), "a\u{0308}\u{0304}".len());
+}
+
+#[test]
+fn emoji_sequences_are_one_cluster() {
+    assert_eq!(get_grapheme_length(&format!("{FAMILY}!")), FAMILY.len());
+    let thumbs = "\u{1F44D}\u{1F3FD}";
+    assert_eq!(get_grapheme_length(thumbs), thumbs.len());
+    let flag = "\u{1F1F0}\u{1F1ED}";
+    assert_eq!(get_grapheme_length(&format!("{flag}\u{1F1FA}")), flag.len());
+}
+
+#[test]
+fn plain_characters_are_one_codepoint() {
+    assert_eq!(get_grapheme_length("ab"), 1);
+    assert_eq!(get_grapheme_length("\r\nx"), 2);
+    assert_eq!(get_grapheme_length(""), 0);
+}
+
+#[test]
+fn zwj_is_kept_only_inside_emoji_sequences() {
+    assert_eq!(khmer_normalize(FAMILY), FAMILY);
+    assert_eq!(khmer_normalize("a\u{200D}b"), "ab");
+    assert_eq!(khmer_normalize("ក\u{200D}ខ"), "កខ");
+}
+
+#[test]
+fn accented_latin_is_not_split_from_its_mark() {
+    let seg = segmenter(&[("បាន", 3.0)], config());
+    assert_eq!(tokens(&seg, "e\u{0301} បាន"), ["e\u{0301}", " ", "បាន"]);
+}
+
+#[test]
+fn khmer_vowels_after_other_scripts_stay_orphans() {
+    let seg = segmenter(&[("ទៅ", 3.0)], config());
+    assert_eq!(tokens(&seg, "e\u{0301}ាទៅ"), ["e\u{0301}ា", "ទៅ"]);
+}
+
+#[test]
+fn emoji_family_survives_normalization_and_dp() {
+    let seg = segmenter(&[("បាន", 3.0)], config());
+    assert_eq!(tokens(&seg, &format!("បាន{FAMILY}")), ["បាន", FAMILY]);
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+
+const WORDS: &[(&str, f32)] = &[("ជំងឺ", 3.0)];
+
+fn joined() -> SegmenterConfig {
+    SegmenterConfig { join_hyphenated_mixed: true, ..Default::default() }
+}
+
+#[test]
+fn split_at_the_hyphen_by_default() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "COVID-១៩"), ["COVID", "-", "១៩"]);
+}
+
+#[test]
+fn latin_word_and_number_join_across_a_hyphen() {
+    let seg = segmenter(WORDS, joined());
+    assert_eq!(tokens(&seg, "ជំងឺCOVID-១៩"), ["ជំងឺ", "COVID-១៩"]);
+    assert_eq!(tokens(&seg, "COVID-19"), ["COVID-19"]);
+    assert_eq!(tokens(&seg, "COVID–១៩"), ["COVID–១៩"]);
+    assert_eq!(tokens(&seg, "iPhone-13-Pro ជំងឺ"), ["iPhone-13-Pro", " ", "ជំងឺ"]);
+}
+
+#[test]
+fn spaces_same_script_pairs_and_stray_hyphens_stay_apart() {
+    let seg = segmenter(WORDS, joined());
+    assert_eq!(tokens(&seg, "iPhone ១៣"), ["iPhone", " ", "១៣"]);
+    assert_eq!(tokens(&seg, "COVID -១៩"), ["COVID", " ", "-", "១៩"]);
+    assert_eq!(tokens(&seg, "X-ray"), ["X", "-", "ray"]);
+    assert_eq!(tokens(&seg, "១-២"), ["១", "-", "២"]);
+    assert_eq!(tokens(&seg, "COVID-"), ["COVID", "-"]);
+    assert_eq!(tokens(&seg, "COVID—១៩"), ["COVID", "—", "១៩"]);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common::segmenter;
+use khmer_segmenter::interner::{HashInterner, Interner};
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0), ("ផ្ទះ", 3.0)];
+
+#[test]
+fn identical_tokens_share_storage_across_calls() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let mut interner = HashInterner::new();
+    let a = seg.segment_interned("ខ្ញុំទៅផ្ទះ", &mut interner);
+    let b = seg.segment_interned("ផ្ទះខ្ញុំ", &mut interner);
+
+    let strs: Vec<&str> = a.iter().map(|t| &**t).collect();
+    assert_eq!(strs, ["ខ្ញុំ", "ទៅ", "ផ្ទះ"]);
+    assert!(Arc::ptr_eq(&a[0], &b[1]));
+    assert!(Arc::ptr_eq(&a[2], &b[0]));
+    assert_eq!(interner.len(), 3);
+}
+
+struct CountingInterner {
+    seen: HashMap<String, Arc<str>>,
+    calls: usize,
+}
+
+impl Interner for CountingInterner {
+    fn intern(&mut self, token: &str) -> Arc<str> {
+        self.calls += 1;
+        self.seen.entry(token.to_string()).or_insert_with(|| Arc::from(token)).clone()
+    }
+}
+
+#[test]
+fn accepts_custom_interner() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let mut interner = CountingInterner { seen: HashMap::new(), calls: 0 };
+    let tokens = seg.segment_interned("ទៅទៅ", &mut interner);
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(interner.calls, 2);
+    assert_eq!(interner.seen.len(), 1);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::segmenter;
+use khmer_segmenter::khmer_segmenter::{PunctuationAttachment, SegmenterConfig};
+use khmer_segmenter::normalization::{deprecated_fold_table, NormalizationConfig};
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0), ("ផ្ទះ", 3.0)];
+
+fn join(attach: PunctuationAttachment, text: &str) -> String {
+    let config = SegmenterConfig { punctuation_attachment: attach, ..Default::default() };
+    segmenter(WORDS, config).segment(text, Some(" | "))
+}
+
+#[test]
+fn standalone_separates_every_token() {
+    assert_eq!(join(PunctuationAttachment::Standalone, "ខ្ញុំទៅ។ផ្ទះ"), "ខ្ញុំ | ទៅ | ។ | ផ្ទះ");
+}
+
+#[test]
+fn attach_left_glues_punctuation_to_previous_word() {
+    assert_eq!(join(PunctuationAttachment::AttachLeft, "ខ្ញុំទៅ។ផ្ទះ"), "ខ្ញុំ | ទៅ។ | ផ្ទះ");
+    assert_eq!(join(PunctuationAttachment::AttachLeft, "ទៅ។ ផ្ទះ"), "ទៅ។  | ផ្ទះ");
+}
+
+#[test]
+fn attach_right_glues_punctuation_to_next_word() {
+    assert_eq!(join(PunctuationAttachment::AttachRight, "ខ្ញុំទៅ។ផ្ទះ"), "ខ្ញុំ | ទៅ | ។ផ្ទះ");
+}
+
+#[test]
+fn leading_punctuation_is_unaffected_by_attach_left() {
+    assert_eq!(join(PunctuationAttachment::AttachLeft, "(ទៅ)"), "( | ទៅ)");
+}
+
+#[test]
+fn multi_join_matches_separate_calls() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let text = "ខ្ញុំទៅ។";
+    let joined = seg.segment_multi(text, &["\u{200B}", " | "]);
+    assert_eq!(joined, [seg.segment(text, None), seg.segment(text, Some(" | "))]);
+    assert_eq!(joined[1], "ខ្ញុំ | ទៅ | ។");
+    assert!(seg.segment_multi(text, &[]).is_empty());
+}
+
+#[test]
+fn resegment_preserving_keeps_upstream_boundaries() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    // The DP would join these, but upstream split them
+    assert_eq!(seg.resegment_preserving("ខ្ញុំ\u{200B}ទៅ", "\u{200B}", " | "), "ខ្ញុំ | ទៅ");
+    assert_eq!(seg.resegment_preserving("ខ្\u{200B}ញុំ", "\u{200B}", "|"), "ខ្|ញុំ");
+}
+
+#[test]
+fn resegment_preserving_normalizes_each_token() {
+    let normalization = NormalizationConfig { fold_table: deprecated_fold_table(), ..Default::default() };
+    let seg = segmenter(WORDS, SegmenterConfig { normalization, ..Default::default() });
+    // Vowel typed before the subscript is reordered; QAA is folded
+    assert_eq!(seg.resegment_preserving("ស\u{17B6}\u{17D2}រ/\u{17A4}", "/", "|"), "ស\u{17D2}រ\u{17B6}|អា");
+}
+
+#[test]
+fn resegment_preserving_drops_empty_tokens() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(seg.resegment_preserving("/ខ្ញុំ//\u{200C}/ទៅ/", "/", "|"), "ខ្ញុំ|ទៅ");
+    assert_eq!(seg.resegment_preserving("", "/", "|"), "");
+}
+
+#[test]
+fn default_separator_replaces_zwsp_when_none_is_given() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(seg.segment("ខ្ញុំទៅ", None), "ខ្ញុំ\u{200B}ទៅ");
+
+    let config = SegmenterConfig { default_separator: Some(" | ".to_string()), ..Default::default() };
+    let seg = segmenter(WORDS, config);
+    assert_eq!(seg.segment("ខ្ញុំទៅ", None), "ខ្ញុំ | ទៅ");
+    assert_eq!(seg.segment_original("ខ្ញុំទៅ", None), "ខ្ញុំ | ទៅ");
+    // The per-call separator still wins
+    assert_eq!(seg.segment("ខ្ញុំទៅ", Some("/")), "ខ្ញុំ/ទៅ");
+}
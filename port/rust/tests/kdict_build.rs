@@ -0,0 +1,115 @@
+use khmer_segmenter::kdict::build::{build_kdict, prune, KDictBuilder};
+use khmer_segmenter::kdict::KDict;
+
+fn header(dict: &KDict) -> (u32, u32, u32) {
+    let h = unsafe { *dict.header };
+    (h.num_entries, h.table_size, h.max_word_length)
+}
+
+fn sorted_words(dict: &KDict) -> Vec<(String, f32)> {
+    let mut words: Vec<_> = dict.words()
+        .map(|(w, c)| (String::from_utf8(w.to_vec()).unwrap(), c))
+        .collect();
+    words.sort_by(|a, b| a.0.cmp(&b.0));
+    words
+}
+
+#[test]
+fn prune_drops_costly_words_and_recomputes_header() {
+    let full = KDict::load("tests/data/tiny.kdict").unwrap();
+    let (full_entries, full_table, _) = header(&full);
+
+    let slim = KDict::from_bytes(prune(&full, 5.0)).unwrap();
+    let kept = sorted_words(&slim);
+    let expected: Vec<_> = sorted_words(&full).into_iter().filter(|(_, c)| *c <= 5.0).collect();
+    assert_eq!(kept, expected);
+    assert!(!kept.is_empty() && kept.len() < full_entries as usize);
+
+    let (entries, table, max_len) = header(&slim);
+    assert_eq!(entries as usize, kept.len());
+    assert!(table.is_power_of_two() && table > entries && table <= full_table);
+    assert_eq!(max_len as usize, kept.iter().map(|(w, _)| w.len()).max().unwrap());
+
+    let (h_full, h_slim) = unsafe { (*full.header, *slim.header) };
+    assert_eq!({ h_slim.default_cost }, { h_full.default_cost });
+    assert_eq!({ h_slim.unknown_cost }, { h_full.unknown_cost });
+}
+
+#[test]
+fn keep_cheapest_keeps_top_n() {
+    let mut builder = KDictBuilder::new(10.0, 15.0);
+    builder.insert("ក", 5.0);
+    builder.insert("ខ", 1.0);
+    builder.insert("គ", 3.0);
+    builder.insert("ខ", 4.0); // duplicate keeps the lower cost
+    builder.keep_cheapest(2);
+
+    let dict = KDict::from_bytes(builder.build()).unwrap();
+    assert_eq!(sorted_words(&dict), vec![("ខ".to_string(), 1.0), ("គ".to_string(), 3.0)]);
+}
+
+#[test]
+fn build_is_deterministic_and_handles_empty_input() {
+    let words = vec![("សួស្តី".to_string(), 4.0), ("ខ្មែរ".to_string(), 3.0)];
+    let reversed: Vec<_> = words.iter().rev().cloned().collect();
+    assert_eq!(build_kdict(&words, 10.0, 15.0), build_kdict(&reversed, 10.0, 15.0));
+
+    let empty = KDict::from_bytes(build_kdict(&[], 10.0, 15.0)).unwrap();
+    assert_eq!(header(&empty).0, 0);
+    assert_eq!(empty.words().count(), 0);
+}
+
+fn table_size(builder: &KDictBuilder) -> u32 {
+    header(&KDict::from_bytes(builder.build()).unwrap()).1
+}
+
+#[test]
+fn load_factor_and_reserve_size_the_table() {
+    let mut builder = KDictBuilder::new(10.0, 15.0);
+    for i in 0..100 {
+        builder.insert(&format!("w{}", i), 1.0);
+    }
+    // 100 / 0.7 -> 143 -> 256
+    assert_eq!(table_size(&builder), 256);
+    // 100 / 0.3 -> 334 -> 512
+    builder.load_factor(0.3);
+    assert_eq!(table_size(&builder), 512);
+    // 1000 / 0.3 -> 3334 -> 4096
+    builder.reserve(1000);
+    assert_eq!(table_size(&builder), 4096);
+}
+
+#[test]
+#[should_panic(expected = "load factor")]
+fn load_factor_must_be_below_one() {
+    KDictBuilder::new(10.0, 15.0).load_factor(1.0);
+}
+
+#[test]
+fn build_kdict_round_trips_every_word() {
+    // Enough words to force long probe chains, of assorted byte lengths
+    let consonants: Vec<char> = ('\u{1780}'..='\u{17A2}').collect();
+    let mut words: Vec<(String, f32)> = Vec::new();
+    for (i, &a) in consonants.iter().enumerate() {
+        for (j, &b) in consonants.iter().enumerate() {
+            let word = format!("{a}{}{b}", "\u{17B6}".repeat((i + j) % 4));
+            words.push((word, (i * 35 + j) as f32 / 100.0));
+        }
+    }
+    words.push(("ok".to_string(), 1.5));
+
+    let dict = KDict::from_bytes(build_kdict(&words, 10.0, 15.0)).unwrap();
+    for (word, cost) in &words {
+        let slot = dict.lookup(word.as_bytes()).unwrap_or_else(|| panic!("{word} not found"));
+        assert_eq!(dict.get_pool_bytes(dict.entry(slot).name_offset), word.as_bytes());
+        assert_eq!({ dict.entry(slot).cost }, *cost);
+    }
+    assert!(!dict.contains("កក\u{17B6}\u{17B6}\u{17B6}\u{17B6}"));
+
+    let (entries, table, max_len) = header(&dict);
+    assert_eq!(entries as usize, words.len());
+    assert!(table.is_power_of_two() && table as f64 >= words.len() as f64 / 0.7);
+    assert_eq!(max_len as usize, words.iter().map(|(w, _)| w.len()).max().unwrap());
+    let h = unsafe { *dict.header };
+    assert_eq!(({ h.default_cost }, { h.unknown_cost }), (10.0, 15.0));
+}
@@ -0,0 +1,199 @@
+mod common;
+
+use common::{dict, dict_bytes};
+use std::sync::Arc;
+
+use khmer_segmenter::kdict::KDict;
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+use khmer_segmenter::normalization::khmer_normalize;
+use khmer_segmenter::utils::djb2_hash;
+
+fn header(num_entries: u32, table_size: u32) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(b"KDIC");
+    out.extend_from_slice(&1u32.to_le_bytes());
+    out.extend_from_slice(&num_entries.to_le_bytes());
+    out.extend_from_slice(&table_size.to_le_bytes());
+    out.extend_from_slice(&10.0f32.to_le_bytes());
+    out.extend_from_slice(&15.0f32.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out
+}
+
+fn load_error(bytes: Vec<u8>) -> String {
+    let err = KDict::from_bytes(bytes).err().expect("dictionary should be rejected");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    err.to_string()
+}
+
+#[test]
+fn header_only_dictionary_with_empty_table_is_rejected() {
+    assert_eq!(load_error(header(0, 0)), "Empty table");
+}
+
+#[test]
+fn empty_table_is_rejected_even_with_trailing_bytes() {
+    let mut bytes = header(0, 0);
+    bytes.extend_from_slice(&[0; 16]);
+    assert_eq!(load_error(bytes), "Empty table");
+}
+
+#[test]
+fn shorter_than_header_is_rejected() {
+    assert_eq!(load_error(header(0, 0)[..31].to_vec()), "File too small");
+}
+
+#[test]
+fn dictionary_without_words_loads_and_finds_nothing() {
+    let dict = KDict::from_bytes(dict_bytes(&[])).unwrap();
+    assert_eq!(dict.words().count(), 0);
+    assert!(!dict.contains("ក"));
+}
+
+#[test]
+fn check_normalized_reports_unnormalized_words() {
+    // Vowel before the subscript: normalization moves the coeng pair first
+    let dict = KDict::from_bytes(dict_bytes(&[("ខ្ញុំ", 3.0), ("ស\u{17B6}\u{17D2}រ", 3.0), ("ទៅ", 3.0)])).unwrap();
+    assert_eq!(dict.check_normalized(khmer_normalize), ["ស\u{17B6}\u{17D2}រ"]);
+}
+
+#[test]
+fn checked_in_dictionary_is_normalized() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/tiny.kdict");
+    let dict = KDict::load(path).unwrap();
+    assert!(dict.check_normalized(khmer_normalize).is_empty());
+}
+
+#[test]
+fn memory_breakdown_adds_up_to_the_image() {
+    let bytes = dict_bytes(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)]);
+    let len = bytes.len();
+    let dict = KDict::from_bytes(bytes).unwrap();
+    let parts = dict.memory_breakdown();
+    assert_eq!(parts.header, 32);
+    assert_eq!(parts.table, 4 * 8);
+    assert_eq!(parts.slot_arrays, 0);
+    assert_eq!(parts.pool, 1 + "ខ្ញុំ\0ទៅ\0".len());
+    assert_eq!(parts.header + parts.table + parts.slot_arrays + parts.pool, len);
+    assert!(dict.memory_footprint() >= len);
+}
+
+#[test]
+fn mapped_footprint_is_the_file_size() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/tiny.kdict");
+    let dict = KDict::load(path).unwrap();
+    assert_eq!(dict.memory_footprint() as u64, std::fs::metadata(path).unwrap().len());
+}
+
+#[test]
+fn longest_match_at_prefers_the_longest_word() {
+    let dict = dict(&[("ខ្ញុំ", 3.0), ("ទៅ", 4.0), ("ទៅរៀន", 5.0)]);
+    let text = "ខ្ញុំទៅរៀនទៀត";
+    assert_eq!(dict.longest_match_at(text, 0), Some(("ខ្ញុំ".len(), 3.0)));
+    let at = "ខ្ញុំ".len();
+    assert_eq!(dict.longest_match_at(text, at), Some(("ទៅរៀន".len(), 5.0)));
+    assert_eq!(dict.longest_match_at(text, at + "ទៅរៀន".len()), None);
+    // Off a char boundary, or past the end
+    assert_eq!(dict.longest_match_at(text, 1), None);
+    assert_eq!(dict.longest_match_at(text, text.len() + 1), None);
+
+    let mut ends = Vec::new();
+    dict.for_each_match_at(text, at, 1, |end, _| ends.push(end - at), |_| {});
+    assert_eq!(ends, ["ទៅ".len(), "ទៅរៀន".len()]);
+    ends.clear();
+    dict.for_each_match_at(text, at, 3, |end, _| ends.push(end - at), |_| {});
+    assert_eq!(ends, ["ទៅរៀន".len()]);
+}
+
+#[test]
+fn shared_image_backs_every_dictionary_without_copies() {
+    let data: Arc<[u8]> = dict_bytes(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)]).into();
+    let a = KDict::from_arc(Arc::clone(&data)).unwrap();
+    let b = KDict::from_arc(Arc::clone(&data)).unwrap();
+    assert_eq!(a.header as *const u8, data.as_ptr());
+    assert_eq!(b.header as *const u8, data.as_ptr());
+    assert_eq!(Arc::strong_count(&data), 3);
+    assert_eq!(a.memory_footprint(), data.len());
+
+    let seg_a = KhmerSegmenter::new_with_dict(Some(a), SegmenterConfig::default());
+    let seg_b = KhmerSegmenter::new_with_dict(Some(b), SegmenterConfig::default());
+    assert_eq!(seg_a.segment("ខ្ញុំទៅ", Some("|")), "ខ្ញុំ|ទៅ");
+    assert_eq!(seg_b.segment("ខ្ញុំទៅ", Some("|")), "ខ្ញុំ|ទៅ");
+    drop((seg_a, seg_b));
+    assert_eq!(Arc::strong_count(&data), 1);
+
+    // Same checks as `from_bytes`
+    let truncated: Arc<[u8]> = data[..40].into();
+    assert_eq!(KDict::from_arc(truncated).err().unwrap().to_string(), "File truncated");
+}
+
+#[test]
+fn table_size_must_be_a_power_of_two() {
+    let mut bytes = header(0, 3);
+    bytes.extend_from_slice(&[0; 3 * 8 + 1]);
+    assert_eq!(load_error(bytes), "Table size 3 is not a power of two");
+}
+
+// A one-entry image with a 2-slot table whose entry points at `offset`,
+// followed by `pool`. The entry sits in the slot "ok" hashes to.
+fn one_entry(num_entries: u32, max_word_length: u32, offset: u32, pool: &[u8]) -> KDict {
+    let mut bytes = header(num_entries, 2);
+    bytes[24..28].copy_from_slice(&max_word_length.to_le_bytes());
+    let mut table = [0u8; 16];
+    let slot = (djb2_hash(b"ok") & 1) as usize * 8;
+    table[slot..slot + 4].copy_from_slice(&offset.to_le_bytes());
+    table[slot + 4..slot + 8].copy_from_slice(&3.0f32.to_le_bytes());
+    bytes.extend_from_slice(&table);
+    bytes.extend_from_slice(pool);
+    KDict::from_bytes(bytes).unwrap()
+}
+
+#[test]
+fn pool_reads_stop_at_the_end_of_the_image() {
+    // Offset past the pool, then a word with no NUL before the end
+    assert_eq!(one_entry(1, 8, 100, b"\0ok\0").get_pool_bytes(100), b"");
+    let unterminated = one_entry(1, 8, 1, b"\0ok");
+    assert_eq!(unterminated.get_pool_bytes(1), b"");
+    assert!(!unterminated.contains("ok"));
+    assert_eq!(unterminated.longest_match_at("ok", 0), None);
+    assert_eq!(one_entry(1, 8, 1, b"\0ok\0").longest_match_at("ok", 0), Some((2, 3.0)));
+}
+
+#[test]
+fn validate_accepts_built_dictionaries() {
+    assert_eq!(dict(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)]).validate(), Ok(()));
+    assert_eq!(dict(&[]).validate(), Ok(()));
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/tiny.kdict");
+    assert_eq!(KDict::load(path).unwrap().validate(), Ok(()));
+    assert_eq!(one_entry(1, 2, 1, b"\0ok\0").validate(), Ok(()));
+}
+
+#[test]
+fn validate_reports_what_loading_does_not_check() {
+    let err = |dict: KDict| dict.validate().unwrap_err();
+    let s = djb2_hash(b"ok") & 1;
+    assert_eq!(err(one_entry(1, 8, 100, b"\0ok\0")), format!("slot {s}: name offset 100 is past the pool (4 bytes)"));
+    assert_eq!(err(one_entry(1, 8, 1, b"\0ok")), format!("slot {s}: name offset 1 points at a word with no NUL before the pool ends"));
+    assert_eq!(err(one_entry(1, 8, 3, b"\0ok\0")), format!("slot {s}: name offset 3 points at an empty word"));
+    assert_eq!(err(one_entry(1, 1, 1, b"\0ok\0")), format!("slot {s}: word of 2 bytes exceeds max_word_length 1"));
+    assert_eq!(err(one_entry(2, 8, 1, b"\0ok\0")), "1 occupied slots but num_entries is 2");
+
+    let mut full = header(1, 1);
+    full.extend_from_slice(&1u32.to_le_bytes());
+    full.extend_from_slice(&3.0f32.to_le_bytes());
+    full.extend_from_slice(b"\0ok\0");
+    full[24..28].copy_from_slice(&2u32.to_le_bytes());
+    assert_eq!(err(KDict::from_bytes(full).unwrap()), "table has no empty slot, so failed lookups never end");
+}
+
+#[test]
+fn words_yields_every_live_entry_once() {
+    let entries = [("ខ្ញុំ", 3.0), ("ទៅ", 4.0), ("ផ្ទះ", 5.5), ("ok", 1.0)];
+    let dict = dict(&entries);
+    let mut words: Vec<(String, f32)> = dict.words().map(|(w, c)| (String::from_utf8(w.to_vec()).unwrap(), c)).collect();
+    words.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut expected: Vec<(String, f32)> = entries.iter().map(|&(w, c)| (w.to_string(), c)).collect();
+    expected.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(words, expected);
+}
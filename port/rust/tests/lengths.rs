@@ -0,0 +1,26 @@
+mod common;
+
+use common::segmenter;
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+use khmer_segmenter::utils::cluster_count;
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ស្រឡាញ់", 3.0), ("ខ្មែរ", 3.0)];
+
+#[test]
+fn byte_char_and_cluster_lengths_agree_on_tokens() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let text = "ខ្ញុំស្រឡាញ់ខ្មែរ ok";
+    assert_eq!(seg.segment_lengths(text), [15, 21, 15, 1, 2]);
+    assert_eq!(seg.segment_char_lengths(text), [5, 7, 5, 1, 2]);
+    assert_eq!(seg.segment_cluster_lengths(text), [1, 3, 2, 1, 2]);
+}
+
+#[test]
+fn cluster_count_groups_khmer_clusters() {
+    assert_eq!(cluster_count(""), 0);
+    assert_eq!(cluster_count("ស្ត្រី"), 1);
+    assert_eq!(cluster_count("ok"), 2);
+    // A combining mark is its own cluster unless graphemes are on
+    let accented = if cfg!(feature = "grapheme-clusters") { 1 } else { 2 };
+    assert_eq!(cluster_count("e\u{0301}"), accented);
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::segmenter;
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+use khmer_segmenter::normalization::{deprecated_fold_table, khmer_normalize, khmer_normalize_mapped_with, khmer_normalize_with, NormalizationConfig, OffsetMap};
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0), ("អាង", 3.0)];
+
+// Folds QAA (U+17A4) to QA + AA, an edit that grows the text.
+fn folding() -> NormalizationConfig {
+    NormalizationConfig { fold_table: deprecated_fold_table(), ..Default::default() }
+}
+
+fn folding_segmenter() -> KhmerSegmenter {
+    segmenter(WORDS, SegmenterConfig { normalization: folding(), ..Default::default() })
+}
+
+#[test]
+fn spans_map_back_over_deleted_characters() {
+    let raw = "ខ្ញុំ\u{200B}ទៅ";
+    let normalized = khmer_normalize(raw);
+    assert_eq!(normalized, "ខ្ញុំទៅ");
+
+    let mut map = OffsetMap::new();
+    map.push(15, 15);
+    map.push(18, 15);
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let spans = seg.segment_with_mapping(&normalized, &map);
+    assert_eq!(spans, [(0, 18), (18, 24)]);
+    assert_eq!(&raw[spans[1].0..spans[1].1], "ទៅ");
+}
+
+#[test]
+fn spans_map_back_over_expanded_characters() {
+    // QAA folds to QA + AA: 3 raw bytes become 6 normalized bytes
+    let raw = "\u{17A4}ង ទៅ";
+    let normalized = khmer_normalize_with(raw, &folding());
+    assert_eq!(normalized, "អាង ទៅ");
+
+    let map = OffsetMap::from_anchors(vec![(0, 0), (3, 6)]);
+    let seg = folding_segmenter();
+    let spans = seg.segment_with_mapping(&normalized, &map);
+    assert_eq!(spans, [(0, 6), (6, 7), (7, 13)]);
+    let tokens: Vec<&str> = spans.iter().map(|&(s, e)| &raw[s..e]).collect();
+    assert_eq!(tokens, ["\u{17A4}ង", " ", "ទៅ"]);
+}
+
+#[test]
+fn normalization_records_its_own_anchors() {
+    let (normalized, map) = khmer_normalize_mapped_with("\u{17A4}ង\u{200B}ទៅ", &folding());
+    assert_eq!(normalized, "អាងទៅ");
+    assert_eq!(map, OffsetMap::from_anchors(vec![(3, 6), (9, 9)]));
+}
+
+#[test]
+fn original_tokens_round_trip() {
+    let seg = folding_segmenter();
+    for raw in [
+        "ខ្ញុំ\u{200B}ទៅ",
+        "\u{200B}ខ្ញុំទៅ\u{200B}",
+        "\u{17A4}ង ទៅ",
+        "ទ\u{17C1}\u{17B8}ខ្ញុំ",
+        "\u{200B}",
+        "",
+    ] {
+        let tokens = seg.segment_original_tokens(raw);
+        assert_eq!(tokens.concat(), raw);
+        assert!(tokens.iter().all(|t| !t.is_empty()));
+        assert_eq!(seg.segment_original(raw, Some("")), raw);
+    }
+}
+
+#[test]
+fn original_tokens_absorb_edited_characters() {
+    let seg = folding_segmenter();
+    assert_eq!(seg.segment_original_tokens("ខ្ញុំ\u{200B}ទៅ"), ["ខ្ញុំ\u{200B}", "ទៅ"]);
+    assert_eq!(seg.segment_original_tokens("\u{200B}ខ្ញុំទៅ"), ["\u{200B}ខ្ញុំ", "ទៅ"]);
+    assert_eq!(seg.segment_original("\u{17A4}ង ទៅ", Some("|")), "\u{17A4}ង| |ទៅ");
+}
+
+#[test]
+fn empty_map_is_identity() {
+    let map = OffsetMap::new();
+    assert_eq!(map.to_raw(0), 0);
+    assert_eq!(map.to_raw(42), 42);
+}
+
+#[test]
+#[should_panic(expected = "in order")]
+fn anchors_must_be_ordered() {
+    let mut map = OffsetMap::new();
+    map.push(5, 5);
+    map.push(4, 6);
+}
+
+#[test]
+fn char_spans_count_both_units() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let raw = "ខ្ញុំ ទៅ";
+    let spans = seg.segment_char_spans(raw);
+    let summary: Vec<_> = spans.iter().map(|s| (s.byte_range(), s.char_range())).collect();
+    assert_eq!(summary, [(0..15, 0..5), (15..16, 5..6), (16..22, 6..8)]);
+
+    let chars: Vec<char> = raw.chars().collect();
+    for span in &spans {
+        assert_eq!(chars[span.char_range()].iter().collect::<String>(), &raw[span.byte_range()]);
+    }
+}
+
+#[test]
+fn char_spans_index_the_raw_text() {
+    let seg = folding_segmenter();
+    let spans = seg.segment_char_spans("\u{17A4}ង\u{200B}ទៅ");
+    let summary: Vec<_> = spans.iter().map(|s| (s.byte_range(), s.char_range())).collect();
+    assert_eq!(summary, [(0..9, 0..3), (9..15, 3..5)]);
+}
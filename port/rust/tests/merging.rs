@@ -0,0 +1,67 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::{SegmenterConfig, SeparatorPolicy};
+
+const WORDS: &[(&str, f32)] = &[("បាន", 3.0)];
+
+fn allow_hyphen() -> SegmenterConfig {
+    SegmenterConfig { merge_unknowns_across: SeparatorPolicy::Allow(vec!['-']), ..Default::default() }
+}
+
+#[test]
+fn never_merges_across_any_separator_by_default() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ក្សាក្សា-ខ្មាខ្មា"), ["ក្សាក្សា", "-", "ខ្មាខ្មា"]);
+    assert_eq!(tokens(&seg, "abc-def"), ["abc", "-", "def"]);
+    assert_eq!(tokens(&seg, "ក្សា។ខ្មា"), ["ក្សា", "។", "ខ្មា"]);
+    assert_eq!(tokens(&seg, "abc—def"), ["abc", "—", "def"]);
+}
+
+#[test]
+fn allowed_separator_joins_unknown_runs() {
+    let seg = segmenter(WORDS, allow_hyphen());
+    assert_eq!(tokens(&seg, "ក្សាក្សា-ខ្មាខ្មា"), ["ក្សាក្សា-ខ្មាខ្មា"]);
+    assert_eq!(tokens(&seg, "abc-def-ghi"), ["abc-def-ghi"]);
+}
+
+#[test]
+fn allowed_separator_stays_next_to_known_tokens() {
+    let seg = segmenter(WORDS, allow_hyphen());
+    assert_eq!(tokens(&seg, "ក្សា-បាន"), ["ក្សា", "-", "បាន"]);
+    assert_eq!(tokens(&seg, "ក្សា-"), ["ក្សា", "-"]);
+    assert_eq!(tokens(&seg, "ក្សា--ខ្មា"), ["ក្សា", "-", "-", "ខ្មា"]);
+    assert_eq!(tokens(&seg, "abc.def"), ["abc", ".", "def"]);
+}
+
+#[test]
+fn allowed_separator_does_not_bridge_scripts() {
+    let seg = segmenter(WORDS, allow_hyphen());
+    assert_eq!(tokens(&seg, "ក្សា-abc"), ["ក្សា", "-", "abc"]);
+}
+
+#[test]
+fn short_bare_letters_merge_into_a_neighbouring_word() {
+    let words = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)];
+    let config = SegmenterConfig { min_merge_char_len: 2, ..Default::default() };
+    let seg = segmenter(words, config);
+    assert_eq!(tokens(&seg, "ខ្ញុំកទៅ"), ["ខ្ញុំក", "ទៅ"]);
+    assert_eq!(tokens(&seg, "កទៅ"), ["កទៅ"]);
+    // Standalone between separators
+    assert_eq!(tokens(&seg, "ទៅ ក ទៅ"), ["ទៅ", " ", "ក", " ", "ទៅ"]);
+    assert_eq!(tokens(&seg, "ក ១"), ["ក", " ", "១"]);
+}
+
+#[test]
+fn short_dictionary_words_are_not_merged() {
+    let words = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0), ("ក", 3.0)];
+    let config = SegmenterConfig { min_merge_char_len: 2, ..Default::default() };
+    let seg = segmenter(words, config);
+    assert_eq!(tokens(&seg, "ខ្ញុំកទៅ"), ["ខ្ញុំ", "ក", "ទៅ"]);
+}
+
+#[test]
+fn short_letters_stay_by_default() {
+    let seg = segmenter(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)], SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ខ្ញុំកទៅ"), ["ខ្ញុំ", "ក", "ទៅ"]);
+}
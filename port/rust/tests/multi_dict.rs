@@ -0,0 +1,48 @@
+mod common;
+
+use common::{dict, dict_bytes, tokens};
+use khmer_segmenter::kdict::build::merge;
+use khmer_segmenter::kdict::KDict;
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+
+// Writes `words` as a .kdict under the temp dir, named after the test.
+fn write_dict(name: &str, words: &[(&str, f32)]) -> String {
+    let path = std::env::temp_dir().join(format!("khmer_segmenter_{}_{}.kdict", name, std::process::id()));
+    std::fs::write(&path, dict_bytes(words)).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn merge_keeps_the_lowest_cost_and_the_longest_word() {
+    let base = dict(&[("ខ្ញុំ", 3.0), ("ទៅ", 6.0)]);
+    let extra = dict(&[("ទៅ", 2.0), ("សាកលវិទ្យាល័យ", 5.0)]);
+    let merged = KDict::from_bytes(merge(&[&base, &extra]).unwrap()).unwrap();
+
+    let cost = |w: &str| merged.lookup(w.as_bytes()).map(|slot| merged.entry(slot).cost);
+    assert_eq!(cost("ខ្ញុំ"), Some(3.0));
+    assert_eq!(cost("ទៅ"), Some(2.0));
+    assert_eq!(cost("សាកលវិទ្យាល័យ"), Some(5.0));
+    let h = unsafe { *merged.header };
+    assert_eq!({ h.num_entries }, 3);
+    assert_eq!({ h.max_word_length } as usize, "សាកលវិទ្យាល័យ".len());
+    assert!(merge(&[]).is_none());
+}
+
+#[test]
+fn new_multi_segments_against_every_dictionary() {
+    let base = write_dict("multi_base", &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)]);
+    let addon = write_dict("multi_addon", &[("ឆ្កែ", 4.0), ("សាកលវិទ្យាល័យ", 5.0)]);
+
+    let seg = KhmerSegmenter::new_multi(&[&base, &addon], SegmenterConfig::default()).unwrap();
+    assert_eq!(tokens(&seg, "ខ្ញុំទៅសាកលវិទ្យាល័យ"), ["ខ្ញុំ", "ទៅ", "សាកលវិទ្យាល័យ"]);
+    // The add-on word stays known, so unknown-merging leaves it apart
+    assert_eq!(tokens(&seg, "ឆ្កែឆ្មា"), ["ឆ្កែ", "ឆ្មា"]);
+
+    let single = KhmerSegmenter::new_multi(&[&base], SegmenterConfig::default()).unwrap();
+    assert_eq!(tokens(&single, "ឆ្កែឆ្មា"), ["ឆ្កែឆ្មា"]);
+    assert!(KhmerSegmenter::new_multi(&[&base, "no/such/file.kdict"], SegmenterConfig::default()).is_err());
+
+    drop((seg, single));
+    std::fs::remove_file(base).unwrap();
+    std::fs::remove_file(addon).unwrap();
+}
@@ -0,0 +1,103 @@
+use khmer_segmenter::normalization::{deprecated_fold_table, khmer_normalize, khmer_normalize_checked, khmer_normalize_checked_with, khmer_normalize_with, NormalizationConfig};
+
+#[test]
+fn checked_reports_untouched_text() {
+    let (out, changed) = khmer_normalize_checked("ខ្ញុំស្រឡាញ់ hello");
+    assert_eq!(out, "ខ្ញុំស្រឡាញ់ hello");
+    assert!(!changed);
+}
+
+#[test]
+fn checked_reports_each_kind_of_edit() {
+    // Zero-width space stripped
+    assert!(khmer_normalize_checked("ក\u{200B}ខ").1);
+    // e + ii merged into oe
+    assert!(khmer_normalize_checked("ក\u{17C1}\u{17B8}").1);
+    // Vowel typed before the subscript gets reordered
+    assert!(khmer_normalize_checked("ក\u{17B6}\u{17D2}\u{178F}").1);
+    // Deprecated form folded, when asked to
+    let folding = NormalizationConfig { fold_table: deprecated_fold_table(), ..Default::default() };
+    assert!(khmer_normalize_checked_with("\u{17A3}", &folding).1);
+    assert!(!khmer_normalize_checked("\u{17A3}").1);
+}
+
+#[test]
+fn checked_output_matches_plain_normalize() {
+    for s in ["ក\u{17B6}\u{17D2}\u{178F}", "ក\u{200B}ខ", "ខ្ញុំ", ""] {
+        assert_eq!(khmer_normalize_checked(s).0, khmer_normalize(s));
+    }
+}
+
+#[test]
+fn long_coeng_chain_terminates_and_is_preserved() {
+    let chain = format!("ក{}", "\u{17D2}ក".repeat(100_000));
+    let start = std::time::Instant::now();
+    let (out, changed) = khmer_normalize_checked(&chain);
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(out, chain);
+    assert!(!changed);
+}
+
+#[test]
+fn cluster_cap_flushes_excess_parts() {
+    // Vowel typed before the subscript: reordered while both fit in one cluster
+    let text = "ក\u{17B6}\u{17D2}\u{179A}";
+    assert_eq!(khmer_normalize(text), "ក\u{17D2}\u{179A}\u{17B6}");
+
+    let config = NormalizationConfig { max_cluster_parts: 2, ..Default::default() };
+    assert_eq!(khmer_normalize_with(text, &config), text);
+}
+
+#[test]
+fn strips_common_invisibles_by_default() {
+    assert_eq!(khmer_normalize("ខ្មែ\u{00AD}រ"), "ខ្មែរ");
+    assert_eq!(khmer_normalize("ក\u{FEFF}ខ"), "កខ");
+    assert!(khmer_normalize_checked("ក\u{00AD}").1);
+}
+
+#[test]
+fn word_joiner_is_kept_unless_opted_in() {
+    assert_eq!(khmer_normalize("ក\u{2060}ខ"), "ក\u{2060}ខ");
+
+    let mut config = NormalizationConfig::default();
+    config.strip_chars.push('\u{2060}');
+    assert_eq!(khmer_normalize_with("ក\u{2060}ខ", &config), "កខ");
+
+    let keep_all = NormalizationConfig { strip_chars: Vec::new(), ..Default::default() };
+    assert_eq!(khmer_normalize_with("ក\u{00AD}ខ", &keep_all), "ក\u{00AD}ខ");
+}
+
+#[test]
+fn two_part_clusters_are_canonical_as_typed() {
+    // Base plus a single shifter, vowel, sign or subscript: nothing to reorder
+    for text in ["ស\u{17CA}", "ម\u{17C9}", "ស\u{17B8}", "ក\u{17CB}", "ក\u{17D2}\u{178F}"] {
+        assert_eq!(khmer_normalize_checked(text), (text.to_string(), false));
+    }
+    // A mark with no base is not a cluster, so swapping it across the base
+    // changes the text rather than its order
+    assert_eq!(khmer_normalize("\u{17CA}ស"), "\u{17CA}ស");
+}
+
+#[test]
+fn register_shifter_and_vowel_normalize_identically_in_either_order() {
+    for (canonical, swapped) in [("ស\u{17CA}\u{17B8}", "ស\u{17B8}\u{17CA}"), ("ម\u{17C9}\u{17BC}", "ម\u{17BC}\u{17C9}")] {
+        assert_eq!(khmer_normalize_checked(canonical), (canonical.to_string(), false));
+        assert_eq!(khmer_normalize_checked(swapped), (canonical.to_string(), true));
+    }
+}
+
+#[test]
+fn fold_table_is_empty_by_default_and_user_extensible() {
+    assert_eq!(khmer_normalize("\u{17A4}ង \u{17D8}"), "\u{17A4}ង \u{17D8}");
+
+    let mut config = NormalizationConfig::default();
+    config.fold_table.insert('\u{17A4}', "\u{17A2}\u{17B6}".to_string());
+    // A legacy-encoding form, folded to a sequence that is reordered as usual
+    config.fold_table.insert('X', "\u{17B6}\u{17D2}\u{178F}".to_string());
+    assert_eq!(khmer_normalize_with("\u{17A4}ង", &config), "អាង");
+    assert_eq!(khmer_normalize_with("កX", &config), "ក\u{17D2}\u{178F}\u{17B6}");
+    assert_eq!(khmer_normalize_with("\u{17D8}", &config), "\u{17D8}");
+
+    let config = NormalizationConfig { fold_table: deprecated_fold_table(), ..Default::default() };
+    assert_eq!(khmer_normalize_with("\u{17D8}\u{17B4}", &config), "។ល។");
+}
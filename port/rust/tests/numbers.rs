@@ -0,0 +1,185 @@
+mod common;
+
+use common::segmenter;
+use khmer_segmenter::khmer_segmenter::{DottedNumbers, SegmenterConfig};
+use khmer_segmenter::utils::{get_number_length, get_number_length_with, parse_number, parse_number_with, DecimalStyle};
+
+#[test]
+fn parses_ascii_and_khmer_digits() {
+    assert_eq!(parse_number("42"), Some(42.0));
+    assert_eq!(parse_number("១២៣"), Some(123.0));
+    assert_eq!(parse_number("10,000.50"), Some(10000.5));
+    assert_eq!(parse_number("១,០០០.៥"), Some(1000.5));
+}
+
+#[test]
+fn rejects_malformed_and_partial_numbers() {
+    assert_eq!(parse_number(""), None);
+    assert_eq!(parse_number("1.2.3"), None);
+    assert_eq!(parse_number("1.000,5"), None);
+    assert_eq!(parse_number("50$"), None);
+    assert_eq!(parse_number("abc"), None);
+}
+
+#[test]
+fn overflow_is_none() {
+    assert_eq!(parse_number(&"9".repeat(400)), None);
+}
+
+#[test]
+fn tags_number_tokens_only() {
+    let seg = segmenter(&[("ដុល្លារ", 3.0)], SegmenterConfig::default());
+    let tagged = seg.segment_numbers("១០០ដុល្លារ $10,000.00");
+    assert_eq!(tagged, [
+        ("១០០".to_string(), Some(100.0)),
+        ("ដុល្លារ".to_string(), None),
+        (" ".to_string(), None),
+        ("$".to_string(), None),
+        ("10,000.00".to_string(), Some(10000.0)),
+    ]);
+}
+
+#[test]
+fn dotted_ids_are_one_token_by_default() {
+    let seg = segmenter(&[], SegmenterConfig::default());
+    assert_eq!(common::tokens(&seg, "192.168.0.1"), ["192.168.0.1"]);
+    assert_eq!(common::tokens(&seg, "1.2.3."), ["1.2.3", "."]);
+    assert_eq!(common::tokens(&seg, "១.២.៣"), ["១.២.៣"]);
+    assert_eq!(parse_number("192.168.0.1"), None);
+}
+
+#[test]
+fn dotted_ids_split_into_components_when_configured() {
+    let config = SegmenterConfig { dotted_numbers: DottedNumbers::Split, ..Default::default() };
+    let seg = segmenter(&[], config);
+    assert_eq!(common::tokens(&seg, "192.168.0.1"), ["192", ".", "168", ".", "0", ".", "1"]);
+    assert_eq!(common::tokens(&seg, "v1.2.3."), ["v", "1", ".", "2", ".", "3", "."]);
+    // Plain decimals and grouping are untouched
+    assert_eq!(common::tokens(&seg, "3.14 10,000.50"), ["3.14", " ", "10,000.50"]);
+    assert_eq!(common::tokens(&seg, "1. 2.5"), ["1", ".", " ", "2.5"]);
+}
+
+#[test]
+fn number_length_corner_cases() {
+    let cases: &[(&str, &str)] = &[
+        ("1", "1"),
+        ("1.", "1"),
+        ("1,", "1"),
+        ("1.2", "1.2"),
+        ("1.2.3", "1.2.3"),
+        ("1.2.3.", "1.2.3"),
+        ("1,,2", "1"),
+        ("1.,2", "1"),
+        ("1,.2", "1"),
+        ("1,000,000", "1,000,000"),
+        ("1 000", "1"),
+        ("១.", "១"),
+        ("១,២", "១"),
+        ("1,5", "1"),
+        ("1,0000", "1"),
+        ("1,000.5", "1,000.5"),
+        ("1.5,000", "1.5"),
+        ("១.២.៣", "១.២.៣"),
+        ("1.២", "1.២"),
+        ("១,000", "១,000"),
+        ("១a", "១"),
+        (".1", ""),
+        (",1", ""),
+        ("", ""),
+        ("a1", ""),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(get_number_length(input), expected.len(), "input {:?}", input);
+    }
+}
+
+// Every string of up to five symbols from a small alphabet mixing ASCII and
+// Khmer digits, separators and non-digits
+#[test]
+fn number_length_lands_on_digit_boundaries() {
+    let alphabet = ['1', '\u{17E1}', '.', ',', 'a', ' ', '\u{1780}'];
+    let mut inputs = vec![String::new()];
+    let mut frontier = vec![String::new()];
+    for _ in 0..5 {
+        frontier = frontier.iter()
+            .flat_map(|s| alphabet.iter().map(move |c| format!("{}{}", s, c)))
+            .collect();
+        inputs.extend(frontier.iter().cloned());
+    }
+    for (input, style) in inputs.iter().flat_map(|s| [(s, DecimalStyle::Dot), (s, DecimalStyle::Comma)]) {
+        let len = get_number_length_with(input, style);
+        assert!(len <= input.len() && input.is_char_boundary(len), "input {:?} -> {}", input, len);
+        let number = &input[..len];
+        let first_is_digit = input.chars().next().is_some_and(|c| c == '1' || c == '\u{17E1}');
+        assert_eq!(len > 0, first_is_digit, "input {:?}", input);
+        if len > 0 {
+            // Ends on a digit and never has two separators in a row
+            let last = number.chars().last().unwrap();
+            assert!(last == '1' || last == '\u{17E1}', "input {:?}", input);
+            assert!(!number.contains(".,") && !number.contains(",.") && !number.contains("..") && !number.contains(",,"));
+        }
+    }
+}
+
+#[test]
+fn number_length_follows_the_decimal_style() {
+    // (input, number under Dot, number under Comma)
+    let cases: &[(&str, &str, &str)] = &[
+        ("1,000", "1,000", "1,000"),
+        ("1,5", "1", "1,5"),
+        ("1.000", "1.000", "1.000"),
+        ("1.5", "1.5", "1"),
+        ("1,000.50", "1,000.50", "1,000"),
+        ("10.000,50", "10.000", "10.000,50"),
+        ("1.000.000,50", "1.000.000,50", "1.000.000,50"),
+        ("1.000.00,5", "1.000.00", "1.000"),
+        ("១.០០០.០០០,៥", "១.០០០.០០០,៥", "១.០០០.០០០,៥"),
+        ("1.2.3", "1.2.3", "1"),
+        ("1,2,3", "1", "1,2,3"),
+    ];
+    for (input, dot, comma) in cases {
+        assert_eq!(get_number_length_with(input, DecimalStyle::Dot), dot.len(), "input {:?} under Dot", input);
+        assert_eq!(get_number_length_with(input, DecimalStyle::Comma), comma.len(), "input {:?} under Comma", input);
+    }
+}
+
+#[test]
+fn token_boundaries_follow_the_configured_convention() {
+    let dot = segmenter(&[], SegmenterConfig::default());
+    assert_eq!(common::tokens(&dot, "1,5 10.000,50"), ["1", ",", "5", " ", "10.000", ",", "50"]);
+    assert_eq!(common::tokens(&dot, "1.000.000,50"), ["1.000.000,50"]);
+
+    let config = SegmenterConfig { decimal_convention: DecimalStyle::Comma, ..Default::default() };
+    let comma = segmenter(&[], config);
+    assert_eq!(common::tokens(&comma, "1,5 10.000,50"), ["1,5", " ", "10.000,50"]);
+    assert_eq!(common::tokens(&comma, "1.5 1,000.50"), ["1", ".", "5", " ", "1,000", ".", "50"]);
+}
+
+#[test]
+fn dot_grouped_numbers_read_as_grouping_under_dot() {
+    assert_eq!(parse_number("1.000.000"), Some(1_000_000.0));
+    assert_eq!(parse_number("1.000.000,50"), Some(1_000_000.5));
+    assert_eq!(parse_number("1.000.00"), None);
+}
+
+#[test]
+fn comma_decimal_convention_swaps_the_marks() {
+    assert_eq!(parse_number_with("1.000", DecimalStyle::Comma), Some(1000.0));
+    assert_eq!(parse_number_with("1.000", DecimalStyle::Dot), Some(1.0));
+    assert_eq!(parse_number_with("10.000,50", DecimalStyle::Comma), Some(10000.5));
+    assert_eq!(parse_number_with("១.០០០,៥", DecimalStyle::Comma), Some(1000.5));
+    assert_eq!(parse_number_with("1.000.000", DecimalStyle::Comma), Some(1_000_000.0));
+    assert_eq!(parse_number_with("1,5,3", DecimalStyle::Comma), None);
+    assert_eq!(parse_number_with("1,000.5", DecimalStyle::Comma), None);
+}
+
+#[test]
+fn segment_numbers_follows_the_configured_convention() {
+    let config = SegmenterConfig { decimal_convention: DecimalStyle::Comma, ..Default::default() };
+    let seg = segmenter(&[], config);
+    assert_eq!(seg.segment_numbers("1.000 2,5"), [
+        ("1.000".to_string(), Some(1000.0)),
+        (" ".to_string(), None),
+        ("2,5".to_string(), Some(2.5)),
+    ]);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, OrphanedVowels, SegmenterConfig, TokenKind};
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)];
+
+fn flagging() -> KhmerSegmenter {
+    let config = SegmenterConfig { orphaned_vowels: OrphanedVowels::Flag, ..Default::default() };
+    segmenter(WORDS, config)
+}
+
+fn kinds(seg: &KhmerSegmenter, text: &str) -> Vec<(String, TokenKind)> {
+    seg.segment_detailed(text).into_iter().map(|t| (t.text, t.kind)).collect()
+}
+
+#[test]
+fn repair_folds_orphans_into_the_previous_token_by_default() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ខ្ញុំ ាទៅ"), ["ខ្ញុំ", " ", "ា", "ទៅ"]);
+    assert_eq!(tokens(&seg, "okាទៅ"), ["okា", "ទៅ"]);
+    assert_eq!(tokens(&flagging(), "okាទៅ"), ["ok", "ា", "ទៅ"]);
+}
+
+#[test]
+fn flag_keeps_each_orphan_as_its_own_token() {
+    let seg = flagging();
+    let orphan = |t: &str| (t.to_string(), TokenKind::Orphaned);
+    let word = |t: &str| (t.to_string(), TokenKind::Word);
+    assert_eq!(kinds(&seg, "ខ្ញុំ ាទៅ"), [word("ខ្ញុំ"), (" ".to_string(), TokenKind::Separator), orphan("ា"), word("ទៅ")]);
+    // At the start of the line, with no base anywhere before it
+    assert_eq!(kinds(&seg, "ាទៅ"), [orphan("ា"), word("ទៅ")]);
+    // Consecutive orphans are counted one by one
+    assert_eq!(kinds(&seg, "ាេទៅ"), [orphan("ា"), orphan("េ"), word("ទៅ")]);
+}
+
+#[test]
+fn flag_needs_repair_mode() {
+    let config = SegmenterConfig { orphaned_vowels: OrphanedVowels::Flag, enable_repair_mode: false, ..Default::default() };
+    let seg = segmenter(WORDS, config);
+    assert!(kinds(&seg, "ាទៅ").iter().all(|(_, kind)| *kind != TokenKind::Orphaned));
+}
+
+#[test]
+fn dictionary_matches_stop_short_of_an_orphan() {
+    // Entries that would swallow the orphan whole
+    let words: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0), ("okា", 1.0), ("ខ្ញុំ ាទៅ", 1.0)];
+    let config = SegmenterConfig { orphaned_vowels: OrphanedVowels::Flag, ..Default::default() };
+    let seg = segmenter(words, config);
+    let orphan = ("ា".to_string(), TokenKind::Orphaned);
+    assert_eq!(kinds(&seg, "okាទៅ")[1..], [orphan.clone(), ("ទៅ".to_string(), TokenKind::Word)]);
+    assert_eq!(kinds(&seg, "ខ្ញុំ ាទៅ")[2], orphan);
+    // A vowel after its base is no orphan, so words spanning it still match
+    assert_eq!(tokens(&seg, "ទៅទៅ"), ["ទៅ", "ទៅ"]);
+
+    // Without repair mode nothing is isolated and the entries match
+    let config = SegmenterConfig { enable_repair_mode: false, ..Default::default() };
+    let seg = segmenter(words, config);
+    assert_eq!(tokens(&seg, "okាទៅ"), ["okា", "ទៅ"]);
+    assert_eq!(tokens(&seg, "ខ្ញុំ ាទៅ"), ["ខ្ញុំ ាទៅ"]);
+}
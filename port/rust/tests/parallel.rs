@@ -0,0 +1,48 @@
+// `segment` is pure, so running it across threads over one shared segmenter
+// and its memory-mapped dictionary must give exactly the sequential results.
+// Guards against hidden shared mutable state and the manual `Send`/`Sync`
+// impls on `KDict`.
+
+use std::fs;
+use std::path::PathBuf;
+
+use khmer_segmenter::kdict::KDict;
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+use rayon::prelude::*;
+
+fn data(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data").join(name)
+}
+
+// Every regression input, plus its suffixes from every 5th char, so threads
+// work on many different lengths and starting clusters at once.
+fn corpus() -> Vec<String> {
+    let text = fs::read_to_string(data("regression.tsv")).unwrap();
+    let mut lines = Vec::new();
+    for line in text.lines().filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        let input = line.split_once('\t').unwrap().0;
+        lines.extend(input.char_indices().step_by(5).map(|(i, _)| input[i..].to_string()));
+    }
+    lines
+}
+
+#[test]
+fn parallel_and_sequential_segmentation_agree() {
+    let kdict = KDict::load(data("tiny.kdict").to_str().unwrap()).unwrap();
+    let seg = KhmerSegmenter::new_with_dict(Some(kdict), SegmenterConfig::default());
+    let lines = corpus();
+    assert!(lines.len() > 100);
+
+    let sequential: Vec<String> = lines.iter().map(|l| seg.segment(l, Some("|"))).collect();
+    let detailed: Vec<_> = lines.iter().map(|l| seg.segment_detailed(l)).collect();
+
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+    for _ in 0..3 {
+        let parallel: Vec<String> = pool.install(|| lines.par_iter().map(|l| seg.segment(l, Some("|"))).collect());
+        for (k, (seq, par)) in sequential.iter().zip(&parallel).enumerate() {
+            assert_eq!(seq, par, "line {k} differs between sequential and parallel runs");
+        }
+        let parallel_detailed: Vec<_> = pool.install(|| lines.par_iter().map(|l| seg.segment_detailed(l)).collect());
+        assert!(detailed == parallel_detailed, "detailed tokens differ between sequential and parallel runs");
+    }
+}
@@ -0,0 +1,20 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+
+const WORDS: &[(&str, f32)] = &[("ភ្នំ", 4.0), ("ពេញ", 4.0), ("ភ្នំ ពេញ", 5.0), ("ទៅ", 3.0)];
+
+#[test]
+fn entry_with_internal_space_is_matched_and_preferred() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ទៅភ្នំ ពេញ"), ["ទៅ", "ភ្នំ ពេញ"]);
+    assert_eq!(tokens(&seg, "ភ្នំ ពេញ ទៅ"), ["ភ្នំ ពេញ", " ", "ទៅ"]);
+}
+
+#[test]
+fn split_path_is_kept_when_cheaper() {
+    let words = &[("ភ្នំ", 2.0), ("ពេញ", 2.0), ("ភ្នំ ពេញ", 9.0)];
+    let seg = segmenter(words, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ភ្នំ ពេញ"), ["ភ្នំ", " ", "ពេញ"]);
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::segmenter;
+use khmer_segmenter::kdict::KDict;
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+
+#[test]
+fn matches_whole_line_segmentation_on_corpus() {
+    let data = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data");
+    let kdict = KDict::load(data.join("tiny.kdict").to_str().unwrap()).unwrap();
+    let seg = KhmerSegmenter::new_with_dict(Some(kdict), SegmenterConfig::default());
+
+    let corpus = fs::read_to_string(data.join("regression.tsv")).unwrap();
+    for line in corpus.lines().filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        let input = line.split_once('\t').unwrap().0;
+        assert_eq!(seg.segment_presplit(input, Some("|")), seg.segment(input, Some("|")), "{}", input);
+    }
+}
+
+#[test]
+fn boundaries_are_configurable() {
+    let words = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)];
+    let config = SegmenterConfig { presplit_boundaries: vec![','], ..Default::default() };
+    let seg = segmenter(words, config);
+    assert_eq!(seg.segment_presplit("ខ្ញុំ,ទៅ ខ្ញុំ", Some("|")), "ខ្ញុំ|,|ទៅ| |ខ្ញុំ");
+    assert_eq!(seg.segment_presplit(",,", Some("|")), ",|,");
+    assert_eq!(seg.segment_presplit("", Some("|")), "");
+}
+
+#[test]
+fn phrases_do_not_match_across_a_boundary() {
+    let words = &[("ថ្ងៃ នេះ", 1.0), ("ថ្ងៃ", 3.0), ("នេះ", 3.0)];
+    let seg = segmenter(words, SegmenterConfig::default());
+    assert_eq!(seg.segment("ថ្ងៃ នេះ", Some("|")), "ថ្ងៃ នេះ");
+    assert_eq!(seg.segment_presplit("ថ្ងៃ នេះ", Some("|")), "ថ្ងៃ| |នេះ");
+}
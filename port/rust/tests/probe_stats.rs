@@ -0,0 +1,41 @@
+#![cfg(feature = "probe-stats")]
+
+mod common;
+
+use common::segmenter;
+use khmer_segmenter::kdict::{ProbeCounter, ProbeStats};
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0), ("សាលា", 3.0)];
+
+#[test]
+fn counter_tallies_collisions() {
+    let counter = ProbeCounter::default();
+    counter.record(1);
+    counter.record(3);
+    counter.record(2);
+    let stats = counter.take();
+    assert_eq!(stats, ProbeStats { lookups: 3, probes: 6, collisions: 2 });
+    assert_eq!(stats.mean_probes(), 2.0);
+    assert_eq!(counter.snapshot(), ProbeStats::default());
+}
+
+#[test]
+fn segmentation_records_dp_lookups() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(seg.probe_stats(), ProbeStats::default());
+
+    let _ = seg.segment("ខ្ញុំទៅសាលា", None);
+    let one = seg.take_probe_stats();
+    assert!(one.lookups > 0);
+    assert!(one.probes >= one.lookups);
+    assert_eq!(seg.probe_stats(), ProbeStats::default());
+
+    // A batch accumulates across calls
+    for _ in 0..3 {
+        let _ = seg.segment("ខ្ញុំទៅសាលា", None);
+    }
+    let batch = seg.take_probe_stats();
+    assert_eq!(batch.lookups, one.lookups * 3);
+    assert_eq!(batch.probes, one.probes * 3);
+}
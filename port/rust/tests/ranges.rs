@@ -0,0 +1,74 @@
+mod common;
+
+use common::segmenter;
+use khmer_segmenter::khmer_segmenter::{Fallback, KhmerSegmenter, SegmentError, SegmenterConfig};
+use khmer_segmenter::normalization::khmer_normalize;
+
+#[test]
+fn ranges_slice_the_input() {
+    let seg = segmenter(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)], SegmenterConfig::default());
+    let text = "ខ្ញុំទៅ ១២";
+    let ranges = seg.segment_ranges(text);
+    assert_eq!(ranges, [0..15, 15..21, 21..22, 22..28]);
+
+    let tokens: Vec<&str> = ranges.iter().map(|r| &text[r.clone()]).collect();
+    assert_eq!(tokens, ["ខ្ញុំ", "ទៅ", " ", "១២"]);
+    assert_eq!(ranges.last().unwrap().end, text.len());
+}
+
+#[test]
+fn spans_are_what_segment_joins_and_report_normalization() {
+    let seg = segmenter(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0), ("ផ្ទះ", 3.0)], SegmenterConfig::default());
+
+    let text = "ខ្ញុំទៅ ផ្ទះ";
+    let (spans, changed) = seg.segment_spans(text);
+    assert!(!changed);
+    let tokens: Vec<&str> = spans.iter().map(|&(s, e)| &text[s..e]).collect();
+    assert_eq!(tokens.join("|"), seg.segment(text, Some("|")));
+
+    // A zero-width space is stripped, so the spans index the normalized text
+    let raw = "ខ្ញុំ\u{200B}ទៅ";
+    let (spans, changed) = seg.segment_spans(raw);
+    assert!(changed);
+    let normalized = khmer_normalize(raw);
+    let tokens: Vec<&str> = spans.iter().map(|&(s, e)| &normalized[s..e]).collect();
+    assert_eq!(tokens, ["ខ្ញុំ", "ទៅ"]);
+
+    let config = SegmenterConfig { enable_normalization: false, ..Default::default() };
+    let seg = segmenter(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)], config);
+    assert!(!seg.segment_spans(raw).1);
+}
+
+#[test]
+fn tokens_borrow_the_input_unless_normalization_changed_it() {
+    let seg = segmenter(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)], SegmenterConfig::default());
+    let text = "ខ្ញុំទៅ ១២";
+    let tokens = seg.segment_tokens(text).unwrap();
+    assert_eq!(tokens, ["ខ្ញុំ", "ទៅ", " ", "១២"]);
+    assert_eq!(tokens[1].as_ptr(), text[15..].as_ptr());
+
+    let raw = "ខ្ញុំ\u{200B}ទៅ";
+    assert_eq!(seg.segment_tokens(raw), Err(SegmentError::NormalizationRequired));
+
+    let config = SegmenterConfig { enable_normalization: false, ..Default::default() };
+    let seg = segmenter(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)], config);
+    assert_eq!(seg.segment_tokens(raw).unwrap().concat(), raw);
+}
+
+#[test]
+fn try_segment_reports_what_segment_papers_over() {
+    let seg = segmenter(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)], SegmenterConfig::default());
+    assert_eq!(seg.try_segment("ខ្ញុំទៅ", Some("|")).unwrap(), "ខ្ញុំ|ទៅ");
+    assert_eq!(seg.try_segment("", Some("|")).unwrap(), "");
+
+    let bare = KhmerSegmenter::new_with_dict(None, SegmenterConfig::default());
+    assert_eq!(bare.try_segment("ខ្ញុំទៅ", Some("|")), Err(SegmentError::NoDictionary));
+    // `segment` keeps returning the text whole
+    assert_eq!(bare.segment("ខ្ញុំទៅ", Some("|")), "ខ្ញុំទៅ");
+    // Text without Khmer needs no dictionary once a fallback takes it
+    let config = SegmenterConfig { non_khmer_fallback: Fallback::WhitespaceSplit, ..Default::default() };
+    let bare = KhmerSegmenter::new_with_dict(None, config);
+    assert_eq!(bare.try_segment("hi there", Some("|")).unwrap(), "hi| |there");
+    assert_eq!(bare.try_segment("ខ្ញុំ", Some("|")), Err(SegmentError::NoDictionary));
+    assert_eq!(SegmentError::NoDictionary.to_string(), "no dictionary loaded");
+}
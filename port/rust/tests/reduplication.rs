@@ -0,0 +1,50 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::{Reduplication, SegmenterConfig, TokenKind};
+
+const WORDS: &[(&str, f32)] = &[("ផ្សេង", 3.0), ("ក្មេង", 3.0), ("លេង", 3.0)];
+
+fn merging() -> SegmenterConfig {
+    SegmenterConfig { reduplication: Reduplication::Merge, ..Default::default() }
+}
+
+#[test]
+fn split_by_default() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "ផ្សេងៗ"), ["ផ្សេង", "ៗ"]);
+    assert_eq!(tokens(&seg, "ក្មេងក្មេង"), ["ក្មេង", "ក្មេង"]);
+}
+
+#[test]
+fn repetition_sign_joins_the_word_before_it() {
+    let seg = segmenter(WORDS, merging());
+    assert_eq!(tokens(&seg, "ក្មេងលេងផ្សេងៗ"), ["ក្មេង", "លេង", "ផ្សេងៗ"]);
+    let detailed = seg.segment_detailed("ផ្សេងៗ");
+    assert_eq!(detailed.len(), 1);
+    assert_eq!(detailed[0].kind, TokenKind::Reduplication);
+}
+
+#[test]
+fn full_repetition_is_one_token() {
+    let seg = segmenter(WORDS, merging());
+    assert_eq!(tokens(&seg, "ក្មេងក្មេងលេង"), ["ក្មេងក្មេង", "លេង"]);
+    assert_eq!(seg.segment_detailed("ក្មេងក្មេង")[0].kind, TokenKind::Reduplication);
+}
+
+#[test]
+fn repetition_sign_without_a_word_stays_a_separator() {
+    let seg = segmenter(WORDS, merging());
+    assert_eq!(tokens(&seg, "ៗលេង"), ["ៗ", "លេង"]);
+    assert_eq!(tokens(&seg, "លេង ៗ"), ["លេង", " ", "ៗ"]);
+    assert_eq!(tokens(&seg, "១ៗ"), ["១", "ៗ"]);
+    assert_eq!(seg.segment_detailed("ៗ")[0].kind, TokenKind::Separator);
+}
+
+#[test]
+fn no_merge_across_sentence_boundaries() {
+    let seg = segmenter(WORDS, merging());
+    assert_eq!(tokens(&seg, "ក្មេង។ក្មេង"), ["ក្មេង", "។", "ក្មេង"]);
+    assert_eq!(tokens(&seg, "ក្មេង ក្មេង"), ["ក្មេង", " ", "ក្មេង"]);
+    assert_eq!(tokens(&seg, "ផ្សេងៗៗ"), ["ផ្សេងៗ", "ៗ"]);
+}
@@ -0,0 +1,84 @@
+// Regression corpus: every line of `data/regression.tsv` is an input and its
+// expected segmentation joined with " | ", run against the checked-in tiny
+// dictionary `data/tiny.kdict` (compiled from `data/tiny_dict.tsv`).
+//
+// After editing the word list, regenerate the binary (from `port/rust`) with:
+//     cargo run --bin debug_dict -- --build tests/data/tiny_dict.tsv tests/data/tiny.kdict 6.1016 11.1016
+
+use std::fs;
+use std::path::PathBuf;
+
+use khmer_segmenter::kdict::build::build_kdict;
+use khmer_segmenter::kdict::KDict;
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+
+// Header costs of the production dictionary the words were taken from.
+const DEFAULT_COST: f32 = 6.1016;
+const UNKNOWN_COST: f32 = 11.1016;
+
+fn data(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data").join(name)
+}
+
+fn tiny_dict_words() -> Vec<(String, f32)> {
+    fs::read_to_string(data("tiny_dict.tsv"))
+        .unwrap()
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            let (word, cost) = l.split_once('\t').unwrap();
+            (word.to_string(), cost.parse().unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn checked_in_dict_matches_word_list() {
+    let built = build_kdict(&tiny_dict_words(), DEFAULT_COST, UNKNOWN_COST);
+    let checked_in = fs::read(data("tiny.kdict")).unwrap();
+    assert!(
+        built == checked_in,
+        "tiny.kdict is stale; regenerate it with `cargo run --bin debug_dict -- --build \
+         tests/data/tiny_dict.tsv tests/data/tiny.kdict {DEFAULT_COST} {UNKNOWN_COST}`"
+    );
+}
+
+#[test]
+fn regression_corpus() {
+    let kdict = KDict::load(data("tiny.kdict").to_str().unwrap()).unwrap();
+    let seg = KhmerSegmenter::new_with_dict(Some(kdict), SegmenterConfig::default());
+
+    let corpus = fs::read_to_string(data("regression.tsv")).unwrap();
+    let mut failures = Vec::new();
+    for (lineno, line) in corpus.lines().enumerate() {
+        if line.is_empty() || line.starts_with('#') { continue; }
+        let (input, expected) = line.split_once('\t').unwrap();
+        let got = seg.segment(input, Some(" | "));
+        if got != expected {
+            failures.push(format!("line {}: {}\n  expected: {}\n       got: {}", lineno + 1, input, expected, got));
+        }
+    }
+    assert!(failures.is_empty(), "{} regression(s):\n{}", failures.len(), failures.join("\n"));
+}
+
+// Long enough to take the `parallel-scan` path when that feature is on; the
+// result must not depend on how the candidates were computed.
+#[test]
+fn long_document_matches_line_by_line() {
+    let kdict = KDict::load(data("tiny.kdict").to_str().unwrap()).unwrap();
+    let seg = KhmerSegmenter::new_with_dict(Some(kdict), SegmenterConfig::default());
+
+    let corpus = fs::read_to_string(data("regression.tsv")).unwrap();
+    let pairs: Vec<(&str, &str)> = corpus.lines()
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| l.split_once('\t').unwrap())
+        .collect();
+
+    let mut input = Vec::new();
+    let mut expected = Vec::new();
+    while input.join("\n").len() < 64 * 1024 {
+        input.extend(pairs.iter().map(|p| p.0));
+        expected.extend(pairs.iter().map(|p| p.1));
+    }
+    assert_eq!(seg.segment(&input.join("\n"), Some(" | ")), expected.join(" | \n | "));
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+use khmer_segmenter::rule_engine::{QaPrefixMerge, RuleEngine};
+
+const WORDS: &[(&str, f32)] = &[("ទៅ", 3.0)];
+
+fn config(qa_prefix_merge: QaPrefixMerge) -> SegmenterConfig {
+    SegmenterConfig { qa_prefix_merge, enable_unknown_merging: false, ..Default::default() }
+}
+
+#[test]
+fn qa_prefix_merges_into_any_token_by_default() {
+    let seg = segmenter(WORDS, config(QaPrefixMerge::default()));
+    assert_eq!(tokens(&seg, "អទៅ"), ["អទៅ"]);
+    assert_eq!(tokens(&seg, "អគា"), ["អគា"]);
+    assert_eq!(tokens(&seg, "អ ទៅ"), ["អ", " ", "ទៅ"]);
+}
+
+#[test]
+fn qa_prefix_before_known_word_only() {
+    let seg = segmenter(WORDS, config(QaPrefixMerge::BeforeKnownWord));
+    assert_eq!(tokens(&seg, "អទៅ"), ["អទៅ"]);
+    assert_eq!(tokens(&seg, "អគា"), ["អ", "គា"]);
+}
+
+#[test]
+fn qa_prefix_merge_can_be_disabled() {
+    let seg = segmenter(WORDS, config(QaPrefixMerge::Never));
+    assert_eq!(tokens(&seg, "អទៅ"), ["អ", "ទៅ"]);
+    assert_eq!(tokens(&seg, "អគា"), ["អ", "គា"]);
+}
+
+#[test]
+fn sign_rules_never_glue_a_token_onto_a_separator() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    // Rule 2 (suffix sign merges left) at the start of a line and after a space
+    assert_eq!(tokens(&seg, " គ៏ទៅ"), [" ", "គ៏", "ទៅ"]);
+    assert_eq!(tokens(&seg, "ទៅ គ៏"), ["ទៅ", " ", "គ៏"]);
+    // Rule 3 (samyok sannya merges right) before a space or at the end
+    assert_eq!(tokens(&seg, "ស័ ទៅ"), ["ស័", " ", "ទៅ"]);
+    assert_eq!(tokens(&seg, "ទៅស័ "), ["ទៅ", "ស័", " "]);
+}
+
+#[test]
+fn edge_separators_match_interior_ones_and_no_token_is_empty() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let inputs = [" ទៅ", "ទៅ ", "  ទៅ  ទៅ  ", "។ទៅ។", " ។ ", "\u{200B} ទៅ", " គ៏", "គ៏ ", " ស័", "ស័ ", " ៗ", " \u{17D2}"];
+    for text in inputs {
+        let detailed = seg.segment_detailed(text);
+        for t in &detailed {
+            assert!(!t.text.is_empty(), "empty token in {text:?}");
+            let trimmed = t.text.trim();
+            assert!(trimmed.is_empty() || trimmed == t.text, "{:?} mixes whitespace into a token in {text:?}", t.text);
+        }
+        let padded = format!("។{text}។");
+        let interior = tokens(&seg, &padded);
+        let edge = tokens(&seg, text);
+        assert_eq!(interior[1..interior.len() - 1], edge[..], "{text:?}");
+    }
+}
+
+// Each case makes every token fire a rule; with a quadratic engine these
+// take minutes instead of milliseconds.
+#[test]
+fn rule_engine_is_linear_on_adversarial_inputs() {
+    const N: usize = 50_000;
+    let cases = [
+        ("ក", "់", 1),        // rule 2: every sign token merges left into the growing head
+        ("ក", "ន", 1),        // rule 4: every letter merges left
+        ("ក", "ៈ", 1),        // rule 5: every invalid single merges left
+        ("ទៅ", "ស័", 1 + N / 2), // rule 3: merges right once per pair
+    ];
+    for (head, unit, expected) in cases {
+        let text = format!("{head}{}", unit.repeat(N));
+        let mut segments = vec![(0, head.len())];
+        segments.extend((0..N).map(|k| (head.len() + k * unit.len(), head.len() + (k + 1) * unit.len())));
+        RuleEngine::new().apply(&text, &mut segments);
+        assert_eq!(segments.len(), expected, "{unit:?}");
+        assert_eq!(segments.first().unwrap().0, 0);
+        assert_eq!(segments.last().unwrap().1, text.len());
+    }
+}
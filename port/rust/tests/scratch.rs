@@ -0,0 +1,32 @@
+mod common;
+
+use common::segmenter;
+use khmer_segmenter::khmer_segmenter::{Scratch, SegmenterConfig};
+use rayon::prelude::*;
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0), ("ផ្សារ", 3.0)];
+
+#[test]
+fn reused_scratch_matches_fresh_calls() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let long = "ខ្ញុំទៅផ្សារ ".repeat(20_000);
+    // Long, short, empty and long again: nothing leaks between calls
+    let inputs = [long.as_str(), "ទៅផ្សារ", "", "hello ខ្ញុំ", long.as_str(), "ខ្ញុំ"];
+
+    let mut scratch = Scratch::new();
+    for text in inputs {
+        let expected = seg.segment(text, Some("|"));
+        assert_eq!(seg.segment_with_scratch(text, Some("|"), &mut scratch), expected);
+        assert_eq!(seg.segment(text, Some("|")), expected);
+    }
+    assert_eq!(seg.segment("ខ្ញុំទៅផ្សារ", Some("|")), "ខ្ញុំ|ទៅ|ផ្សារ");
+}
+
+#[test]
+fn per_thread_buffers_do_not_mix_lines() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let lines: Vec<String> = (0..2000).map(|i| "ខ្ញុំទៅផ្សារ".repeat(i % 7) + &i.to_string()).collect();
+    let sequential: Vec<String> = lines.iter().map(|l| seg.segment(l, None)).collect();
+    let parallel: Vec<String> = lines.par_iter().map(|l| seg.segment(l, None)).collect();
+    assert_eq!(parallel, sequential);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::segmenter;
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+use khmer_segmenter::utils::Script;
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)];
+
+fn runs(text: &str) -> Vec<(Script, Vec<String>)> {
+    segmenter(WORDS, SegmenterConfig::default()).segment_by_script(text)
+}
+
+fn run(script: Script, tokens: &[&str]) -> (Script, Vec<String>) {
+    (script, tokens.iter().map(|t| t.to_string()).collect())
+}
+
+#[test]
+fn groups_consecutive_tokens_by_script() {
+    assert_eq!(runs("ខ្ញុំទៅ hello world ១២ 😀"), [
+        run(Script::Khmer, &["ខ្ញុំ", "ទៅ", " "]),
+        run(Script::Latin, &["hello", " ", "world", " "]),
+        run(Script::Digits, &["១២", " "]),
+        run(Script::Other, &["😀"]),
+    ]);
+}
+
+#[test]
+fn leading_separators_join_the_first_run() {
+    assert_eq!(runs("« ខ្ញុំ"), [run(Script::Khmer, &["«", " ", "ខ្ញុំ"])]);
+    assert_eq!(runs("..."), [run(Script::Common, &[".", ".", "."])]);
+    assert!(runs("").is_empty());
+}
+
+#[test]
+fn script_of_classifies_characters() {
+    assert_eq!(Script::of('ក'), Script::Khmer);
+    assert_eq!(Script::of('é'), Script::Latin);
+    assert_eq!(Script::of('៥'), Script::Digits);
+    assert_eq!(Script::of('។'), Script::Common);
+    assert_eq!(Script::of('中'), Script::Other);
+}
+
+#[test]
+fn character_predicates_hold_at_range_edges() {
+    use khmer_segmenter::utils::{is_digit_cp, is_separator_cp, is_valid_single_base_char};
+
+    let digits: Vec<char> = ['/', '0', '9', ':', '\u{17DF}', '\u{17E0}', '\u{17E9}', '\u{17EA}'].into_iter().filter(|&c| is_digit_cp(c)).collect();
+    assert_eq!(digits, ['0', '9', '\u{17E0}', '\u{17E9}']);
+
+    // The last currency symbol, the edge of the lookup table and beyond it
+    let seps = ['\u{17D3}', '\u{17D4}', '\u{17DB}', '\u{17DC}', '\u{206F}', '\u{2070}', '\u{20CF}', '\u{20D0}', '\u{20FF}', '\u{2100}', '\u{3001}'];
+    let found: Vec<char> = seps.into_iter().filter(|&c| is_separator_cp(c)).collect();
+    assert_eq!(found, ['\u{17D4}', '\u{17DB}', '\u{206F}', '\u{20CF}']);
+
+    let bases: Vec<char> = ['\u{177F}', '\u{1780}', '\u{17B3}', '\u{17B4}'].into_iter().filter(|&c| is_valid_single_base_char(c)).collect();
+    assert_eq!(bases, ['\u{1780}', '\u{17B3}']);
+}
@@ -0,0 +1,29 @@
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+
+#[test]
+fn uses_first_path_that_loads() {
+    let paths = ["tests/data/missing.kdict", "tests/data/tiny.kdict"];
+    let seg = KhmerSegmenter::new_searching(&paths, SegmenterConfig::default()).unwrap();
+    assert_eq!(seg.segment("ខ្ញុំស្រឡាញ់ប្រទេសកម្ពុជា", Some("|")), "ខ្ញុំ|ស្រឡាញ់|ប្រទេស|កម្ពុជា");
+}
+
+#[test]
+fn error_lists_every_path_tried() {
+    let paths = ["tests/data/missing.kdict", "tests/data/tiny_dict.tsv"];
+    let err = KhmerSegmenter::new_searching(&paths, SegmenterConfig::default()).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    let msg = err.to_string();
+    assert!(msg.contains("tests/data/missing.kdict"), "{}", msg);
+    assert!(msg.contains("tests/data/tiny_dict.tsv: Invalid magic"), "{}", msg);
+}
+
+#[test]
+fn exposes_the_loaded_dictionary() {
+    let seg = KhmerSegmenter::new_searching(&["tests/data/tiny.kdict"], SegmenterConfig::default()).unwrap();
+    let dict = seg.dictionary().unwrap();
+    assert!(dict.contains("ខ្ញុំ"));
+    assert!(!dict.contains("hello"));
+    assert_eq!(dict.words().count(), 285);
+
+    assert!(KhmerSegmenter::new_with_dict(None, SegmenterConfig::default()).dictionary().is_none());
+}
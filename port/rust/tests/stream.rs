@@ -0,0 +1,124 @@
+mod common;
+
+use std::io::{self, Write};
+
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+use khmer_segmenter::stream::{process_lines, ReaderOptions};
+
+#[test]
+fn output_keeps_input_order_across_windows() {
+    let lines: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+    let mut out = Vec::new();
+    let n = process_lines(lines.clone(), &mut out, 7, |l| format!("{}\n", l.len())).unwrap();
+    assert_eq!(n, 1000);
+
+    let expected: String = lines.iter().map(|l| format!("{}\n", l.len())).collect();
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
+
+#[test]
+fn pulls_input_lazily_one_window_at_a_time() {
+    let pulled = std::cell::Cell::new(0);
+    let lines = (0..10).map(|i| {
+        pulled.set(pulled.get() + 1);
+        i.to_string()
+    });
+
+    struct Probe<'a>(&'a std::cell::Cell<usize>, Vec<usize>);
+    impl Write for Probe<'_> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.1.push(self.0.get());
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+
+    let mut probe = Probe(&pulled, Vec::new());
+    process_lines(lines, &mut probe, 4, |l| l.to_string()).unwrap();
+    // Each write happens before the next window is read
+    assert_eq!(probe.1, [4, 4, 4, 4, 8, 8, 8, 8, 10, 10]);
+}
+
+#[test]
+fn write_errors_are_returned() {
+    struct Broken;
+    impl Write for Broken {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> { Err(io::Error::other("disk full")) }
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+    let err = process_lines(vec!["a".to_string()], &mut Broken, 4, |l| l.to_string()).unwrap_err();
+    assert_eq!(err.to_string(), "disk full");
+}
+
+#[test]
+fn empty_input_writes_nothing() {
+    let mut out = Vec::new();
+    assert_eq!(process_lines(Vec::<String>::new(), &mut out, 4, |l| l.to_string()).unwrap(), 0);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn segment_reader_matches_segment_line_by_line() {
+    let seg = common::segmenter(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)], SegmenterConfig::default());
+    let input: String = (0..5000).map(|i| format!("ខ្ញុំទៅ {i}\n")).collect();
+    let mut out = Vec::new();
+    seg.segment_reader(input.as_bytes(), &mut out, Some("|")).unwrap();
+
+    let expected: String = input.lines().map(|l| seg.segment(l, Some("|")) + "\n").collect();
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
+
+#[test]
+fn segment_reader_stops_at_invalid_utf8() {
+    let seg = common::segmenter(&[("ទៅ", 3.0)], SegmenterConfig::default());
+    let mut out = Vec::new();
+    let err = seg.segment_reader(&b"ok\n\xff\nlater\n"[..], &mut out, Some("|")).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(out, b"ok\n");
+}
+
+#[test]
+fn segment_reader_with_can_skip_invalid_utf8() {
+    let seg = common::segmenter(&[("ទៅ", 3.0)], SegmenterConfig::default());
+    let options = ReaderOptions { skip_invalid_utf8: true, ..Default::default() };
+    let mut out = Vec::new();
+    let summary = seg.segment_reader_with(&b"ok\n\xff\r\nlater\r\n"[..], &mut out, Some("|"), options, |_, s| s + "\n").unwrap();
+    assert_eq!(summary.lines, 2);
+    assert!(summary.read_error.is_none());
+    assert_eq!(out, b"ok\nlater\n");
+}
+
+#[test]
+fn segment_reader_with_reports_invalid_utf8_in_the_summary() {
+    let seg = common::segmenter(&[("ទៅ", 3.0)], SegmenterConfig::default());
+    let mut out = Vec::new();
+    let summary = seg.segment_reader_with(&b"ok\n\xff\nlater\n"[..], &mut out, Some("|"), ReaderOptions::default(), |_, s| s + "\n").unwrap();
+    assert_eq!(summary.lines, 1);
+    assert_eq!(summary.read_error.unwrap().kind(), io::ErrorKind::InvalidData);
+    assert_eq!(out, b"ok\n");
+}
+
+#[test]
+fn segment_reader_with_stops_reading_at_max_lines() {
+    let seg = common::segmenter(&[("ទៅ", 3.0)], SegmenterConfig::default());
+    let options = ReaderOptions { max_lines: 2, ..Default::default() };
+    let mut reader = &b"a\nb\nc\nd\n"[..];
+    let mut out = Vec::new();
+    let summary = seg.segment_reader_with(&mut reader, &mut out, Some("|"), options, |_, s| s + "\n").unwrap();
+    assert_eq!(summary.lines, 2);
+    assert_eq!(out, b"a\nb\n");
+    // The rest is left unread
+    assert_eq!(reader, b"c\nd\n");
+}
+
+#[test]
+fn segment_reader_with_strips_a_leading_bom_and_renders_records() {
+    let seg = common::segmenter(&[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)], SegmenterConfig::default());
+    let options = ReaderOptions { strip_bom: true, ..Default::default() };
+    let mut out = Vec::new();
+    let input = "\u{FEFF}ខ្ញុំទៅ\n\u{FEFF}ទៅ\n";
+    seg.segment_reader_with(input.as_bytes(), &mut out, Some("|"), options, |original, s| format!("{}\t{}\n", original, s)).unwrap();
+    // Only the BOM at the start of the input is dropped before `render` sees
+    // the line; later ones reach it and are removed by normalization
+    assert_eq!(String::from_utf8(out).unwrap(), "ខ្ញុំទៅ\tខ្ញុំ|ទៅ\n\u{FEFF}ទៅ\tទៅ\n");
+}
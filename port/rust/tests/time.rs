@@ -0,0 +1,54 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::{SegmenterConfig, TokenKind};
+use khmer_segmenter::utils::get_time_length;
+
+const WORDS: &[(&str, f32)] = &[("ម៉ោង", 3.0)];
+
+fn with_times() -> SegmenterConfig {
+    SegmenterConfig { enable_time_detection: true, ..Default::default() }
+}
+
+#[test]
+fn time_length_accepts_clock_times() {
+    assert_eq!(get_time_length("10:30"), 5);
+    assert_eq!(get_time_length("9:05 am"), 4);
+    assert_eq!(get_time_length("23:59:59"), 8);
+    assert_eq!(get_time_length("១០:៣០"), "១០:៣០".len());
+}
+
+#[test]
+fn time_length_rejects_non_times() {
+    assert_eq!(get_time_length("10"), 0);
+    assert_eq!(get_time_length("10:"), 0);
+    assert_eq!(get_time_length("10:3"), 0);
+    assert_eq!(get_time_length("10:300"), 0);
+    assert_eq!(get_time_length("10:75"), 0);
+    assert_eq!(get_time_length("123:45"), 0);
+    assert_eq!(get_time_length("abc"), 0);
+}
+
+#[test]
+fn time_length_leaves_trailing_colon() {
+    assert_eq!(get_time_length("10:30:"), 5);
+    assert_eq!(get_time_length("10:30:1"), 5);
+}
+
+#[test]
+fn times_are_one_token_when_enabled() {
+    let seg = segmenter(WORDS, with_times());
+    assert_eq!(tokens(&seg, "ម៉ោង១០:៣០"), ["ម៉ោង", "១០:៣០"]);
+    assert_eq!(tokens(&seg, "10:30:15 ok"), ["10:30:15", " ", "ok"]);
+    assert_eq!(tokens(&seg, "10:30:"), ["10:30", ":"]);
+    assert_eq!(tokens(&seg, "ម៉ោង: ១០"), ["ម៉ោង", ":", " ", "១០"]);
+
+    let detailed = seg.segment_detailed("១០:៣០");
+    assert_eq!(detailed[0].kind, TokenKind::Time);
+}
+
+#[test]
+fn colon_splits_times_by_default() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "១០:៣០"), ["១០", ":", "៣០"]);
+}
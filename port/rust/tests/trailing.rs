@@ -0,0 +1,37 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::{SegmenterConfig, TokenKind, TrailingCoeng};
+
+const WORDS: &[(&str, f32)] = &[("បាន", 3.0)];
+
+fn with(trailing_coeng: TrailingCoeng) -> SegmenterConfig {
+    SegmenterConfig { trailing_coeng, ..Default::default() }
+}
+
+#[test]
+fn keep_merges_eof_coeng_into_unknown_run() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "បានក្"), ["បាន", "ក\u{17D2}"]);
+    assert_eq!(tokens(&seg, "បាន\u{17D2}"), ["បាន\u{17D2}"]);
+}
+
+#[test]
+fn drop_removes_eof_coeng() {
+    let seg = segmenter(WORDS, with(TrailingCoeng::Drop));
+    assert_eq!(tokens(&seg, "បានក្"), ["បាន", "ក"]);
+    assert_eq!(tokens(&seg, "បាន\u{17D2}\u{17D2}"), ["បាន"]);
+    assert_eq!(seg.segment("\u{17D2}", Some("|")), "");
+    // Only the end of the input is affected
+    assert_eq!(tokens(&seg, "ក្ បាន"), ["ក\u{17D2}", " ", "បាន"]);
+}
+
+#[test]
+fn flag_keeps_eof_coeng_as_its_own_token() {
+    let seg = segmenter(WORDS, with(TrailingCoeng::Flag));
+    assert_eq!(tokens(&seg, "បាន\u{17D2}"), ["បាន", "\u{17D2}"]);
+
+    let detailed = seg.segment_detailed("បានក្");
+    let kinds: Vec<_> = detailed.iter().map(|t| (t.text.as_str(), t.kind)).collect();
+    assert_eq!(kinds, [("បាន", TokenKind::Word), ("ក", TokenKind::Unknown), ("\u{17D2}", TokenKind::Incomplete)]);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::{SegmenterConfig, TokenKind};
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0)];
+
+#[test]
+fn added_words_are_matched_like_dictionary_words() {
+    let mut seg = segmenter(WORDS, SegmenterConfig { enable_unknown_merging: false, ..Default::default() });
+    assert_eq!(tokens(&seg, "ខ្ញុំទៅភ្នំពេញ"), ["ខ្ញុំ", "ទៅ", "ភ្នំ", "ពេ", "ញ"]);
+
+    seg.add_word("ភ្នំពេញ", 4.0);
+    assert_eq!(tokens(&seg, "ខ្ញុំទៅភ្នំពេញ"), ["ខ្ញុំ", "ទៅ", "ភ្នំពេញ"]);
+    let token = seg.segment_detailed("ភ្នំពេញ").remove(0);
+    assert_eq!((token.kind, token.cost, token.slot), (TokenKind::Word, Some(4.0), None));
+
+    // Clones and reconfigured copies keep them
+    assert_eq!(tokens(&seg.clone(), "ភ្នំពេញ"), ["ភ្នំពេញ"]);
+    assert_eq!(tokens(&seg.with_config(SegmenterConfig::default()), "ភ្នំពេញ"), ["ភ្នំពេញ"]);
+}
+
+#[test]
+fn added_words_longer_than_any_dictionary_word_are_reachable() {
+    let mut seg = segmenter(WORDS, SegmenterConfig::default());
+    let long = "សាកលវិទ្យាល័យភូមិន្ទភ្នំពេញ";
+    seg.add_word(long, 5.0);
+    assert_eq!(tokens(&seg, &format!("ខ្ញុំទៅ{long}")), ["ខ្ញុំ", "ទៅ", long]);
+}
+
+#[test]
+fn added_words_count_as_known_when_merging_unknowns() {
+    let mut seg = segmenter(WORDS, SegmenterConfig::default());
+    // Both unknown: merged into one run
+    assert_eq!(tokens(&seg, "ឆ្កែឆ្មា"), ["ឆ្កែឆ្មា"]);
+    // Re-adding replaces the cost; the word then stands apart from the run
+    seg.add_word("ឆ្កែ", 40.0);
+    seg.add_word("ឆ្កែ", 4.0);
+    assert_eq!(tokens(&seg, "ឆ្កែឆ្មា"), ["ឆ្កែ", "ឆ្មា"]);
+}
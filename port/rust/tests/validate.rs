@@ -0,0 +1,29 @@
+use khmer_segmenter::utils::validate_khmer_clusters;
+
+#[test]
+fn well_formed_text_passes() {
+    assert_eq!(validate_khmer_clusters("ខ្ញុំស្រឡាញ់ប្រទេសកម្ពុជា"), Ok(()));
+    assert_eq!(validate_khmer_clusters("ស្ត្រី ១២៣។ abc"), Ok(()));
+    assert_eq!(validate_khmer_clusters(""), Ok(()));
+}
+
+#[test]
+fn sign_without_base_is_reported() {
+    assert_eq!(validate_khmer_clusters("\u{17B6}ក"), Err(0));
+    assert_eq!(validate_khmer_clusters("ក \u{17C6}"), Err(4));
+    // After the cluster was closed by a digit
+    assert_eq!(validate_khmer_clusters("ក១\u{17B6}"), Err(6));
+}
+
+#[test]
+fn coeng_needs_a_consonant() {
+    assert_eq!(validate_khmer_clusters("ក\u{17D2}"), Err(3));
+    assert_eq!(validate_khmer_clusters("ក\u{17D2}\u{17B6}"), Err(3));
+    assert_eq!(validate_khmer_clusters("ក\u{17D2}a"), Err(3));
+    assert_eq!(validate_khmer_clusters("\u{17D2}ក"), Err(0));
+}
+
+#[test]
+fn deprecated_inherent_vowels_are_reported() {
+    assert_eq!(validate_khmer_clusters("ក\u{17B4}"), Err(3));
+}
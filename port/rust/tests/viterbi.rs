@@ -0,0 +1,99 @@
+mod common;
+
+use common::{segmenter, tokens};
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+use khmer_segmenter::viterbi::{viterbi, viterbi_nbest, Transition, Transitions};
+
+// Every position can step 1 or 2 bytes at the given costs.
+struct Steps {
+    one: f32,
+    two: f32,
+}
+
+impl Transitions for Steps {
+    type Kind = ();
+
+    fn transitions<F: FnMut(Transition<()>)>(&self, pos: usize, mut emit: F) {
+        emit(Transition { next: pos + 1, cost: self.one, kind: () });
+        emit(Transition { next: pos + 2, cost: self.two, kind: () });
+    }
+}
+
+#[test]
+fn cheapest_path_wins() {
+    assert_eq!(viterbi(Steps { one: 1.0, two: 5.0 }, 3), [(0, 1), (1, 2), (2, 3)]);
+}
+
+#[test]
+fn equal_cost_prefers_fewer_tokens() {
+    // Two 1-byte steps cost the same as one 2-byte step.
+    assert_eq!(viterbi(Steps { one: 1.0, two: 2.0 }, 4), [(0, 2), (2, 4)]);
+}
+
+#[test]
+fn equal_cost_and_count_prefers_longer_final_token() {
+    // 1+2 and 2+1 tie on cost and token count; the path ending in the longer
+    // token wins regardless of emission order.
+    assert_eq!(viterbi(Steps { one: 1.5, two: 1.5 }, 3), [(0, 1), (1, 3)]);
+}
+
+#[test]
+fn unreachable_end_yields_no_spans() {
+    struct Nothing;
+    impl Transitions for Nothing {
+        type Kind = ();
+        fn transitions<F: FnMut(Transition<()>)>(&self, _pos: usize, _emit: F) {}
+    }
+    assert!(viterbi(Nothing, 4).is_empty());
+}
+
+#[test]
+fn segmenter_breaks_dictionary_ties_toward_the_compound() {
+    let seg = segmenter(&[("ក", 1.0), ("ខ", 1.0), ("កខ", 2.0)], SegmenterConfig::default());
+    assert_eq!(tokens(&seg, "កខ"), ["កខ"]);
+}
+
+#[test]
+fn nbest_lists_paths_cheapest_first_with_stable_ties() {
+    let paths = viterbi_nbest(Steps { one: 1.0, two: 5.0 }, 3, 5);
+    assert_eq!(paths, [
+        (3.0, vec![(0, 1), (1, 2), (2, 3)]),
+        // Tied on cost and count: the longer final token first, as in `viterbi`
+        (6.0, vec![(0, 1), (1, 3)]),
+        (6.0, vec![(0, 2), (2, 3)]),
+    ]);
+    for (one, two) in [(1.0, 2.0), (1.5, 1.5), (1.0, 5.0)] {
+        assert_eq!(viterbi_nbest(Steps { one, two }, 7, 1)[0].1, viterbi(Steps { one, two }, 7));
+    }
+    assert_eq!(viterbi_nbest(Steps { one: 1.0, two: 5.0 }, 6, 4).len(), 4);
+    assert!(viterbi_nbest(Steps { one: 1.0, two: 5.0 }, 0, 4).is_empty());
+}
+
+#[test]
+fn nbest_counts_a_span_reached_by_two_kinds_once() {
+    struct Twice;
+    impl Transitions for Twice {
+        type Kind = u8;
+        fn transitions<F: FnMut(Transition<u8>)>(&self, pos: usize, mut emit: F) {
+            emit(Transition { next: pos + 1, cost: 2.0, kind: 0 });
+            emit(Transition { next: pos + 1, cost: 1.0, kind: 1 });
+        }
+    }
+    assert_eq!(viterbi_nbest(Twice, 2, 3), [(2.0, vec![(0, 1), (1, 2)])]);
+}
+
+#[test]
+fn segmenter_nbest_runs_the_passes_per_path() {
+    let seg = segmenter(&[("ក", 1.0), ("ខ", 1.0), ("កខ", 2.0), ("ទៅ", 3.0)], SegmenterConfig::default());
+    let text = "កខទៅ";
+    let best = seg.segment_nbest(text, 3);
+    assert_eq!(best[0], (5.0, seg.segment_spans(text).0));
+    assert_eq!(best[1], (5.0, vec![(0, 3), (3, 6), (6, 12)]));
+    assert!(best.windows(2).all(|w| w[0].0 <= w[1].0));
+    assert_eq!(seg.segment_nbest(text, 3), best);
+
+    // Every path folds the stray sign into the token before it, so the
+    // paths collapse into one result
+    assert_eq!(seg.segment_nbest("ទៅ៍", 10), [(15.0, vec![(0, 9)])]);
+    assert!(seg.segment_nbest(text, 0).is_empty());
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{dict, segmenter};
+use khmer_segmenter::khmer_segmenter::SegmenterConfig;
+use khmer_segmenter::vocab::Vocab;
+
+const WORDS: &[(&str, f32)] = &[("ខ្ញុំ", 3.0), ("ទៅ", 3.0), ("ផ្ទះ", 3.0)];
+
+#[test]
+fn vocab_from_dict_numbers_sorted_words_after_specials() {
+    let vocab = Vocab::from_dict(&dict(WORDS));
+    assert_eq!(vocab.len(), 6);
+    assert_eq!(vocab.token(Vocab::UNK), Some("<unk>"));
+    let ids: Vec<u32> = ["ខ្ញុំ", "ទៅ", "ផ្ទះ"].iter().map(|w| vocab.get(w).unwrap()).collect();
+    assert_eq!(ids, [3, 4, 5]);
+    assert_eq!(vocab.get("ការ"), None);
+}
+
+#[test]
+fn segment_ids_maps_kinds_to_special_ids() {
+    let seg = segmenter(WORDS, SegmenterConfig::default());
+    let mut vocab = Vocab::from_dict(seg.dictionary().unwrap());
+    let space = vocab.insert(" ");
+    let (me, go, home) = (vocab.get("ខ្ញុំ").unwrap(), vocab.get("ទៅ").unwrap(), vocab.get("ផ្ទះ").unwrap());
+
+    assert_eq!(seg.segment_ids("ខ្ញុំទៅផ្ទះ", &vocab), [me, go, home]);
+    assert_eq!(
+        seg.segment_ids("ខ្ញុំ ១២ ក.ខ. ទៅ។", &vocab),
+        [me, space, Vocab::NUMBER, space, Vocab::ACRONYM, space, go, Vocab::UNK]
+    );
+    // Unknown clusters are UNK even when the text was added to the vocabulary
+    vocab.insert("ឆ");
+    assert_eq!(seg.segment_ids("ឆ", &vocab), [Vocab::UNK]);
+}
+
+#[test]
+fn insert_is_idempotent() {
+    let mut vocab = Vocab::new();
+    let id = vocab.insert("<pad>");
+    assert_eq!(id, 3);
+    assert_eq!(vocab.insert("<pad>"), id);
+    assert_eq!(vocab.len(), 4);
+}
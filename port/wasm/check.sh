@@ -0,0 +1,9 @@
+#!/bin/sh
+# Type-checks the core crate and this binding for the browser target, then
+# runs the wasm-bindgen tests under Node. Needs the wasm32-unknown-unknown
+# target and wasm-pack.
+set -e
+cd "$(dirname "$0")"
+(cd ../rust && cargo check --lib --target wasm32-unknown-unknown --features serde)
+cargo check --target wasm32-unknown-unknown
+wasm-pack test --node
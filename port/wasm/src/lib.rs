@@ -0,0 +1,37 @@
+// JavaScript bindings for the Rust port, built for the browser with wasm-pack.
+
+use khmer_segmenter::kdict::KDict;
+use khmer_segmenter::khmer_segmenter::{KhmerSegmenter, SegmenterConfig};
+use wasm_bindgen::prelude::*;
+
+/// A segmenter over a dictionary image the caller has fetched (there is no
+/// file access in the browser).
+#[wasm_bindgen]
+pub struct WasmSegmenter {
+    inner: KhmerSegmenter,
+}
+
+#[wasm_bindgen]
+impl WasmSegmenter {
+    /// `new WasmSegmenter(dict, config)`: `dict` is the bytes of a `.kdict`
+    /// file, copied into wasm memory. `config` is an optional JSON object of
+    /// `SegmenterConfig` fields; fields it leaves out keep their defaults.
+    ///
+    /// Throws when the dictionary fails validation or the JSON does not
+    /// parse as a config.
+    #[wasm_bindgen(constructor)]
+    pub fn new(dict: &[u8], config: Option<String>) -> Result<WasmSegmenter, JsError> {
+        let config = match config {
+            Some(json) => serde_json::from_str::<SegmenterConfig>(&json)?,
+            None => SegmenterConfig::default(),
+        };
+        let kdict = KDict::from_bytes(dict.to_vec())?;
+        Ok(WasmSegmenter { inner: KhmerSegmenter::new_with_dict(Some(kdict), config) })
+    }
+
+    /// `text` with `separator` between tokens; without one, the config's
+    /// `default_separator` or U+200B ZERO WIDTH SPACE.
+    pub fn segment(&self, text: &str, separator: Option<String>) -> String {
+        self.inner.segment(text, separator.as_deref())
+    }
+}
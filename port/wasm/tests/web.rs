@@ -0,0 +1,35 @@
+// Run with `wasm-pack test --node` (see README.md).
+
+use khmer_segmenter_wasm::WasmSegmenter;
+use wasm_bindgen_test::*;
+
+// The regression dictionary checked in with the Rust port
+const TINY_KDICT: &[u8] = include_bytes!("../../rust/tests/data/tiny.kdict");
+
+const TEXT: &str = "ខ្ញុំស្រឡាញ់ប្រទេសកម្ពុជា";
+
+fn segmenter(config: Option<&str>) -> WasmSegmenter {
+    let Ok(seg) = WasmSegmenter::new(TINY_KDICT, config.map(str::to_string)) else {
+        panic!("tiny.kdict failed to load");
+    };
+    seg
+}
+
+#[wasm_bindgen_test]
+fn segments_with_the_given_separator() {
+    let seg = segmenter(None);
+    assert_eq!(seg.segment(TEXT, Some(" | ".to_string())), "ខ្ញុំ | ស្រឡាញ់ | ប្រទេស | កម្ពុជា");
+    assert_eq!(seg.segment(TEXT, None), "ខ្ញុំ\u{200B}ស្រឡាញ់\u{200B}ប្រទេស\u{200B}កម្ពុជា");
+}
+
+#[wasm_bindgen_test]
+fn config_json_sets_the_default_separator() {
+    let seg = segmenter(Some(r#"{"default_separator": "/"}"#));
+    assert_eq!(seg.segment(TEXT, None), "ខ្ញុំ/ស្រឡាញ់/ប្រទេស/កម្ពុជា");
+}
+
+#[wasm_bindgen_test]
+fn bad_input_throws() {
+    assert!(WasmSegmenter::new(b"not a kdict", None).is_err());
+    assert!(WasmSegmenter::new(TINY_KDICT, Some("{".to_string())).is_err());
+}